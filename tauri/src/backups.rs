@@ -0,0 +1,249 @@
+// Whole-mod snapshots to roll back a batch operation. A backup is a zip of
+// what an export would include, stored as `.r5vbackups/<timestamp>.zip`
+// inside the mod, where the timestamp is the backup time in milliseconds
+// and doubles as its id. Being hidden, the folder never ends up in an
+// export or in a backup of its own.
+
+use crate::versions::{self, VersionRetention};
+use crate::{export, paths};
+use serde::Serialize;
+use std::fs;
+use std::io;
+use std::ops::ControlFlow;
+use std::path::{Path, PathBuf};
+
+pub const BACKUPS_DIR: &str = ".r5vbackups";
+const BACKUP_EXTENSION: &str = "zip";
+
+#[derive(Debug, Serialize)]
+pub struct ModBackup {
+    pub timestamp: u64,
+    // Size of the archive
+    pub size: u64,
+}
+
+fn backup_path(mod_root: &Path, timestamp: u64) -> PathBuf {
+    mod_root
+        .join(BACKUPS_DIR)
+        .join(format!("{}.{}", timestamp, BACKUP_EXTENSION))
+}
+
+/// Backups of the mod, newest first.
+pub fn list(mod_root: &Path) -> Result<Vec<ModBackup>, String> {
+    let dir = mod_root.join(BACKUPS_DIR);
+    let entries = match fs::read_dir(&dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(format!("Failed to read {}: {}", dir.display(), e)),
+    };
+    let mut backups: Vec<ModBackup> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let path = entry.path();
+            if path.extension()? != BACKUP_EXTENSION {
+                return None;
+            }
+            let timestamp = path.file_stem()?.to_str()?.parse().ok()?;
+            let size = entry.metadata().ok()?.len();
+            Some(ModBackup { timestamp, size })
+        })
+        .collect();
+    backups.sort_by_key(|b| std::cmp::Reverse(b.timestamp));
+    Ok(backups)
+}
+
+/// Removes the backups `retention` doesn't keep, judging age against the
+/// newest backup, which always stays. Returns the removed backups.
+pub fn prune(mod_root: &Path, retention: &VersionRetention) -> Result<Vec<ModBackup>, String> {
+    let mut backups = list(mod_root)?.into_iter();
+    let Some(newest) = backups.next() else {
+        return Ok(Vec::new());
+    };
+    let mut removed = Vec::new();
+    for (index, old) in backups.enumerate() {
+        if !retention.keeps(index, old.timestamp, newest.timestamp) {
+            let path = backup_path(mod_root, old.timestamp);
+            fs::remove_file(&path)
+                .map_err(|e| format!("Failed to remove {}: {}", path.display(), e))?;
+            removed.push(old);
+        }
+    }
+    Ok(removed)
+}
+
+fn write_backup(mod_root: &Path) -> Result<ModBackup, String> {
+    let files = export::collect_files(mod_root)
+        .map_err(|e| format!("Failed to read {}: {}", mod_root.display(), e))?;
+    let dir = mod_root.join(BACKUPS_DIR);
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+
+    let mut timestamp = versions::now_ms();
+    // Two backups within the same millisecond still get distinct ids
+    if let Some(latest) = list(mod_root)?.first() {
+        timestamp = timestamp.max(latest.timestamp + 1);
+    }
+    let path = backup_path(mod_root, timestamp);
+    export::write_zip(&files, None, &path, &mut |_, _| ControlFlow::Continue(()))?;
+    let size = fs::metadata(&path).map_err(|e| e.to_string())?.len();
+    Ok(ModBackup { timestamp, size })
+}
+
+/// Zips the mod into a new backup, then prunes by `retention`. The new
+/// backup is always kept.
+pub fn create(mod_root: &Path, retention: &VersionRetention) -> Result<ModBackup, String> {
+    let backup = write_backup(mod_root)?;
+    prune(mod_root, retention)?;
+    Ok(backup)
+}
+
+// Moves a file, creating the folders `to` needs
+fn move_file(from: &Path, to: &Path) -> io::Result<()> {
+    if let Some(parent) = to.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::rename(from, to)
+}
+
+// Removes `dir` and then its parents, up to but not including `root`, for as
+// long as they are empty
+fn remove_empty_dirs(root: &Path, dir: &Path) {
+    let mut dir = dir;
+    while dir != root && dir.starts_with(root) && fs::remove_dir(dir).is_ok() {
+        match dir.parent() {
+            Some(parent) => dir = parent,
+            None => break,
+        }
+    }
+}
+
+// Writes the backup's files under `staging`
+fn extract(
+    archive: &mut zip::ZipArchive<fs::File>,
+    entries: &[(usize, PathBuf)],
+    staging: &Path,
+) -> Result<(), String> {
+    for (index, relative) in entries {
+        let target = staging.join(relative);
+        let name = relative.display();
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+        }
+        let mut entry = archive.by_index(*index).map_err(|e| e.to_string())?;
+        let mut output =
+            fs::File::create(&target).map_err(|e| format!("Failed to write {}: {}", name, e))?;
+        io::copy(&mut entry, &mut output)
+            .map_err(|e| format!("Failed to write {}: {}", name, e))?;
+    }
+    Ok(())
+}
+
+// Moves the mod's current files into `previous` and the staged ones into
+// place. On failure everything moved so far is moved back; `previous` is
+// only kept when some file couldn't be, so nothing is lost.
+fn swap_in(mod_root: &Path, staging: &Path, previous: &Path) -> Result<(), String> {
+    let current = export::collect_files(mod_root).map_err(|e| e.to_string())?;
+    let staged = paths::walk_files(staging).map_err(|e| e.to_string())?;
+    let mut set_aside: Vec<&str> = Vec::new();
+    let mut placed: Vec<String> = Vec::new();
+
+    let result = (|| {
+        for file in &current {
+            move_file(&file.path, &previous.join(&file.relative))
+                .map_err(|e| format!("Failed to remove {}: {}", file.relative, e))?;
+            set_aside.push(&file.relative);
+        }
+        for path in &staged {
+            let Some(relative) = paths::relative_slash_path(staging, path) else {
+                continue;
+            };
+            move_file(path, &mod_root.join(&relative))
+                .map_err(|e| format!("Failed to write {}: {}", relative, e))?;
+            placed.push(relative);
+        }
+        Ok(())
+    })();
+
+    if let Err(e) = result {
+        for relative in &placed {
+            let target = mod_root.join(relative);
+            let _ = fs::remove_file(&target);
+            if let Some(parent) = target.parent() {
+                remove_empty_dirs(mod_root, parent);
+            }
+        }
+        let stuck = set_aside
+            .iter()
+            .filter(|relative| {
+                move_file(&previous.join(relative), &mod_root.join(relative)).is_err()
+            })
+            .count();
+        if stuck > 0 {
+            return Err(format!(
+                "{}, and {} files couldn't be put back; they are in {}",
+                e,
+                stuck,
+                previous.display()
+            ));
+        }
+        let _ = fs::remove_dir_all(previous);
+        return Err(e);
+    }
+    // Folders only the replaced files kept alive
+    for file in &current {
+        if let Some(parent) = file.path.parent() {
+            remove_empty_dirs(mod_root, parent);
+        }
+    }
+    let _ = fs::remove_dir_all(previous);
+    Ok(())
+}
+
+/// Puts the mod back the way backup `timestamp` recorded it: files the
+/// backup doesn't have are removed, along with folders left empty, and the
+/// rest replaced. Hidden files, which backups leave out, are not touched.
+/// The backup is extracted to a staging folder first and only swapped in once
+/// complete; if the swap fails, the mod is put back as it was. The current
+/// state is backed up first and returned, so a restore can itself be undone,
+/// and its id is part of any later error. Nothing is pruned, so the backup
+/// being restored can't go away midway.
+pub fn restore(mod_root: &Path, timestamp: u64) -> Result<ModBackup, String> {
+    let path = backup_path(mod_root, timestamp);
+    let file = fs::File::open(&path).map_err(|e| match e.kind() {
+        io::ErrorKind::NotFound => format!("No backup {} of {}", timestamp, mod_root.display()),
+        _ => e.to_string(),
+    })?;
+    let mut archive = zip::ZipArchive::new(file)
+        .map_err(|e| format!("Backup {} can't be read: {}", timestamp, e))?;
+    // Every name is checked before anything is changed
+    let mut entries = Vec::with_capacity(archive.len());
+    for index in 0..archive.len() {
+        let entry = archive.by_index(index).map_err(|e| e.to_string())?;
+        let relative = entry
+            .enclosed_name()
+            .ok_or_else(|| format!("Backup {} has an unsafe path {}", timestamp, entry.name()))?;
+        if !entry.is_dir() {
+            entries.push((index, relative));
+        }
+    }
+
+    let safety = write_backup(mod_root)?;
+    // Inside the hidden backups folder, so on the same volume as the mod and
+    // never collected as part of it
+    let staging = mod_root
+        .join(BACKUPS_DIR)
+        .join(format!("{}.restoring", safety.timestamp));
+    let previous = mod_root
+        .join(BACKUPS_DIR)
+        .join(format!("{}.replaced", safety.timestamp));
+    let result = extract(&mut archive, &entries, &staging)
+        .and_then(|_| swap_in(mod_root, &staging, &previous));
+    let _ = fs::remove_dir_all(&staging);
+    result.map_err(|e| {
+        format!(
+            "Failed to restore backup {}: {}. The mod as it was before is in backup {}",
+            timestamp, e, safety.timestamp
+        )
+    })?;
+    Ok(safety)
+}
@@ -0,0 +1,93 @@
+// Comparing the files of two mods, e.g. a fork against its upstream.
+
+use crate::diff::{self, LineKind};
+use crate::export::{self, ExportFile};
+use crate::file_type;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+#[derive(Debug, Serialize)]
+pub struct ChangedFile {
+    // Forward-slash path relative to both mod roots
+    pub path: String,
+    pub size_a: u64,
+    pub size_b: u64,
+    // Binary files are only reported as changed, without line counts
+    pub binary: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub added_lines: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub removed_lines: Option<usize>,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct FileComparison {
+    pub only_in_a: Vec<String>,
+    pub only_in_b: Vec<String>,
+    pub changed: Vec<ChangedFile>,
+    pub unchanged: usize,
+}
+
+fn collect(root: &Path) -> io::Result<BTreeMap<String, ExportFile>> {
+    Ok(export::collect_mod_files(root)?
+        .into_iter()
+        .map(|file| (file.relative.clone(), file))
+        .collect())
+}
+
+// Line counts for two differing text files; None if either isn't UTF-8
+fn changed_lines(a: &Path, b: &Path) -> Option<(usize, usize)> {
+    let old = fs::read_to_string(a).ok()?;
+    let new = fs::read_to_string(b).ok()?;
+    let (mut added, mut removed) = (0, 0);
+    for line in diff::diff_hunks(&old, &new, 0)
+        .iter()
+        .flat_map(|h| &h.lines)
+    {
+        match line.kind {
+            LineKind::Added => added += 1,
+            LineKind::Removed => removed += 1,
+            LineKind::Context => {}
+        }
+    }
+    Some((added, removed))
+}
+
+/// Compares the files of two mod roots. Files in both count as changed when
+/// their sizes or SHA-256 hashes differ.
+pub fn compare(root_a: &Path, root_b: &Path) -> io::Result<FileComparison> {
+    let files_a = collect(root_a)?;
+    let mut files_b = collect(root_b)?;
+    let mut comparison = FileComparison::default();
+
+    for (relative, a) in files_a {
+        let Some(b) = files_b.remove(&relative) else {
+            comparison.only_in_a.push(relative);
+            continue;
+        };
+        if a.size == b.size && export::hash_file(&a.path)? == export::hash_file(&b.path)? {
+            comparison.unchanged += 1;
+            continue;
+        }
+        let binary = !file_type::detect_file_type(&a.path).is_text()
+            || !file_type::detect_file_type(&b.path).is_text();
+        let lines = if binary {
+            None
+        } else {
+            changed_lines(&a.path, &b.path)
+        };
+        comparison.changed.push(ChangedFile {
+            path: relative,
+            size_a: a.size,
+            size_b: b.size,
+            binary: binary || lines.is_none(),
+            added_lines: lines.map(|(added, _)| added),
+            removed_lines: lines.map(|(_, removed)| removed),
+        });
+    }
+    comparison.only_in_b = files_b.into_keys().collect();
+    Ok(comparison)
+}
@@ -0,0 +1,238 @@
+// Checking a mod against what a given game version expects of `mod.vdf` and
+// `manifest.json`.
+//
+// Each rule has an id the UI can key on and the range of game versions it
+// applies to, so a requirement introduced or dropped by a build only fires
+// for the versions that have it. Versions compare numerically component by
+// component, with missing components counting as zero.
+
+use crate::{manifest, vdf};
+use serde::Serialize;
+use serde_json::Value;
+use std::cmp::Ordering;
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    // The game rejects or misloads the mod
+    Error,
+    // Loads, but probably not as intended
+    Warning,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Finding {
+    pub rule: &'static str,
+    pub severity: Severity,
+    pub message: String,
+}
+
+struct ModFiles {
+    // The mod's block in mod.vdf and its key; None when missing or malformed
+    vdf: Option<(String, vdf::Value)>,
+    // None without a manifest.json
+    manifest: Option<Result<Value, String>>,
+}
+
+struct Rule {
+    id: &'static str,
+    // First version the rule applies to, and the first it no longer does
+    since: Option<&'static str>,
+    until: Option<&'static str>,
+    check: fn(&ModFiles) -> Vec<(Severity, String)>,
+}
+
+// Top-level manifest fields the studio and the game know about
+const MANIFEST_FIELDS: &[&str] = &[
+    "name",
+    "description",
+    "version",
+    "author",
+    "modId",
+    "scripts",
+    "rpaks",
+    "audio",
+    "localization",
+];
+
+const RULES: &[Rule] = &[
+    Rule {
+        id: "vdf-present",
+        since: None,
+        until: None,
+        check: |files| match files.vdf {
+            Some(_) => Vec::new(),
+            None => vec![(
+                Severity::Error,
+                "mod.vdf is missing or could not be parsed".to_string(),
+            )],
+        },
+    },
+    Rule {
+        id: "vdf-required-keys",
+        since: None,
+        until: None,
+        check: |files| {
+            let Some((_, block)) = &files.vdf else {
+                return Vec::new();
+            };
+            ["Name", "Version"]
+                .into_iter()
+                .filter(|key| block.get_str(key).is_none_or(|v| v.trim().is_empty()))
+                .map(|key| (Severity::Error, format!("mod.vdf has no {} value", key)))
+                .collect()
+        },
+    },
+    Rule {
+        id: "vdf-required-on-client",
+        since: None,
+        until: None,
+        check: |files| {
+            let Some((_, block)) = &files.vdf else {
+                return Vec::new();
+            };
+            match block.get_str("RequiredOnClient") {
+                None => vec![(
+                    Severity::Warning,
+                    "mod.vdf doesn't say whether clients need the mod (RequiredOnClient)"
+                        .to_string(),
+                )],
+                Some("0") | Some("1") => Vec::new(),
+                Some(other) => vec![(
+                    Severity::Error,
+                    format!("RequiredOnClient must be 0 or 1, not {:?}", other),
+                )],
+            }
+        },
+    },
+    Rule {
+        id: "manifest-parses",
+        since: None,
+        until: None,
+        check: |files| match &files.manifest {
+            Some(Err(e)) => vec![(Severity::Error, e.clone())],
+            Some(Ok(manifest)) if !manifest.is_object() => vec![(
+                Severity::Error,
+                "manifest.json must hold an object".to_string(),
+            )],
+            _ => Vec::new(),
+        },
+    },
+    Rule {
+        id: "manifest-section-shapes",
+        since: None,
+        until: None,
+        check: |files| {
+            let Some(Ok(manifest)) = &files.manifest else {
+                return Vec::new();
+            };
+            let mut findings: Vec<(Severity, String)> = ["scripts", "rpaks", "audio"]
+                .into_iter()
+                .filter(|key| manifest.get(key).is_some_and(|v| !v.is_array()))
+                .map(|key| (Severity::Error, format!("\"{}\" must be a list", key)))
+                .collect();
+            if manifest.get("localization").is_some_and(|v| !v.is_object()) {
+                findings.push((
+                    Severity::Error,
+                    "\"localization\" must map languages to files".to_string(),
+                ));
+            }
+            findings
+        },
+    },
+    Rule {
+        id: "manifest-mod-id",
+        since: None,
+        until: None,
+        check: |files| {
+            let (Some((vdf_key, _)), Some(Ok(manifest))) = (&files.vdf, &files.manifest) else {
+                return Vec::new();
+            };
+            match manifest.get("modId").and_then(Value::as_str) {
+                Some(id) if id != vdf_key => vec![(
+                    Severity::Warning,
+                    format!("modId {:?} differs from the mod.vdf key {:?}", id, vdf_key),
+                )],
+                _ => Vec::new(),
+            }
+        },
+    },
+    Rule {
+        id: "manifest-unknown-fields",
+        since: None,
+        until: None,
+        check: |files| {
+            let Some(Ok(Value::Object(manifest))) = &files.manifest else {
+                return Vec::new();
+            };
+            manifest
+                .keys()
+                .filter(|key| !MANIFEST_FIELDS.contains(&key.as_str()))
+                .map(|key| {
+                    (
+                        Severity::Warning,
+                        format!("\"{}\" is not a field this version reads", key),
+                    )
+                })
+                .collect()
+        },
+    },
+];
+
+/// Parses a version such as "1.2", "v2.0.1" or "3.1-beta" into its numeric
+/// components. Anything after `-` or `+` is ignored.
+pub fn parse_version(version: &str) -> Result<Vec<u32>, String> {
+    let core = version
+        .trim()
+        .trim_start_matches(['v', 'V'])
+        .split(['-', '+'])
+        .next()
+        .unwrap_or_default();
+    core.split('.')
+        .map(|part| part.parse::<u32>())
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|_| format!("Invalid game version {:?}", version))
+}
+
+fn compare_versions(a: &[u32], b: &[u32]) -> Ordering {
+    let len = a.len().max(b.len());
+    let at = |v: &[u32], i: usize| v.get(i).copied().unwrap_or(0);
+    (0..len)
+        .map(|i| at(a, i).cmp(&at(b, i)))
+        .find(|o| o.is_ne())
+        .unwrap_or(Ordering::Equal)
+}
+
+fn applies(rule: &Rule, version: &[u32]) -> bool {
+    // Bounds are written by hand above, so they always parse
+    let bound = |v: Option<&str>| v.and_then(|v| parse_version(v).ok());
+    bound(rule.since).is_none_or(|since| compare_versions(version, &since).is_ge())
+        && bound(rule.until).is_none_or(|until| compare_versions(version, &until).is_lt())
+}
+
+/// Runs every rule that applies to `game_version` against the mod in
+/// `mod_root`. Errors come before warnings.
+pub fn validate(mod_root: &Path, game_version: &str) -> Result<Vec<Finding>, String> {
+    let version = parse_version(game_version)?;
+    let manifest_path = mod_root.join(manifest::MANIFEST_FILE_NAME);
+    let files = ModFiles {
+        vdf: manifest::load_mod_vdf_entry(mod_root),
+        manifest: manifest_path.is_file().then(|| manifest::load(mod_root)),
+    };
+    let mut findings: Vec<Finding> = RULES
+        .iter()
+        .filter(|rule| applies(rule, &version))
+        .flat_map(|rule| {
+            (rule.check)(&files)
+                .into_iter()
+                .map(|(severity, message)| Finding {
+                    rule: rule.id,
+                    severity,
+                    message,
+                })
+        })
+        .collect();
+    findings.sort_by_key(|f| matches!(f.severity, Severity::Warning));
+    Ok(findings)
+}
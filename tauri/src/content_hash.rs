@@ -0,0 +1,84 @@
+// One digest for everything a mod ships, for telling whether it changed
+// since it was last published.
+//
+// The digest is SHA-256 over one `<relative path>\0<file sha256>\n` line per
+// file, in path order, so it depends on names and content but not on
+// timestamps or the order files are found in. File hashes are cached by
+// size and modification time, so repeat calls only read what changed.
+
+use crate::export;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+struct CachedHash {
+    size: u64,
+    modified: Option<SystemTime>,
+    hash: String,
+}
+
+#[derive(Default)]
+pub struct HashCache {
+    files: Mutex<HashMap<PathBuf, CachedHash>>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ContentHash {
+    pub hash: String,
+    pub file_count: usize,
+    // Files read this time rather than served from the cache
+    pub hashed_files: usize,
+}
+
+impl HashCache {
+    fn file_hash(&self, path: &Path, hashed_files: &mut usize) -> io::Result<String> {
+        let metadata = fs::metadata(path)?;
+        let (size, modified) = (metadata.len(), metadata.modified().ok());
+        let mut files = self.files.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(cached) = files.get(path) {
+            if cached.size == size && modified.is_some() && cached.modified == modified {
+                return Ok(cached.hash.clone());
+            }
+        }
+        // Not held while reading the file
+        drop(files);
+
+        let hash = export::hash_file(path)?;
+        *hashed_files += 1;
+        files = self.files.lock().unwrap_or_else(|e| e.into_inner());
+        files.insert(
+            path.to_path_buf(),
+            CachedHash {
+                size,
+                modified,
+                hash: hash.clone(),
+            },
+        );
+        Ok(hash)
+    }
+
+    /// Digest of the files under `mod_root` that would be exported: hidden
+    /// entries and whatever `.r5vignore` lists are left out.
+    pub fn mod_hash(&self, mod_root: &Path) -> io::Result<ContentHash> {
+        let files = export::collect_mod_files(mod_root)?;
+        let mut hasher = Sha256::new();
+        let mut hashed_files = 0;
+        for file in &files {
+            let hash = self.file_hash(&file.path, &mut hashed_files)?;
+            hasher.update(file.relative.as_bytes());
+            hasher.update([0]);
+            hasher.update(hash.as_bytes());
+            hasher.update(b"\n");
+        }
+        Ok(ContentHash {
+            hash: format!("{:x}", hasher.finalize()),
+            file_count: files.len(),
+            hashed_files,
+        })
+    }
+}
@@ -0,0 +1,225 @@
+// Line-based diffing (Myers' algorithm) grouped into unified-style hunks.
+
+use serde::Serialize;
+
+// Past this many edits the diff is reported as a full replacement instead,
+// keeping memory bounded for files that share almost nothing
+const MAX_EDIT_DISTANCE: usize = 2000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LineKind {
+    Context,
+    Added,
+    Removed,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DiffLine {
+    pub kind: LineKind,
+    pub text: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Hunk {
+    // 1-based line numbers, as in unified diffs
+    pub old_start: usize,
+    pub old_lines: usize,
+    pub new_start: usize,
+    pub new_lines: usize,
+    pub lines: Vec<DiffLine>,
+}
+
+// One step of the edit script, with the positions in both inputs
+#[derive(Debug, Clone, Copy)]
+struct Edit {
+    kind: LineKind,
+    old: usize,
+    new: usize,
+}
+
+/// Diffs two texts line by line and groups the changes into hunks with
+/// `context` unchanged lines around them. Identical inputs give no hunks.
+pub fn diff_hunks(old: &str, new: &str, context: usize) -> Vec<Hunk> {
+    let old_lines: Vec<&str> = old.lines().collect();
+    let new_lines: Vec<&str> = new.lines().collect();
+    let edits = diff_lines(&old_lines, &new_lines);
+
+    let mut hunks = Vec::new();
+    let mut i = 0;
+    while i < edits.len() {
+        if edits[i].kind == LineKind::Context {
+            i += 1;
+            continue;
+        }
+
+        // Extend the hunk while changes are close enough to share context
+        let start = i.saturating_sub(context);
+        let mut last_change = i;
+        let mut j = i;
+        while j < edits.len() {
+            if edits[j].kind != LineKind::Context {
+                last_change = j;
+            } else if j - last_change > context * 2 {
+                break;
+            }
+            j += 1;
+        }
+        let end = (last_change + context + 1).min(edits.len());
+
+        let slice = &edits[start..end];
+        hunks.push(Hunk {
+            old_start: slice[0].old + 1,
+            old_lines: slice.iter().filter(|e| e.kind != LineKind::Added).count(),
+            new_start: slice[0].new + 1,
+            new_lines: slice.iter().filter(|e| e.kind != LineKind::Removed).count(),
+            lines: slice
+                .iter()
+                .map(|e| DiffLine {
+                    kind: e.kind,
+                    text: match e.kind {
+                        LineKind::Added => new_lines[e.new],
+                        _ => old_lines[e.old],
+                    }
+                    .to_string(),
+                })
+                .collect(),
+        });
+        i = end;
+    }
+
+    hunks
+}
+
+fn diff_lines(a: &[&str], b: &[&str]) -> Vec<Edit> {
+    let prefix = a.iter().zip(b).take_while(|(x, y)| x == y).count();
+    let suffix = a[prefix..]
+        .iter()
+        .rev()
+        .zip(b[prefix..].iter().rev())
+        .take_while(|(x, y)| x == y)
+        .count();
+    let a_mid = &a[prefix..a.len() - suffix];
+    let b_mid = &b[prefix..b.len() - suffix];
+
+    let mut edits: Vec<Edit> = (0..prefix)
+        .map(|i| Edit {
+            kind: LineKind::Context,
+            old: i,
+            new: i,
+        })
+        .collect();
+
+    let middle = myers(a_mid, b_mid).unwrap_or_else(|| replace_all(a_mid.len(), b_mid.len()));
+    edits.extend(middle.into_iter().map(|e| Edit {
+        kind: e.kind,
+        old: e.old + prefix,
+        new: e.new + prefix,
+    }));
+
+    edits.extend((0..suffix).map(|i| Edit {
+        kind: LineKind::Context,
+        old: a.len() - suffix + i,
+        new: b.len() - suffix + i,
+    }));
+    edits
+}
+
+fn replace_all(old_len: usize, new_len: usize) -> Vec<Edit> {
+    let removed = (0..old_len).map(|i| Edit {
+        kind: LineKind::Removed,
+        old: i,
+        new: 0,
+    });
+    let added = (0..new_len).map(|i| Edit {
+        kind: LineKind::Added,
+        old: old_len,
+        new: i,
+    });
+    removed.chain(added).collect()
+}
+
+// Shortest edit script, or None when it would exceed MAX_EDIT_DISTANCE
+fn myers(a: &[&str], b: &[&str]) -> Option<Vec<Edit>> {
+    let (n, m) = (a.len() as isize, b.len() as isize);
+    let max = (n + m) as usize;
+    let offset = max as isize + 1;
+    let mut v = vec![0isize; 2 * max + 3];
+    let mut trace: Vec<Vec<isize>> = Vec::new();
+
+    let mut found = None;
+    'outer: for d in 0..=max as isize {
+        if d as usize > MAX_EDIT_DISTANCE {
+            return None;
+        }
+        // Only diagonals -d-1..=d+1 can be read when backtracking this step
+        trace.push(v[(offset - d - 1) as usize..=(offset + d + 1) as usize].to_vec());
+        for k in (-d..=d).step_by(2) {
+            let idx = (k + offset) as usize;
+            let mut x = if k == -d || (k != d && v[idx - 1] < v[idx + 1]) {
+                v[idx + 1]
+            } else {
+                v[idx - 1] + 1
+            };
+            let mut y = x - k;
+            while x < n && y < m && a[x as usize] == b[y as usize] {
+                x += 1;
+                y += 1;
+            }
+            v[idx] = x;
+            if x >= n && y >= m {
+                found = Some(d);
+                break 'outer;
+            }
+        }
+    }
+    let depth = found?;
+
+    // Walk the trace backwards to recover the path
+    let mut edits = Vec::new();
+    let (mut x, mut y) = (n, m);
+    for d in (0..=depth).rev() {
+        let window = &trace[d as usize];
+        let at = |k: isize| window[(k + d + 1) as usize];
+        let k = x - y;
+        let prev_k = if k == -d || (k != d && at(k - 1) < at(k + 1)) {
+            k + 1
+        } else {
+            k - 1
+        };
+        let prev_x = at(prev_k);
+        let prev_y = prev_x - prev_k;
+
+        while x > prev_x && y > prev_y {
+            x -= 1;
+            y -= 1;
+            edits.push(Edit {
+                kind: LineKind::Context,
+                old: x as usize,
+                new: y as usize,
+            });
+        }
+        if d > 0 {
+            if x == prev_x {
+                y -= 1;
+                edits.push(Edit {
+                    kind: LineKind::Added,
+                    old: x as usize,
+                    new: y as usize,
+                });
+            } else {
+                x -= 1;
+                edits.push(Edit {
+                    kind: LineKind::Removed,
+                    old: x as usize,
+                    new: y as usize,
+                });
+            }
+        }
+        x = prev_x;
+        y = prev_y;
+    }
+
+    edits.reverse();
+    Some(edits)
+}
@@ -0,0 +1,37 @@
+// Free space queries, so big saves can fail before writing anything instead of
+// leaving a partial file behind when the disk fills up.
+
+use std::io;
+use std::path::Path;
+
+pub struct DiskSpace {
+    pub available: u64,
+    pub total: u64,
+}
+
+// A path that doesn't exist yet is measured on its closest existing ancestor,
+// which is the filesystem the file will be created on
+pub fn space(path: &Path) -> io::Result<DiskSpace> {
+    let existing = path
+        .ancestors()
+        .find(|p| !p.as_os_str().is_empty() && p.exists())
+        .unwrap_or_else(|| Path::new("."));
+    Ok(DiskSpace {
+        available: fs2::available_space(existing)?,
+        total: fs2::total_space(existing)?,
+    })
+}
+
+pub fn ensure_space(path: &Path, needed: u64) -> Result<(), String> {
+    let space = space(path)
+        .map_err(|e| format!("Failed to query free space for {}: {}", path.display(), e))?;
+    if space.available < needed {
+        return Err(format!(
+            "InsufficientSpace: {} needs about {} bytes but only {} are available",
+            path.display(),
+            needed,
+            space.available
+        ));
+    }
+    Ok(())
+}
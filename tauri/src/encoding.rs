@@ -0,0 +1,147 @@
+// Text encodings supported by read_file/write_file.
+//
+// Most scripts are UTF-8, but some Titanfall resource and localization files
+// are UTF-16 or legacy Windows-1252, and need to round-trip without loss.
+
+const UTF16LE_BOM: [u8; 2] = [0xFF, 0xFE];
+const UTF16BE_BOM: [u8; 2] = [0xFE, 0xFF];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextEncoding {
+    Utf8,
+    Utf16Le,
+    Utf16Be,
+    Windows1252,
+}
+
+impl TextEncoding {
+    /// Parses the `encoding` parameter passed by the frontend, defaulting to UTF-8.
+    pub fn from_name(name: Option<&str>) -> Result<Self, String> {
+        match name.map(|n| n.to_ascii_lowercase()).as_deref() {
+            None | Some("utf8") | Some("utf-8") => Ok(TextEncoding::Utf8),
+            Some("utf16le") | Some("utf-16le") => Ok(TextEncoding::Utf16Le),
+            Some("utf16be") | Some("utf-16be") => Ok(TextEncoding::Utf16Be),
+            Some("windows1252") | Some("windows-1252") | Some("cp1252") => {
+                Ok(TextEncoding::Windows1252)
+            }
+            Some(other) => Err(format!("Unsupported encoding: {}", other)),
+        }
+    }
+
+    /// Decodes raw file bytes. UTF-16 input may or may not start with a BOM;
+    /// a BOM matching the requested byte order is stripped.
+    pub fn decode(self, data: &[u8]) -> Result<String, String> {
+        match self {
+            TextEncoding::Utf8 => String::from_utf8(data.to_vec()).map_err(|e| e.to_string()),
+            TextEncoding::Utf16Le => decode_utf16(
+                data.strip_prefix(&UTF16LE_BOM).unwrap_or(data),
+                u16::from_le_bytes,
+            ),
+            TextEncoding::Utf16Be => decode_utf16(
+                data.strip_prefix(&UTF16BE_BOM).unwrap_or(data),
+                u16::from_be_bytes,
+            ),
+            TextEncoding::Windows1252 => Ok(encoding_rs::WINDOWS_1252
+                .decode_without_bom_handling(data)
+                .0
+                .into_owned()),
+        }
+    }
+
+    /// The name `from_name` accepts for this encoding.
+    pub fn name(self) -> &'static str {
+        match self {
+            TextEncoding::Utf8 => "utf-8",
+            TextEncoding::Utf16Le => "utf-16le",
+            TextEncoding::Utf16Be => "utf-16be",
+            TextEncoding::Windows1252 => "windows-1252",
+        }
+    }
+
+    /// Best guess at what `data` is encoded as, falling back to
+    /// Windows-1252, which accepts any bytes.
+    pub fn guess(data: &[u8]) -> Self {
+        Self::detect(data).unwrap_or(TextEncoding::Windows1252)
+    }
+
+    /// The encoding `data` is confidently in, or None. A UTF-16 BOM, or NUL
+    /// bytes falling mostly on odd or even offsets as ASCII does in UTF-16,
+    /// suggest UTF-16; otherwise UTF-8 if it decodes. Windows-1252 is only
+    /// claimed for text using none of its five undefined bytes and no
+    /// control characters beyond whitespace. The result always decodes.
+    pub fn detect(data: &[u8]) -> Option<Self> {
+        let sample = &data[..data.len().min(4096)];
+        let nul_at = |parity| {
+            sample
+                .iter()
+                .skip(parity)
+                .step_by(2)
+                .filter(|&&b| b == 0)
+                .count()
+        };
+        let half = sample.len() / 2;
+        let candidate = if data.starts_with(&UTF16LE_BOM) {
+            TextEncoding::Utf16Le
+        } else if data.starts_with(&UTF16BE_BOM) {
+            TextEncoding::Utf16Be
+        } else if half > 0 && nul_at(1) * 2 > half {
+            TextEncoding::Utf16Le
+        } else if half > 0 && nul_at(0) * 2 > half {
+            TextEncoding::Utf16Be
+        } else if std::str::from_utf8(data).is_ok() {
+            return Some(TextEncoding::Utf8);
+        } else {
+            let plausible = |&b: &u8| {
+                !matches!(b, 0x81 | 0x8D | 0x8F | 0x90 | 0x9D)
+                    && (b >= 0x20 || matches!(b, b'\t' | b'\n' | b'\r' | 0x0C))
+            };
+            return data
+                .iter()
+                .all(plausible)
+                .then_some(TextEncoding::Windows1252);
+        };
+        candidate.decode(data).ok().map(|_| candidate)
+    }
+
+    /// Encodes text for writing. UTF-16 output always starts with a BOM.
+    pub fn encode(self, content: &str) -> Result<Vec<u8>, String> {
+        match self {
+            TextEncoding::Utf8 => Ok(content.as_bytes().to_vec()),
+            TextEncoding::Utf16Le => {
+                let mut data = UTF16LE_BOM.to_vec();
+                for unit in content.encode_utf16() {
+                    data.extend_from_slice(&unit.to_le_bytes());
+                }
+                Ok(data)
+            }
+            TextEncoding::Utf16Be => {
+                let mut data = UTF16BE_BOM.to_vec();
+                for unit in content.encode_utf16() {
+                    data.extend_from_slice(&unit.to_be_bytes());
+                }
+                Ok(data)
+            }
+            TextEncoding::Windows1252 => {
+                let (data, _, had_errors) = encoding_rs::WINDOWS_1252.encode(content);
+                if had_errors {
+                    return Err(
+                        "Content contains characters that cannot be encoded as Windows-1252"
+                            .to_string(),
+                    );
+                }
+                Ok(data.into_owned())
+            }
+        }
+    }
+}
+
+fn decode_utf16(data: &[u8], to_unit: fn([u8; 2]) -> u16) -> Result<String, String> {
+    if !data.len().is_multiple_of(2) {
+        return Err("Invalid UTF-16: odd number of bytes".to_string());
+    }
+    let units: Vec<u16> = data
+        .chunks_exact(2)
+        .map(|pair| to_unit([pair[0], pair[1]]))
+        .collect();
+    String::from_utf16(&units).map_err(|e| format!("Invalid UTF-16: {}", e))
+}
@@ -0,0 +1,205 @@
+// Packaging a mod folder into a distributable zip.
+
+use crate::{ignore, paths};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{self, Write};
+use std::ops::ControlFlow;
+use std::path::{Path, PathBuf};
+
+/// A file that goes into the archive.
+pub struct ExportFile {
+    pub path: PathBuf,
+    // Forward-slash path relative to the mod root
+    pub relative: String,
+    pub size: u64,
+}
+
+/// Files with byte-identical content.
+#[derive(Debug, Serialize)]
+pub struct DuplicateGroup {
+    pub hash: String,
+    pub size: u64,
+    pub files: Vec<String>,
+}
+
+/// Lists every file under `mod_root` that belongs in a release, sorted by
+/// relative path. Hidden files and folders (editor state, `.git`) are left
+/// out.
+pub fn collect_files(mod_root: &Path) -> io::Result<Vec<ExportFile>> {
+    let mut files = Vec::new();
+    for path in paths::walk_files(mod_root)? {
+        let size = fs::metadata(&path)?.len();
+        if let Some(relative) = paths::relative_slash_path(mod_root, &path) {
+            files.push(ExportFile {
+                path,
+                relative,
+                size,
+            });
+        }
+    }
+    files.sort_by(|a, b| a.relative.cmp(&b.relative));
+    Ok(files)
+}
+
+/// Like `collect_files`, but also leaving out what the mod's `.r5vignore`
+/// lists.
+pub fn collect_mod_files(mod_root: &Path) -> io::Result<Vec<ExportFile>> {
+    let rules = ignore::IgnoreRules::load(mod_root);
+    let mut files = collect_files(mod_root)?;
+    files.retain(|file| !rules.is_file_ignored(&file.relative));
+    Ok(files)
+}
+
+/// The files named by `selection`, each a file or folder given relative to
+/// `base` or as an absolute path inside it, with paths relative to `base`.
+/// Folders contribute every file under them, hidden ones excepted. Entries
+/// that resolve outside `base` are refused, so no archive name can climb out
+/// of the folder it is extracted to.
+pub fn collect_selection(base: &Path, selection: &[String]) -> Result<Vec<ExportFile>, String> {
+    let base = paths::canonicalize_lenient(base);
+    let mut by_relative = BTreeMap::new();
+    for entry in selection {
+        let path = paths::ensure_within(&base, &base.join(entry))?;
+        let metadata =
+            fs::metadata(&path).map_err(|e| format!("Failed to read {}: {}", entry, e))?;
+        let found = if metadata.is_dir() {
+            paths::walk_files(&path).map_err(|e| format!("Failed to read {}: {}", entry, e))?
+        } else {
+            vec![path]
+        };
+        for path in found {
+            let Some(relative) = paths::relative_slash_path(&base, &path) else {
+                continue;
+            };
+            if relative.is_empty() || by_relative.contains_key(&relative) {
+                continue;
+            }
+            let size = fs::metadata(&path)
+                .map_err(|e| format!("Failed to read {}: {}", relative, e))?
+                .len();
+            by_relative.insert(
+                relative.clone(),
+                ExportFile {
+                    path,
+                    relative,
+                    size,
+                },
+            );
+        }
+    }
+    Ok(by_relative.into_values().collect())
+}
+
+pub fn hash_file(path: &Path) -> io::Result<String> {
+    let mut hasher = Sha256::new();
+    io::copy(&mut fs::File::open(path)?, &mut hasher)?;
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// Groups files whose content is byte-identical, largest waste first. Only
+/// files sharing a size are hashed, and empty files are ignored.
+pub fn find_duplicates(files: &[ExportFile]) -> io::Result<Vec<DuplicateGroup>> {
+    let mut by_size: BTreeMap<u64, Vec<&ExportFile>> = BTreeMap::new();
+    for file in files.iter().filter(|f| f.size > 0) {
+        by_size.entry(file.size).or_default().push(file);
+    }
+
+    let mut groups = Vec::new();
+    for (size, same_size) in by_size {
+        if same_size.len() < 2 {
+            continue;
+        }
+        let mut by_hash: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        for file in same_size {
+            by_hash
+                .entry(hash_file(&file.path)?)
+                .or_default()
+                .push(file.relative.clone());
+        }
+        groups.extend(
+            by_hash
+                .into_iter()
+                .filter(|(_, files)| files.len() > 1)
+                .map(|(hash, files)| DuplicateGroup { hash, size, files }),
+        );
+    }
+
+    groups.sort_by_key(|g| std::cmp::Reverse(g.size * (g.files.len() as u64 - 1)));
+    Ok(groups)
+}
+
+/// Progress reported after each file: files written so far and their
+/// uncompressed bytes. Returning `ControlFlow::Break` stops the export.
+pub type Progress<'a> = dyn FnMut(usize, u64) -> ControlFlow<()> + 'a;
+
+/// Where an archive is written until it is complete.
+pub fn part_path(output: &Path) -> PathBuf {
+    let mut name = output.as_os_str().to_os_string();
+    name.push(".part");
+    PathBuf::from(name)
+}
+
+/// Writes `files` into a zip at `output`, nested under `folder_name` when
+/// given so the archive extracts to a single mod folder. The archive is built in a `.part`
+/// file next to `output` and only renamed into place once finished, so an
+/// interrupted export never leaves a truncated zip behind. Returns false if
+/// `progress` cancelled it; the partial file is removed either way.
+pub fn write_zip(
+    files: &[ExportFile],
+    folder_name: Option<&str>,
+    output: &Path,
+    progress: &mut Progress,
+) -> Result<bool, String> {
+    let part = part_path(output);
+    let result = write_zip_to(files, folder_name, &part, progress).and_then(|finished| {
+        if finished {
+            fs::rename(&part, output)
+                .map_err(|e| format!("Failed to create {}: {}", output.display(), e))?;
+        }
+        Ok(finished)
+    });
+    if !matches!(result, Ok(true)) {
+        let _ = fs::remove_file(&part);
+    }
+    result
+}
+
+fn write_zip_to(
+    files: &[ExportFile],
+    folder_name: Option<&str>,
+    output: &Path,
+    progress: &mut Progress,
+) -> Result<bool, String> {
+    let options = zip::write::SimpleFileOptions::default()
+        .compression_method(zip::CompressionMethod::Deflated)
+        .large_file(true);
+
+    let file = fs::File::create(output)
+        .map_err(|e| format!("Failed to create {}: {}", output.display(), e))?;
+    let mut writer = zip::ZipWriter::new(file);
+    let mut bytes_written = 0;
+    for (index, export_file) in files.iter().enumerate() {
+        let name = match folder_name {
+            Some(folder_name) => format!("{}/{}", folder_name, export_file.relative),
+            None => export_file.relative.clone(),
+        };
+        writer
+            .start_file(name, options)
+            .map_err(|e| format!("Failed to add {}: {}", export_file.relative, e))?;
+        let mut source = fs::File::open(&export_file.path)
+            .map_err(|e| format!("Failed to read {}: {}", export_file.relative, e))?;
+        bytes_written += io::copy(&mut source, &mut writer)
+            .map_err(|e| format!("Failed to add {}: {}", export_file.relative, e))?;
+        if progress(index + 1, bytes_written).is_break() {
+            return Ok(false);
+        }
+    }
+    writer
+        .finish()
+        .and_then(|mut file| file.flush().map_err(Into::into))
+        .map_err(|e| format!("Failed to finish {}: {}", output.display(), e))?;
+    Ok(true)
+}
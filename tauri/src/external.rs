@@ -0,0 +1,95 @@
+// Handing files off to the OS default application.
+
+use std::path::Path;
+
+const NO_HANDLER: &str = "No application is registered to open this file";
+
+/// Opens `path` with whatever the OS has associated with it. The launched
+/// application is not waited on, only the launcher itself, and on Linux
+/// that only briefly.
+#[cfg(target_os = "macos")]
+pub fn open_with_default(path: &Path) -> Result<(), String> {
+    let output = std::process::Command::new("open")
+        .arg(path)
+        .output()
+        .map_err(|e| format!("Failed to run open: {}", e))?;
+    if output.status.success() {
+        return Ok(());
+    }
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    if stderr.contains("No application knows how to open") {
+        Err(NO_HANDLER.to_string())
+    } else {
+        Err(stderr.trim().to_string())
+    }
+}
+
+// How long xdg-open gets to report a failure. Some handlers keep it running
+// until the opened program exits, so it is not waited on past this.
+#[cfg(all(unix, not(target_os = "macos")))]
+const XDG_OPEN_WAIT: std::time::Duration = std::time::Duration::from_secs(2);
+
+#[cfg(all(unix, not(target_os = "macos")))]
+pub fn open_with_default(path: &Path) -> Result<(), String> {
+    use std::process::Stdio;
+    use std::time::{Duration, Instant};
+
+    let mut child = std::process::Command::new("xdg-open")
+        .arg(path)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| format!("Failed to run xdg-open: {}", e))?;
+    let started = Instant::now();
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break status,
+            Ok(None) if started.elapsed() < XDG_OPEN_WAIT => {
+                std::thread::sleep(Duration::from_millis(50))
+            }
+            // Still running, so the launch got far enough; reap it when it ends
+            Ok(None) => {
+                std::thread::spawn(move || child.wait());
+                return Ok(());
+            }
+            Err(e) => return Err(format!("Failed to wait for xdg-open: {}", e)),
+        }
+    };
+    // xdg-open: 3 = no launcher tool found, 4 = the launch itself failed,
+    // which is what desktop environments report for unknown file types
+    match status.code() {
+        Some(0) => Ok(()),
+        Some(3) | Some(4) => Err(NO_HANDLER.to_string()),
+        Some(code) => Err(format!("xdg-open failed with code {}", code)),
+        None => Err("xdg-open was terminated by a signal".to_string()),
+    }
+}
+
+#[cfg(windows)]
+pub fn open_with_default(path: &Path) -> Result<(), String> {
+    use std::os::windows::ffi::OsStrExt;
+    use windows_sys::Win32::UI::Shell::{ShellExecuteW, SE_ERR_NOASSOC};
+    use windows_sys::Win32::UI::WindowsAndMessaging::SW_SHOWNORMAL;
+
+    let wide = |s: &std::ffi::OsStr| s.encode_wide().chain(Some(0)).collect::<Vec<u16>>();
+    let verb = wide("open".as_ref());
+    let file = wide(path.as_os_str());
+
+    // Values above 32 mean success; anything else is an SE_ERR_* code
+    let code = unsafe {
+        ShellExecuteW(
+            std::ptr::null_mut(),
+            verb.as_ptr(),
+            file.as_ptr(),
+            std::ptr::null(),
+            std::ptr::null(),
+            SW_SHOWNORMAL,
+        )
+    } as isize;
+    match code {
+        c if c > 32 => Ok(()),
+        c if c == SE_ERR_NOASSOC as isize => Err(NO_HANDLER.to_string()),
+        c => Err(format!("ShellExecute failed with code {}", c)),
+    }
+}
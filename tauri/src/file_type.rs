@@ -0,0 +1,85 @@
+// Classifying mod files by what they contain.
+
+use serde::Serialize;
+use std::fs;
+use std::io::Read;
+use std::path::Path;
+
+const SCRIPT_EXTENSIONS: &[&str] = &["nut", "gnut"];
+const TEXT_EXTENSIONS: &[&str] = &[
+    "txt", "json", "vdf", "cfg", "rson", "res", "menu", "md", "csv", "ini", "xml", "yaml", "yml",
+];
+const PAK_EXTENSIONS: &[&str] = &["rpak", "starpak", "vpk", "mbnk"];
+const IMAGE_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg", "dds", "tga", "vtf", "bmp"];
+const AUDIO_EXTENSIONS: &[&str] = &["wav", "mp3", "ogg", "flac", "bnk"];
+
+// How much of a file is sniffed when the extension is not conclusive
+const SNIFF_BYTES: usize = 8192;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FileKind {
+    Script,
+    Text,
+    Pak,
+    Image,
+    Audio,
+    Binary,
+}
+
+impl FileKind {
+    /// Parses the lowercase name the kind is serialized as.
+    pub fn from_name(name: &str) -> Option<FileKind> {
+        match name {
+            "script" => Some(FileKind::Script),
+            "text" => Some(FileKind::Text),
+            "pak" => Some(FileKind::Pak),
+            "image" => Some(FileKind::Image),
+            "audio" => Some(FileKind::Audio),
+            "binary" => Some(FileKind::Binary),
+            _ => None,
+        }
+    }
+
+    pub fn is_text(self) -> bool {
+        matches!(self, FileKind::Script | FileKind::Text)
+    }
+}
+
+/// NUL bytes near the start are a reliable sign of non-text content.
+pub fn looks_binary(data: &[u8]) -> bool {
+    data.iter().take(SNIFF_BYTES).any(|&b| b == 0)
+}
+
+/// Classifies a file by extension, falling back to sniffing its first bytes
+/// for unknown extensions. Files that can't be read count as binary.
+pub fn detect_file_type(path: &Path) -> FileKind {
+    let extension = path
+        .extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    let known = [
+        (SCRIPT_EXTENSIONS, FileKind::Script),
+        (TEXT_EXTENSIONS, FileKind::Text),
+        (PAK_EXTENSIONS, FileKind::Pak),
+        (IMAGE_EXTENSIONS, FileKind::Image),
+        (AUDIO_EXTENSIONS, FileKind::Audio),
+    ];
+    if let Some((_, kind)) = known
+        .iter()
+        .find(|(extensions, _)| extensions.contains(&extension.as_str()))
+    {
+        return *kind;
+    }
+
+    let mut head = Vec::with_capacity(SNIFF_BYTES);
+    let read =
+        fs::File::open(path).and_then(|file| file.take(SNIFF_BYTES as u64).read_to_end(&mut head));
+    // A multi-byte character cut off by the sniff limit is still valid UTF-8
+    let utf8 =
+        |data: &[u8]| std::str::from_utf8(data).map_or_else(|e| e.error_len().is_none(), |_| true);
+    match read {
+        Ok(_) if !looks_binary(&head) && utf8(&head) => FileKind::Text,
+        _ => FileKind::Binary,
+    }
+}
@@ -0,0 +1,296 @@
+// Locating Steam libraries and game installs on disk.
+
+use crate::vdf;
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub const APEX_APP_ID: &str = "1172470";
+
+// Folder name of the game inside a Steam library's steamapps/common
+const STEAM_GAME_FOLDER: &str = "Apex Legends";
+
+const GAME_EXECUTABLES: &[&str] = &["r5apex.exe", "r5apex_ds.exe"];
+
+#[derive(Debug, Clone)]
+pub struct SteamLibrary {
+    pub path: PathBuf,
+    // Installed app ids; empty for the old libraryfolders.vdf format
+    pub apps: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct InstallCandidate {
+    pub game_path: String,
+    pub mods_path: String,
+    pub mods_exists: bool,
+    // Where the candidate came from: "steam", "registry" or "default"
+    pub source: String,
+    // 0-100, higher is more likely to be a real install
+    pub confidence: u8,
+}
+
+#[derive(Debug, Serialize)]
+pub struct LibraryInfo {
+    pub path: String,
+    pub steam_root: String,
+    pub has_app: bool,
+}
+
+fn home_dir() -> Option<PathBuf> {
+    let var = if cfg!(windows) { "USERPROFILE" } else { "HOME" };
+    std::env::var_os(var).map(PathBuf::from)
+}
+
+#[cfg(windows)]
+fn registry_string(hive: &winreg::RegKey, key: &str, value: &str) -> Option<PathBuf> {
+    hive.open_subkey(key)
+        .and_then(|k| k.get_value::<String, _>(value))
+        .ok()
+        .map(PathBuf::from)
+}
+
+/// Candidate Steam installation roots for this platform, existing ones only.
+pub fn steam_roots() -> Vec<PathBuf> {
+    let mut roots = Vec::new();
+
+    #[cfg(windows)]
+    {
+        use winreg::enums::{HKEY_CURRENT_USER, HKEY_LOCAL_MACHINE};
+        use winreg::RegKey;
+        let user = RegKey::predef(HKEY_CURRENT_USER);
+        let machine = RegKey::predef(HKEY_LOCAL_MACHINE);
+        roots.extend(registry_string(
+            &user,
+            "Software\\Valve\\Steam",
+            "SteamPath",
+        ));
+        roots.extend(registry_string(
+            &machine,
+            "SOFTWARE\\WOW6432Node\\Valve\\Steam",
+            "InstallPath",
+        ));
+        roots.push(PathBuf::from("C:\\Program Files (x86)\\Steam"));
+    }
+
+    if let Some(home) = home_dir() {
+        if cfg!(target_os = "macos") {
+            roots.push(home.join("Library/Application Support/Steam"));
+        } else if cfg!(unix) {
+            roots.push(home.join(".steam/steam"));
+            roots.push(home.join(".local/share/Steam"));
+            roots.push(home.join(".var/app/com.valvesoftware.Steam/.local/share/Steam"));
+        }
+    }
+
+    let mut unique: Vec<PathBuf> = Vec::new();
+    for root in roots {
+        if !root.is_dir() {
+            continue;
+        }
+        // ~/.steam/steam is usually a symlink to one of the other roots
+        let canonical = dunce::canonicalize(&root).unwrap_or(root);
+        if !unique.contains(&canonical) {
+            unique.push(canonical);
+        }
+    }
+    unique
+}
+
+/// Parses `steamapps/libraryfolders.vdf`, supporting both the old format
+/// (`"1" "D:\\SteamLibrary"`) and the newer one with a block per library.
+/// The Steam root itself is always included as a library.
+pub fn library_folders(steam_root: &Path) -> Result<Vec<SteamLibrary>, String> {
+    let vdf_path = steam_root.join("steamapps").join("libraryfolders.vdf");
+    let content = fs::read_to_string(&vdf_path)
+        .map_err(|e| format!("Failed to read {}: {}", vdf_path.display(), e))?;
+    let document = vdf::parse(&content)
+        .map_err(|e| format!("Failed to parse {}: {}", vdf_path.display(), e))?;
+
+    let mut libraries = vec![SteamLibrary {
+        path: steam_root.to_path_buf(),
+        apps: Vec::new(),
+    }];
+
+    let folders = document
+        .get("libraryfolders")
+        .ok_or_else(|| "libraryfolders.vdf has no libraryfolders section".to_string())?;
+
+    for (key, value) in folders.entries() {
+        // Libraries are numbered; other keys are bookkeeping like "ContentStatsID"
+        if !key.chars().all(|c| c.is_ascii_digit()) {
+            continue;
+        }
+
+        let library = match value {
+            vdf::Value::Str(path) => SteamLibrary {
+                path: PathBuf::from(path),
+                apps: Vec::new(),
+            },
+            vdf::Value::Block(_) => {
+                let Some(path) = value.get_str("path") else {
+                    continue;
+                };
+                SteamLibrary {
+                    path: PathBuf::from(path),
+                    apps: value
+                        .get("apps")
+                        .map(|apps| apps.entries().iter().map(|(id, _)| id.clone()).collect())
+                        .unwrap_or_default(),
+                }
+            }
+        };
+
+        let same_as_root =
+            dunce::canonicalize(&library.path).ok() == dunce::canonicalize(steam_root).ok();
+        if same_as_root {
+            libraries[0].apps = library.apps;
+        } else {
+            libraries.push(library);
+        }
+    }
+
+    Ok(libraries)
+}
+
+/// Every Steam library on this machine, flagging the ones with `app_id`
+/// installed. Old-format libraries don't list their apps, so for those the
+/// app manifest on disk decides. Roots whose libraryfolders.vdf can't be read
+/// are reported as warnings.
+pub fn list_libraries(app_id: &str) -> (Vec<LibraryInfo>, Vec<String>) {
+    let mut found: Vec<LibraryInfo> = Vec::new();
+    let mut warnings = Vec::new();
+
+    for root in steam_roots() {
+        let libraries = match library_folders(&root) {
+            Ok(libraries) => libraries,
+            Err(e) => {
+                warnings.push(e);
+                continue;
+            }
+        };
+        for library in libraries {
+            let path = library.path.to_string_lossy().to_string();
+            if found.iter().any(|l| l.path == path) {
+                continue;
+            }
+            let has_app = if library.apps.is_empty() {
+                library
+                    .path
+                    .join("steamapps")
+                    .join(format!("appmanifest_{}.acf", app_id))
+                    .is_file()
+            } else {
+                library.apps.iter().any(|id| id == app_id)
+            };
+            found.push(LibraryInfo {
+                path,
+                steam_root: root.to_string_lossy().to_string(),
+                has_app,
+            });
+        }
+    }
+
+    (found, warnings)
+}
+
+/// Scores a folder by how much it looks like a game install, or None if it
+/// doesn't look like one at all.
+fn install_score(game_dir: &Path) -> Option<u8> {
+    if !game_dir.is_dir() {
+        return None;
+    }
+
+    let mut score = 0;
+    if GAME_EXECUTABLES
+        .iter()
+        .any(|exe| game_dir.join(exe).is_file())
+    {
+        score += 60;
+    }
+    if game_dir.join("platform").is_dir() {
+        score += 15;
+    }
+    if game_dir.join("paks").is_dir() {
+        score += 10;
+    }
+    if game_dir.join("mods").is_dir() {
+        score += 15;
+    }
+
+    if score == 0 {
+        None
+    } else {
+        Some(score)
+    }
+}
+
+fn game_dir_candidates() -> Vec<(PathBuf, &'static str)> {
+    let mut candidates = Vec::new();
+
+    for root in steam_roots() {
+        let Ok(libraries) = library_folders(&root) else {
+            continue;
+        };
+        for library in libraries {
+            // New-format libraries list their apps, so skip ones without the game
+            if !library.apps.is_empty() && !library.apps.iter().any(|id| id == APEX_APP_ID) {
+                continue;
+            }
+            let game_dir = library
+                .path
+                .join("steamapps")
+                .join("common")
+                .join(STEAM_GAME_FOLDER);
+            candidates.push((game_dir, "steam"));
+        }
+    }
+
+    #[cfg(windows)]
+    {
+        use winreg::enums::HKEY_LOCAL_MACHINE;
+        let machine = winreg::RegKey::predef(HKEY_LOCAL_MACHINE);
+        if let Some(dir) = registry_string(&machine, "SOFTWARE\\Respawn\\Apex", "Install Dir") {
+            candidates.push((dir, "registry"));
+        }
+        for dir in [
+            "C:\\Program Files\\EA Games\\Apex",
+            "C:\\Program Files (x86)\\Origin Games\\Apex",
+            "C:\\Program Files\\Electronic Arts\\Apex",
+        ] {
+            candidates.push((PathBuf::from(dir), "default"));
+        }
+    }
+
+    candidates
+}
+
+/// Finds plausible game installs, most likely first.
+pub fn detect_installs() -> Vec<InstallCandidate> {
+    let mut found: Vec<InstallCandidate> = Vec::new();
+
+    for (game_dir, source) in game_dir_candidates() {
+        let Some(score) = install_score(&game_dir) else {
+            continue;
+        };
+        let game_dir = dunce::canonicalize(&game_dir).unwrap_or(game_dir);
+        let game_path = game_dir.to_string_lossy().to_string();
+        if found.iter().any(|c| c.game_path == game_path) {
+            continue;
+        }
+
+        // A registry entry or Steam manifest is a stronger signal than a guessed path
+        let bonus = if source == "default" { 0 } else { 10 };
+        let mods_dir = game_dir.join("mods");
+        found.push(InstallCandidate {
+            game_path,
+            mods_exists: mods_dir.is_dir(),
+            mods_path: mods_dir.to_string_lossy().to_string(),
+            source: source.to_string(),
+            confidence: (score + bonus).min(100),
+        });
+    }
+
+    found.sort_by_key(|c| std::cmp::Reverse(c.confidence));
+    found
+}
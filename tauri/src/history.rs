@@ -0,0 +1,277 @@
+// Local per-file history: gzip snapshots of earlier contents.
+//
+// Snapshot content is stored once per distinct content: each blob lives in
+// `dir/.r5vhistory/.objects/<sha256>.gz`, shared by every file in `dir`, and
+// `dir/.r5vhistory/file.nut/index.json` maps each snapshot's creation time in
+// milliseconds to its blob. Older histories stored one `<timestamp>.gz` per
+// snapshot next to the index; those are still listed and read.
+//
+// Blobs are shared, so a snapshot holds `dir/.r5vhistory/.lock` exclusively
+// from writing its blob until unreferenced blobs are collected; otherwise a
+// collection running between another file's blob write and its index save
+// would delete the blob that index is about to refer to.
+
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub const HISTORY_DIR_NAME: &str = ".r5vhistory";
+
+const OBJECTS_DIR_NAME: &str = ".objects";
+const INDEX_FILE_NAME: &str = "index.json";
+const LOCK_FILE_NAME: &str = ".lock";
+const SNAPSHOT_EXTENSION: &str = "gz";
+
+#[derive(Debug, Serialize)]
+pub struct Snapshot {
+    // Milliseconds since the Unix epoch; also the snapshot's id
+    pub timestamp: u64,
+    // Size of the snapshotted content, uncompressed
+    pub size: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct IndexEntry {
+    hash: String,
+    size: u64,
+}
+
+type Index = BTreeMap<u64, IndexEntry>;
+
+fn history_root(file: &Path) -> PathBuf {
+    file.parent()
+        .unwrap_or_else(|| Path::new(""))
+        .join(HISTORY_DIR_NAME)
+}
+
+fn history_dir(file: &Path) -> Result<PathBuf, String> {
+    let name = file
+        .file_name()
+        .ok_or_else(|| format!("{} is not a file", file.display()))?;
+    Ok(history_root(file).join(name))
+}
+
+// Locks the history folder `root` exclusively, creating the lock file if
+// needed. The lock is released when the returned file is dropped.
+fn lock_root(root: &Path) -> Result<fs::File, String> {
+    let path = root.join(LOCK_FILE_NAME);
+    let file = fs::OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(&path)
+        .map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+    file.lock()
+        .map_err(|e| format!("Failed to lock {}: {}", path.display(), e))?;
+    Ok(file)
+}
+
+fn blob_path(root: &Path, hash: &str) -> PathBuf {
+    root.join(OBJECTS_DIR_NAME)
+        .join(format!("{}.{}", hash, SNAPSHOT_EXTENSION))
+}
+
+fn legacy_snapshot_path(dir: &Path, timestamp: u64) -> PathBuf {
+    dir.join(format!("{}.{}", timestamp, SNAPSHOT_EXTENSION))
+}
+
+// Timestamps of snapshots stored as whole files, from before the index
+fn legacy_timestamps(dir: &Path) -> Vec<u64> {
+    let mut found: Vec<u64> = fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .flatten()
+                .filter_map(|entry| {
+                    let path = entry.path();
+                    if path.extension()? != SNAPSHOT_EXTENSION {
+                        return None;
+                    }
+                    path.file_stem()?.to_str()?.parse().ok()
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    found.sort_unstable();
+    found
+}
+
+fn load_index(dir: &Path) -> Result<Index, String> {
+    match fs::read_to_string(dir.join(INDEX_FILE_NAME)) {
+        Ok(content) => serde_json::from_str(&content)
+            .map_err(|e| format!("Invalid history index in {}: {}", dir.display(), e)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Index::new()),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+// Replaces `path` through a temporary file, so a blob or index is never seen
+// half-written: a truncated blob would otherwise be reused for every later
+// snapshot of the same content
+fn write_replace(path: &Path, data: &[u8]) -> std::io::Result<()> {
+    let temp = path.with_extension("tmp");
+    fs::write(&temp, data)
+        .and_then(|_| fs::rename(&temp, path))
+        .inspect_err(|_| {
+            let _ = fs::remove_file(&temp);
+        })
+}
+
+fn save_index(dir: &Path, index: &Index) -> Result<(), String> {
+    let content = serde_json::to_string_pretty(index).map_err(|e| e.to_string())?;
+    write_replace(&dir.join(INDEX_FILE_NAME), content.as_bytes())
+        .map_err(|e| format!("Failed to write history index: {}", e))
+}
+
+// Deletes blobs no index in this history folder refers to any more. The
+// caller holds the lock on `root`.
+fn remove_unreferenced_blobs(root: &Path) {
+    let mut referenced = HashSet::new();
+    for entry in fs::read_dir(root).into_iter().flatten().flatten() {
+        if entry.file_name() == OBJECTS_DIR_NAME || !entry.path().is_dir() {
+            continue;
+        }
+        // An index that can't be read might still refer to anything
+        match load_index(&entry.path()) {
+            Ok(index) => referenced.extend(index.into_values().map(|e| e.hash)),
+            Err(_) => return,
+        }
+    }
+    for entry in fs::read_dir(root.join(OBJECTS_DIR_NAME))
+        .into_iter()
+        .flatten()
+        .flatten()
+    {
+        let path = entry.path();
+        let used = path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .is_some_and(|hash| referenced.contains(hash));
+        if !used {
+            let _ = fs::remove_file(path);
+        }
+    }
+}
+
+/// Stores `content` as a new snapshot of `file`, then drops the oldest
+/// snapshots beyond `retention`. Content already in the store is not written
+/// again. Waits for any other snapshot in the same folder to finish.
+pub fn snapshot(file: &Path, content: &[u8], retention: usize) -> Result<Snapshot, String> {
+    let root = history_root(file);
+    let dir = history_dir(file)?;
+    let objects = root.join(OBJECTS_DIR_NAME);
+    for path in [&dir, &objects] {
+        fs::create_dir_all(path)
+            .map_err(|e| format!("Failed to create {}: {}", path.display(), e))?;
+    }
+    let _lock = lock_root(&root)?;
+
+    let mut index = load_index(&dir)?;
+    let legacy = legacy_timestamps(&dir);
+
+    let mut timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0);
+    // Two snapshots within the same millisecond still get distinct ids
+    let latest = index
+        .keys()
+        .next_back()
+        .copied()
+        .max(legacy.last().copied());
+    if let Some(latest) = latest {
+        timestamp = timestamp.max(latest + 1);
+    }
+
+    let hash = format!("{:x}", Sha256::digest(content));
+    let blob = blob_path(&root, &hash);
+    if !blob.is_file() {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder
+            .write_all(content)
+            .map_err(|e| format!("Compression error: {}", e))?;
+        let compressed = encoder
+            .finish()
+            .map_err(|e| format!("Compression error: {}", e))?;
+        write_replace(&blob, &compressed)
+            .map_err(|e| format!("Failed to write snapshot: {}", e))?;
+    }
+    let size = content.len() as u64;
+    index.insert(timestamp, IndexEntry { hash, size });
+
+    let mut all: Vec<u64> = legacy.iter().chain(index.keys()).copied().collect();
+    all.sort_unstable();
+    let excess = all.len().saturating_sub(retention.max(1));
+    let mut dropped_blobs = false;
+    for old in &all[..excess] {
+        if index.remove(old).is_some() {
+            dropped_blobs = true;
+        } else {
+            let _ = fs::remove_file(legacy_snapshot_path(&dir, *old));
+        }
+    }
+    save_index(&dir, &index)?;
+    if dropped_blobs {
+        remove_unreferenced_blobs(&root);
+    }
+
+    Ok(Snapshot { timestamp, size })
+}
+
+// The gzip trailer ends with the uncompressed size (mod 2^32)
+fn uncompressed_size(path: &Path) -> std::io::Result<u64> {
+    let mut file = fs::File::open(path)?;
+    file.seek(SeekFrom::End(-4))?;
+    let mut trailer = [0u8; 4];
+    file.read_exact(&mut trailer)?;
+    Ok(u32::from_le_bytes(trailer) as u64)
+}
+
+/// Snapshots of `file`, newest first.
+pub fn list(file: &Path) -> Result<Vec<Snapshot>, String> {
+    let dir = history_dir(file)?;
+    let mut snapshots: Vec<Snapshot> = load_index(&dir)?
+        .into_iter()
+        .map(|(timestamp, entry)| Snapshot {
+            timestamp,
+            size: entry.size,
+        })
+        .collect();
+    for timestamp in legacy_timestamps(&dir) {
+        let size = uncompressed_size(&legacy_snapshot_path(&dir, timestamp)).unwrap_or(0);
+        snapshots.push(Snapshot { timestamp, size });
+    }
+    snapshots.sort_by_key(|s| std::cmp::Reverse(s.timestamp));
+    Ok(snapshots)
+}
+
+/// Decompressed content of one snapshot.
+pub fn read(file: &Path, timestamp: u64) -> Result<Vec<u8>, String> {
+    let dir = history_dir(file)?;
+    // Shared, so a collection can't remove the blob between the index lookup
+    // and the read. Without a lock file nothing has been collected yet.
+    let lock = fs::File::open(history_root(file).join(LOCK_FILE_NAME)).ok();
+    if let Some(lock) = &lock {
+        lock.lock_shared()
+            .map_err(|e| format!("Failed to lock history: {}", e))?;
+    }
+    let path = match load_index(&dir)?.get(&timestamp) {
+        Some(entry) => blob_path(&history_root(file), &entry.hash),
+        None => legacy_snapshot_path(&dir, timestamp),
+    };
+    let data = fs::read(&path).map_err(|e| match e.kind() {
+        std::io::ErrorKind::NotFound => format!("No snapshot {} for {}", timestamp, file.display()),
+        _ => e.to_string(),
+    })?;
+    let mut content = Vec::new();
+    GzDecoder::new(&data[..])
+        .read_to_end(&mut content)
+        .map_err(|e| format!("Decompression error: {}", e))?;
+    Ok(content)
+}
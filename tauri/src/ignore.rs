@@ -0,0 +1,117 @@
+// `.r5vignore`: gitignore-style patterns for files the studio should leave
+// out of listings.
+//
+// One pattern per line; `#` starts a comment. `*` and `?` stay within a path
+// segment, `**` crosses them. A pattern containing a slash is matched against
+// the whole path from the root, otherwise against the name alone. A trailing
+// `/` only matches folders and `!` re-includes; the last matching line wins.
+
+use regex::Regex;
+use std::fs;
+use std::path::Path;
+
+pub const IGNORE_FILE_NAME: &str = ".r5vignore";
+
+struct Rule {
+    pattern: Regex,
+    // Matched against the root-relative path rather than the name
+    anchored: bool,
+    dir_only: bool,
+    negated: bool,
+}
+
+#[derive(Default)]
+pub struct IgnoreRules {
+    rules: Vec<Rule>,
+}
+
+fn glob_to_regex(glob: &str) -> String {
+    let mut out = String::from("^");
+    let mut chars = glob.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '*' if chars.peek() == Some(&'*') => {
+                chars.next();
+                // "**/" also matches no folders at all
+                if chars.peek() == Some(&'/') {
+                    chars.next();
+                    out.push_str("(?:.*/)?");
+                } else {
+                    out.push_str(".*");
+                }
+            }
+            '*' => out.push_str("[^/]*"),
+            '?' => out.push_str("[^/]"),
+            c => out.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    out.push('$');
+    out
+}
+
+impl IgnoreRules {
+    pub fn parse(text: &str) -> Self {
+        let rules = text
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .filter_map(|line| {
+                let (negated, line) = match line.strip_prefix('!') {
+                    Some(rest) => (true, rest),
+                    None => (false, line),
+                };
+                let (dir_only, line) = match line.strip_suffix('/') {
+                    Some(rest) => (true, rest),
+                    None => (false, line),
+                };
+                let anchored = line.contains('/');
+                let line = line.trim_start_matches('/');
+                let pattern = Regex::new(&glob_to_regex(line)).ok()?;
+                Some(Rule {
+                    pattern,
+                    anchored,
+                    dir_only,
+                    negated,
+                })
+            })
+            .collect();
+        IgnoreRules { rules }
+    }
+
+    /// Adds rules after the existing ones, so they take precedence.
+    pub fn extend(&mut self, other: IgnoreRules) {
+        self.rules.extend(other.rules);
+    }
+
+    /// Reads `.r5vignore` from `root`; a missing or unreadable file ignores
+    /// nothing.
+    pub fn load(root: &Path) -> Self {
+        fs::read_to_string(root.join(IGNORE_FILE_NAME))
+            .map(|text| Self::parse(&text))
+            .unwrap_or_default()
+    }
+
+    /// `relative` is the slash-separated path from the root.
+    pub fn is_ignored(&self, relative: &str, is_dir: bool) -> bool {
+        let name = relative.rsplit('/').next().unwrap_or(relative);
+        let mut ignored = false;
+        for rule in &self.rules {
+            if rule.dir_only && !is_dir {
+                continue;
+            }
+            let subject = if rule.anchored { relative } else { name };
+            if rule.pattern.is_match(subject) {
+                ignored = !rule.negated;
+            }
+        }
+        ignored
+    }
+
+    /// Like `is_ignored` for a file, but also true when a folder above it is
+    /// ignored, for checking paths from a flat file list.
+    pub fn is_file_ignored(&self, relative: &str) -> bool {
+        let segments: Vec<&str> = relative.split('/').collect();
+        (1..=segments.len())
+            .any(|end| self.is_ignored(&segments[..end].join("/"), end < segments.len()))
+    }
+}
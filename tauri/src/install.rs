@@ -0,0 +1,282 @@
+// Installing a mod into the game's mods folder.
+//
+// A copy is staged next to its destination and only swapped in once
+// complete, so a failed install leaves the previous one in place. A replaced
+// install is moved out of the mods folder rather than deleted, since the game
+// would load a backup left next to it as a second copy of the mod. Linked
+// installs point the mods folder entry at the dev folder instead, so edits
+// are live; removing one only ever removes the link.
+
+use crate::{mod_identity, paths};
+use serde::Deserialize;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum InstallMode {
+    #[default]
+    Copy,
+    Symlink,
+}
+
+pub struct Installed {
+    pub path: PathBuf,
+    // Where the install that was replaced went, if there was one
+    pub backup: Option<PathBuf>,
+    // Linked installs: "symlink", or "junction" where Windows refused to
+    // create a symlink without elevation
+    pub link_kind: Option<&'static str>,
+}
+
+pub struct Uninstalled {
+    // Copied installs are moved aside rather than deleted
+    pub backup: Option<PathBuf>,
+    pub was_link: bool,
+    // Set when the install was backed up but couldn't be fully removed
+    pub warning: Option<String>,
+}
+
+fn is_link(path: &Path) -> bool {
+    fs::symlink_metadata(path).is_ok_and(|m| m.file_type().is_symlink())
+}
+
+// Removes a directory symlink or junction without touching what it points to
+fn remove_link(path: &Path) -> io::Result<()> {
+    if cfg!(windows) {
+        fs::remove_dir(path)
+    } else {
+        fs::remove_file(path)
+    }
+}
+
+#[cfg(unix)]
+fn create_link(source: &Path, link: &Path) -> Result<&'static str, String> {
+    std::os::unix::fs::symlink(source, link)
+        .map(|_| "symlink")
+        .map_err(|e| format!("Failed to link {}: {}", link.display(), e))
+}
+
+// Symlinks need Developer Mode or an elevated process on Windows; junctions
+// don't, and behave the same for a local folder
+#[cfg(windows)]
+fn create_link(source: &Path, link: &Path) -> Result<&'static str, String> {
+    // ERROR_PRIVILEGE_NOT_HELD
+    const PRIVILEGE_NOT_HELD: i32 = 1314;
+    match std::os::windows::fs::symlink_dir(source, link) {
+        Ok(()) => return Ok("symlink"),
+        Err(e) if e.raw_os_error() == Some(PRIVILEGE_NOT_HELD) => {}
+        Err(e) => return Err(format!("Failed to link {}: {}", link.display(), e)),
+    }
+    let output = std::process::Command::new("cmd")
+        .arg("/C")
+        .arg("mklink")
+        .arg("/J")
+        .arg(link)
+        .arg(source)
+        .output()
+        .map_err(|e| format!("Failed to run mklink: {}", e))?;
+    if output.status.success() {
+        Ok("junction")
+    } else {
+        Err(format!(
+            "PrivilegeRequired: creating a link needs Developer Mode or running as \
+             administrator ({})",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ))
+    }
+}
+
+fn timestamped_backup(backup_root: &Path, mod_id: &str) -> PathBuf {
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis())
+        .unwrap_or(0);
+    backup_root.join(format!("{}-{}", mod_id, timestamp))
+}
+
+struct Cleared {
+    backup: Option<PathBuf>,
+    // The backup is complete, but what it was moved from is still (partly)
+    // there
+    leftover: Option<String>,
+}
+
+// Clears the way for a new install: a link is just removed, a real folder
+// is moved into `backup_root`
+fn clear_target(target: &Path, backup_root: &Path, mod_id: &str) -> io::Result<Cleared> {
+    if is_link(target) {
+        remove_link(target)?;
+        return Ok(Cleared {
+            backup: None,
+            leftover: None,
+        });
+    }
+    if !target.exists() {
+        return Ok(Cleared {
+            backup: None,
+            leftover: None,
+        });
+    }
+    let backup = timestamped_backup(backup_root, mod_id);
+    fs::create_dir_all(backup_root)?;
+    let leftover = move_dir(target, &backup)?;
+    Ok(Cleared {
+        backup: Some(backup),
+        leftover,
+    })
+}
+
+fn copy_dir_contents(from: &Path, to: &Path) -> io::Result<()> {
+    for entry in fs::read_dir(from)? {
+        let entry = entry?;
+        let target = to.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            fs::create_dir(&target)?;
+            copy_dir_contents(&entry.path(), &target)?;
+        } else {
+            fs::copy(entry.path(), &target)?;
+        }
+    }
+    Ok(())
+}
+
+// Renames, falling back to copy and delete when the two paths are on
+// different drives. Only an incomplete copy is ever cleaned up: once `to`
+// holds everything, it is the copy to keep, so a source that then can't be
+// deleted is left next to it and described in the returned warning.
+fn move_dir(from: &Path, to: &Path) -> io::Result<Option<String>> {
+    if fs::rename(from, to).is_ok() {
+        return Ok(None);
+    }
+    fs::create_dir(to)?;
+    if let Err(e) = copy_dir_contents(from, to) {
+        let _ = fs::remove_dir_all(to);
+        return Err(e);
+    }
+    Ok(fs::remove_dir_all(from).err().map(|e| {
+        format!(
+            "{} was copied to {}, but couldn't be removed afterwards: {}",
+            from.display(),
+            to.display(),
+            e
+        )
+    }))
+}
+
+/// Installs the mod at `source` as `mods_dir/<mod_id>`, by copying it or by
+/// linking to it. An existing linked install is replaced; an existing copy is
+/// moved into `backup_root` first. Copies leave out version control and
+/// local history.
+pub fn install(
+    source: &Path,
+    mods_dir: &Path,
+    mod_id: &str,
+    mode: InstallMode,
+    backup_root: &Path,
+) -> Result<Installed, String> {
+    mod_identity::validate_mod_id(mod_id)?;
+    if !mods_dir.is_dir() {
+        return Err(format!("{} is not a folder", mods_dir.display()));
+    }
+    let source_canonical = paths::canonicalize_lenient(source);
+    if paths::canonicalize_lenient(mods_dir).starts_with(&source_canonical) {
+        return Err("The mods folder can't be inside the mod being installed".to_string());
+    }
+    let target = mods_dir.join(mod_id);
+    if !is_link(&target) && paths::canonicalize_lenient(&target) == source_canonical {
+        return Err(format!(
+            "{} is already the installed copy",
+            source.display()
+        ));
+    }
+
+    if mode == InstallMode::Symlink {
+        let cleared = clear_target(&target, backup_root, mod_id)
+            .map_err(|e| format!("Failed to replace {}: {}", target.display(), e))?;
+        if let Some(leftover) = cleared.leftover {
+            return Err(format!(
+                "Failed to replace {}: {}",
+                target.display(),
+                leftover
+            ));
+        }
+        let backup = cleared.backup;
+        return match create_link(&source_canonical, &target) {
+            Ok(link_kind) => Ok(Installed {
+                path: target,
+                backup,
+                link_kind: Some(link_kind),
+            }),
+            Err(e) => {
+                if let Some(backup) = &backup {
+                    let _ = move_dir(backup, &target);
+                }
+                Err(e)
+            }
+        };
+    }
+
+    let staging = mods_dir.join(format!(".{}.installing", mod_id));
+    // Left over from an install that was interrupted
+    let _ = fs::remove_dir_all(&staging);
+    if let Err(e) = mod_identity::copy_mod_dir(source, &staging) {
+        let _ = fs::remove_dir_all(&staging);
+        return Err(format!("Failed to copy {}: {}", source.display(), e));
+    }
+
+    let backup = match clear_target(&target, backup_root, mod_id) {
+        // The previous install is safe in its backup, but still in the way
+        Ok(Cleared {
+            leftover: Some(leftover),
+            ..
+        }) => {
+            let _ = fs::remove_dir_all(&staging);
+            return Err(format!(
+                "Failed to replace {}: {}",
+                target.display(),
+                leftover
+            ));
+        }
+        Ok(cleared) => cleared.backup,
+        Err(e) => {
+            let _ = fs::remove_dir_all(&staging);
+            return Err(format!("Failed to back up {}: {}", target.display(), e));
+        }
+    };
+
+    if let Err(e) = fs::rename(&staging, &target) {
+        let _ = fs::remove_dir_all(&staging);
+        // Put the previous install back rather than leave none
+        if let Some(backup) = &backup {
+            let _ = move_dir(backup, &target);
+        }
+        return Err(format!("Failed to install to {}: {}", target.display(), e));
+    }
+
+    Ok(Installed {
+        path: target,
+        backup,
+        link_kind: None,
+    })
+}
+
+/// Removes `mods_dir/<mod_id>`. A link is deleted and what it points to is
+/// left alone; a copied install is moved into `backup_root`.
+pub fn uninstall(mods_dir: &Path, mod_id: &str, backup_root: &Path) -> Result<Uninstalled, String> {
+    mod_identity::validate_mod_id(mod_id)?;
+    let target = mods_dir.join(mod_id);
+    if !is_link(&target) && !target.exists() {
+        return Err(format!("{} is not installed", mod_id));
+    }
+    let was_link = is_link(&target);
+    let cleared = clear_target(&target, backup_root, mod_id)
+        .map_err(|e| format!("Failed to remove {}: {}", target.display(), e))?;
+    Ok(Uninstalled {
+        backup: cleared.backup,
+        was_link,
+        warning: cleared.leftover,
+    })
+}
@@ -0,0 +1,110 @@
+// The order a mod's scripts load in. The manifest's `scripts` list gives the
+// entry points in order; a script's includes load before it, in the order
+// they are written, and a `.rson` list loads its scripts in sequence. Every
+// script loads once, the first time anything reaches it. Includes resolve
+// through `references::include_graph`, as orphan detection's do.
+
+use crate::references::{self, IncludeGraph};
+use crate::{manifest, paths};
+use serde::Serialize;
+use serde_json::Value;
+use std::io;
+use std::path::Path;
+
+const SCRIPT_EXTENSIONS: &[&str] = &["nut", "gnut"];
+
+#[derive(Debug, Default, Serialize)]
+pub struct LoadOrder {
+    // Mod-relative scripts in the order they load
+    pub order: Vec<String>,
+    // Scripts no entry point leads to, sorted
+    pub unreachable: Vec<String>,
+    // Each cycle as the files around it, starting and ending with the same one
+    pub cycles: Vec<Vec<String>>,
+}
+
+fn extension_of(relative: &str) -> String {
+    Path::new(relative)
+        .extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .unwrap_or_default()
+}
+
+fn is_script(relative: &str) -> bool {
+    SCRIPT_EXTENSIONS.contains(&extension_of(relative).as_str())
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Mark {
+    Unvisited,
+    // On the current path, so reaching it again closes a cycle
+    Visiting,
+    Done,
+}
+
+fn visit(
+    graph: &IncludeGraph,
+    node: usize,
+    marks: &mut [Mark],
+    stack: &mut Vec<usize>,
+    out: &mut LoadOrder,
+) {
+    marks[node] = Mark::Visiting;
+    stack.push(node);
+    for &next in &graph.edges[node] {
+        match marks[next] {
+            Mark::Unvisited => visit(graph, next, marks, stack, out),
+            Mark::Visiting => {
+                let start = stack.iter().position(|&n| n == next).unwrap_or(0);
+                let mut cycle: Vec<String> = stack[start..]
+                    .iter()
+                    .map(|&n| graph.files[n].relative.clone())
+                    .collect();
+                cycle.push(graph.files[next].relative.clone());
+                out.cycles.push(cycle);
+            }
+            Mark::Done => {}
+        }
+    }
+    stack.pop();
+    marks[node] = Mark::Done;
+    // Lists and anything else an include reaches only pass scripts on
+    if is_script(&graph.files[node].relative) {
+        out.order.push(graph.files[node].relative.clone());
+    }
+}
+
+/// Resolves the load order of the scripts under `mod_root`. Files
+/// `.r5vignore` lists are left out. Include paths match case-insensitively,
+/// as the game's lookups do.
+pub fn compute(mod_root: &Path, manifest: &Value) -> io::Result<LoadOrder> {
+    let graph = references::include_graph(mod_root)?;
+
+    // Declared paths come back resolved, so compare against the resolved root
+    let root = paths::canonicalize_lenient(mod_root);
+    let (declared, _) = manifest::declared_files(manifest, mod_root);
+    let entry_points: Vec<usize> = declared
+        .iter()
+        .filter(|file| file.section == "scripts")
+        .filter_map(|file| file.path.as_deref())
+        .filter_map(|path| paths::relative_slash_path(&root, Path::new(path)))
+        .filter_map(|relative| graph.find(&relative))
+        .collect();
+
+    let mut out = LoadOrder::default();
+    let mut marks = vec![Mark::Unvisited; graph.files.len()];
+    let mut stack = Vec::new();
+    for entry in entry_points {
+        if marks[entry] == Mark::Unvisited {
+            visit(&graph, entry, &mut marks, &mut stack, &mut out);
+        }
+    }
+    out.unreachable = graph
+        .files
+        .iter()
+        .zip(&marks)
+        .filter(|(file, mark)| **mark == Mark::Unvisited && is_script(&file.relative))
+        .map(|(file, _)| file.relative.clone())
+        .collect();
+    Ok(out)
+}
@@ -0,0 +1,171 @@
+// Advisory locking around writes, so two programs saving the same file don't
+// interleave. Locks are advisory: tools that don't lock are not stopped.
+
+use std::fs::{File, OpenOptions, TryLockError};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+
+// Windows reports files held open without sharing as these rather than as a
+// failed lock
+#[cfg(windows)]
+const ERROR_SHARING_VIOLATION: i32 = 32;
+#[cfg(windows)]
+const ERROR_LOCK_VIOLATION: i32 = 33;
+
+fn locked_error(path: &Path) -> String {
+    format!("Locked: {} is in use by another program", path.display())
+}
+
+fn is_sharing_violation(e: &io::Error) -> bool {
+    #[cfg(windows)]
+    {
+        matches!(
+            e.raw_os_error(),
+            Some(ERROR_SHARING_VIOLATION) | Some(ERROR_LOCK_VIOLATION)
+        )
+    }
+    #[cfg(not(windows))]
+    {
+        let _ = e;
+        false
+    }
+}
+
+fn describe(path: &Path, e: io::Error) -> String {
+    if is_sharing_violation(&e) {
+        locked_error(path)
+    } else {
+        e.to_string()
+    }
+}
+
+/// Replaces the contents of `path` while holding an exclusive lock on it.
+/// Fails with a "Locked:" error instead of waiting when someone else holds
+/// the lock. A durable write is flushed to disk, along with the directory
+/// entry, before returning.
+pub fn write_locked(path: &Path, data: &[u8], durable: bool) -> Result<(), String> {
+    // Not truncated on open: the old contents must stay until we own the lock
+    let mut file = OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(path)
+        .map_err(|e| describe(path, e))?;
+    match file.try_lock() {
+        Ok(()) => {}
+        Err(TryLockError::WouldBlock) => return Err(locked_error(path)),
+        Err(TryLockError::Error(e)) => return Err(describe(path, e)),
+    }
+
+    file.set_len(0)
+        .and_then(|_| file.write_all(data))
+        .and_then(|_| file.flush())
+        .and_then(|_| if durable { file.sync_all() } else { Ok(()) })
+        .and_then(|_| if durable { sync_parent(path) } else { Ok(()) })
+        .map_err(|e| describe(path, e))
+    // The lock is released when the file is closed
+}
+
+/// Overwrites `data.len()` bytes of an existing file at `offset`, locked as
+/// in `write_locked`, leaving the rest as it was. Writing past the end
+/// extends the file; a gap before `offset` reads as zeros. Returns the new
+/// file size.
+pub fn write_at_locked(
+    path: &Path,
+    offset: u64,
+    data: &[u8],
+    durable: bool,
+) -> Result<u64, String> {
+    let mut file = OpenOptions::new()
+        .write(true)
+        .open(path)
+        .map_err(|e| describe(path, e))?;
+    match file.try_lock() {
+        Ok(()) => {}
+        Err(TryLockError::WouldBlock) => return Err(locked_error(path)),
+        Err(TryLockError::Error(e)) => return Err(describe(path, e)),
+    }
+
+    file.seek(SeekFrom::Start(offset))
+        .and_then(|_| file.write_all(data))
+        .and_then(|_| file.flush())
+        .and_then(|_| if durable { file.sync_all() } else { Ok(()) })
+        .and_then(|_| file.metadata())
+        .map(|metadata| metadata.len())
+        .map_err(|e| describe(path, e))
+}
+
+/// Sets the length of `path`, locked as in `write_locked`, creating it empty
+/// first if it doesn't exist. Growing pads with zeros. The file is never replaced, so
+/// other programs holding it open keep a valid handle.
+pub fn set_len_locked(path: &Path, len: u64) -> Result<(), String> {
+    let file = OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(path)
+        .map_err(|e| describe(path, e))?;
+    match file.try_lock() {
+        Ok(()) => {}
+        Err(TryLockError::WouldBlock) => return Err(locked_error(path)),
+        Err(TryLockError::Error(e)) => return Err(describe(path, e)),
+    }
+    file.set_len(len).map_err(|e| describe(path, e))
+}
+
+// A newly created file can still vanish on power loss until its directory
+// is synced too. Windows can't open directories this way, and NTFS journals
+// the entry anyway.
+fn sync_parent(path: &Path) -> io::Result<()> {
+    #[cfg(unix)]
+    {
+        match path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            Some(parent) => File::open(parent)?.sync_all(),
+            None => File::open(".")?.sync_all(),
+        }
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = path;
+        Ok(())
+    }
+}
+
+/// Whether another handle currently holds a lock on `path`.
+pub fn is_locked(path: &Path) -> io::Result<bool> {
+    let file = match File::open(path) {
+        Ok(file) => file,
+        Err(e) if is_sharing_violation(&e) => return Ok(true),
+        Err(e) => return Err(e),
+    };
+    match file.try_lock() {
+        Ok(()) => {
+            file.unlock()?;
+            Ok(false)
+        }
+        Err(TryLockError::WouldBlock) => Ok(true),
+        Err(TryLockError::Error(e)) if is_sharing_violation(&e) => Ok(true),
+        Err(TryLockError::Error(e)) => Err(e),
+    }
+}
+
+/// Reads all of `path`, and whether another program held an exclusive lock
+/// on it at the time, in which case the content is a snapshot that program
+/// may still be changing. The check takes a shared lock and drops it at
+/// once, so it never holds up a writer; files are opened with full sharing
+/// either way, so other programs having them open doesn't block the read.
+pub fn read_shared(path: &Path) -> io::Result<(Vec<u8>, bool)> {
+    let mut file = File::open(path)?;
+    // Only informational, so a failed check doesn't fail the read
+    let locked = match file.try_lock_shared() {
+        Ok(()) => {
+            let _ = file.unlock();
+            false
+        }
+        Err(TryLockError::WouldBlock) => true,
+        Err(TryLockError::Error(e)) => is_sharing_violation(&e),
+    };
+    let mut data = Vec::new();
+    file.read_to_end(&mut data)?;
+    Ok((data, locked))
+}
@@ -1,25 +1,127 @@
 // Prevents additional console window on Windows in release
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
+mod backups;
+mod compare;
+mod compat;
+mod content_hash;
+mod diff;
+mod disk;
+mod encoding;
+mod export;
+mod file_type;
+mod external;
+mod game_install;
+mod history;
+mod ignore;
+mod install;
+mod load_order;
+mod locking;
+mod manifest;
+mod normalize;
+mod mod_identity;
+mod operations;
+mod orphans;
+mod paks;
+mod paths;
+mod permissions;
+mod project_diff;
+mod references;
+mod search;
+mod squirrel;
+mod templates;
+mod timing;
+mod ui_resources;
+mod vdf;
+mod versions;
+mod watch;
+
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use encoding::TextEncoding;
 use flate2::read::GzDecoder;
 use flate2::write::GzEncoder;
 use flate2::Compression;
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use rayon::prelude::*;
+use std::collections::BTreeMap;
 use std::fs;
-use std::io::{Read, Write};
-use std::path::Path;
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::ops::ControlFlow;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, Manager};
 
 // Magic bytes for R5V project files: "R5VP"
 const MAGIC_BYTES: [u8; 4] = [0x52, 0x35, 0x56, 0x50];
 
+// Sidecar metadata lives next to the project as "<project>.r5vp.meta"
+const PROJECT_METADATA_SUFFIX: &str = ".meta";
+
+// Per-file tags for a mod, stored at the mod root (relative path -> tags)
+const TAGS_FILE_NAME: &str = ".r5vtags.json";
+
+// App settings, stored in the platform config directory
+const SETTINGS_FILE_NAME: &str = "settings.json";
+
+// Open mod and tabs from the last run, kept apart from the settings
+const SESSION_FILE_NAME: &str = "session.json";
+
+// read_file refuses larger files unless forced; overridable in settings
+const DEFAULT_MAX_READ_BYTES: u64 = 50 * 1024 * 1024;
+
+// read_binary_ranges limits, per range and for one call, on the bytes that
+// would actually be read
+const MAX_BINARY_RANGE_BYTES: u64 = 16 * 1024 * 1024;
+const MAX_BINARY_RANGES_TOTAL_BYTES: u64 = 64 * 1024 * 1024;
+
+// Files recent_files_in_mod returns when no limit is given
+const DEFAULT_RECENT_FILES: usize = 20;
+
+// Snapshots kept per file in its local history; overridable in settings
+const DEFAULT_SNAPSHOT_RETENTION: usize = 20;
+
+// Emitted with the path of a project the OS asked us to open once the
+// frontend has taken the launch project
+const OPEN_PROJECT_EVENT: &str = "open-project-on-launch";
+
+// search_in_files streams batches of matches, then a summary
+const SEARCH_RESULT_EVENT: &str = "search-result";
+const SEARCH_DONE_EVENT: &str = "search-done";
+const SEARCH_BATCH_SIZE: usize = 50;
+
+// export_mod_zip reports each file added to the archive
+const EXPORT_PROGRESS_EVENT: &str = "export-progress";
+
+// watch_manifest reports each settled change to a mod's manifest.json
+const MANIFEST_UPDATED_EVENT: &str = "manifest-updated";
+const MANIFEST_PARSE_ERROR_EVENT: &str = "manifest-parse-error";
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct FileItem {
+    // Survives renames where the platform allows it (see file_item_id)
+    #[serde(default)]
+    id: String,
     name: String,
     path: String,
     #[serde(rename = "type")]
     item_type: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     children: Option<Vec<FileItem>>,
+    // Files only: size in bytes and last modification in ms since the epoch
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    size: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    modified: Option<u64>,
+    // Folders cut off at the depth limit that have entries; their children
+    // come from expand_directory
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    truncated: Option<bool>,
+    // Set when the entry exists but couldn't be read
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -30,6 +132,15 @@ pub struct DirectoryItem {
     path: String,
 }
 
+#[derive(Debug, Serialize)]
+pub struct FolderItem {
+    name: String,
+    path: String,
+    // Whether it has subfolders of its own, for the picker's expand arrow
+    #[serde(rename = "hasChildren")]
+    has_children: bool,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ModData {
     name: String,
@@ -41,12 +152,108 @@ pub struct ModData {
     path: String,
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CursorPosition {
+    x: f64,
+    y: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    zoom: Option<f64>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ProjectMetadata {
+    #[serde(default)]
+    tags: Vec<String>,
+    #[serde(default)]
+    notes: String,
+    #[serde(rename = "lastCursor", default, skip_serializing_if = "Option::is_none")]
+    last_cursor: Option<CursorPosition>,
+    // Thumbnail image as a data URL
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    thumbnail: Option<String>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct AppSettings {
+    #[serde(rename = "modDirectories", default)]
+    mod_directories: Vec<String>,
+    // Adds elapsed_ms to command results and collects timing stats
+    #[serde(rename = "timingEnabled", default)]
+    timing_enabled: bool,
+    #[serde(rename = "maxReadFileBytes", default, skip_serializing_if = "Option::is_none")]
+    max_read_file_bytes: Option<u64>,
+    #[serde(rename = "snapshotRetention", default, skip_serializing_if = "Option::is_none")]
+    snapshot_retention: Option<usize>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct SessionState {
+    #[serde(rename = "modRoot", default, skip_serializing_if = "Option::is_none")]
+    mod_root: Option<String>,
+    #[serde(rename = "openFiles", default)]
+    open_files: Vec<String>,
+    #[serde(rename = "activeFile", default, skip_serializing_if = "Option::is_none")]
+    active_file: Option<String>,
+    // FileItem ids of the expanded tree folders
+    #[serde(rename = "expandedNodes", default)]
+    expanded_nodes: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ModDirectoryInfo {
+    path: String,
+    exists: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ModSummary {
+    name: String,
+    #[serde(rename = "modId")]
+    mod_id: String,
+    version: String,
+    path: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ModRootScan {
+    root: String,
+    exists: bool,
+    mods: Vec<ModSummary>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
 // Response types
 #[derive(Debug, Serialize)]
 pub struct ReadFileResult {
     success: bool,
     #[serde(skip_serializing_if = "Option::is_none")]
     content: Option<String>,
+    // File size in bytes, reported when the file is over the read limit
+    #[serde(skip_serializing_if = "Option::is_none")]
+    size: Option<u64>,
+    // Set when max_line_length cut at least one line short
+    #[serde(skip_serializing_if = "Option::is_none")]
+    lines_truncated: Option<bool>,
+    // Set when another program had the file locked; the content is what it
+    // held at that moment and may change under it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    read_only_snapshot: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReadFileTokenizedResult {
+    success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    content: Option<String>,
+    // Empty for anything but squirrel scripts
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tokens: Option<Vec<squirrel::HighlightToken>>,
+    // File size in bytes, reported when the file is over the read limit
+    #[serde(skip_serializing_if = "Option::is_none")]
+    size: Option<u64>,
     #[serde(skip_serializing_if = "Option::is_none")]
     error: Option<String>,
 }
@@ -59,445 +266,7507 @@ pub struct WriteFileResult {
 }
 
 #[derive(Debug, Serialize)]
-pub struct ProjectFileReadResult {
+pub struct NameCollisionResult {
     success: bool,
     #[serde(skip_serializing_if = "Option::is_none")]
-    content: Option<String>,
+    collides: Option<bool>,
+    // The entry the name would collide with on a case-insensitive
+    // filesystem
     #[serde(skip_serializing_if = "Option::is_none")]
-    compressed: Option<bool>,
+    existing: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     error: Option<String>,
 }
 
 #[derive(Debug, Serialize)]
-pub struct ProjectFileWriteResult {
+pub struct RecentFile {
+    path: String,
+    // Forward-slash path relative to the mod root
+    relative: String,
+    modified: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RecentFilesResult {
     success: bool,
     #[serde(skip_serializing_if = "Option::is_none")]
-    original_size: Option<usize>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    compressed_size: Option<usize>,
+    files: Option<Vec<RecentFile>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     error: Option<String>,
 }
 
 #[derive(Debug, Serialize)]
-pub struct ListDirectoryResult {
+pub struct ValidateForVersionResult {
     success: bool,
     #[serde(skip_serializing_if = "Option::is_none")]
-    items: Option<Vec<DirectoryItem>>,
+    findings: Option<Vec<compat::Finding>>,
+    // False when any finding is an error
+    #[serde(skip_serializing_if = "Option::is_none")]
+    loadable: Option<bool>,
     #[serde(skip_serializing_if = "Option::is_none")]
     error: Option<String>,
 }
 
 #[derive(Debug, Serialize)]
-pub struct OpenModFolderResult {
+pub struct ScanUiResourcesResult {
     success: bool,
     #[serde(skip_serializing_if = "Option::is_none")]
-    tree: Option<Vec<FileItem>>,
+    found: Option<Vec<ui_resources::UiResource>>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    root_path: Option<String>,
+    missing: Option<Vec<ui_resources::UiResource>>,
+    // Declaration files that could not be read or parsed, as "path: error"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    failed: Option<Vec<String>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     error: Option<String>,
 }
 
 #[derive(Debug, Serialize)]
-pub struct CreateModResult {
+pub struct ModIdConsistency {
+    folder: String,
+    // Not set when mod.vdf or the manifest's modId is missing
+    #[serde(skip_serializing_if = "Option::is_none")]
+    vdf_key: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    manifest_id: Option<String>,
+    // Whether every id present equals the folder name
+    consistent: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ModIdConsistencyResult {
     success: bool,
+    // After fixing, when auto_fix was set
     #[serde(skip_serializing_if = "Option::is_none")]
-    path: Option<String>,
+    ids: Option<ModIdConsistency>,
+    // Files auto_fix rewrote
+    #[serde(skip_serializing_if = "Option::is_none")]
+    fixed: Option<Vec<String>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     error: Option<String>,
 }
 
-// Commands
+#[derive(Debug, Serialize)]
+pub struct ProjectFileReadResult {
+    success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    content: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    compressed: Option<bool>,
+    // On-disk format the content was stored in: 0 for legacy plain text
+    #[serde(skip_serializing_if = "Option::is_none")]
+    format_version: Option<u32>,
+    // Older than what write_project_file writes; migrate_project upgrades it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    needs_migration: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
 
-#[tauri::command]
-async fn read_file(file_path: String) -> ReadFileResult {
-    match fs::read_to_string(&file_path) {
-        Ok(content) => ReadFileResult {
-            success: true,
-            content: Some(content),
-            error: None,
-        },
-        Err(e) => ReadFileResult {
-            success: false,
-            content: None,
-            error: Some(e.to_string()),
-        },
-    }
+#[derive(Debug, Serialize)]
+pub struct MigrateProjectResult {
+    success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    previous_version: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    format_version: Option<u32>,
+    // False when the file was already in the current format
+    #[serde(skip_serializing_if = "Option::is_none")]
+    migrated: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
 }
 
-#[tauri::command]
-async fn write_file(file_path: String, content: String) -> WriteFileResult {
-    match fs::write(&file_path, content) {
-        Ok(_) => WriteFileResult {
-            success: true,
-            error: None,
-        },
-        Err(e) => WriteFileResult {
-            success: false,
-            error: Some(e.to_string()),
-        },
-    }
+#[derive(Debug, Serialize)]
+pub struct ProjectKeySummary {
+    key: String,
+    // "object", "array", "string", "number", "boolean" or "null"
+    kind: &'static str,
+    // Key count, element count or string length in characters; none for
+    // the other kinds
+    #[serde(skip_serializing_if = "Option::is_none")]
+    size: Option<usize>,
 }
 
-#[tauri::command]
-async fn read_project_file(file_path: String) -> ProjectFileReadResult {
-    match fs::read(&file_path) {
-        Ok(data) => {
-            // Check for magic bytes
-            if data.len() >= 4 && data[0..4] == MAGIC_BYTES {
-                // Compressed file
-                let compressed_data = &data[4..];
-                let mut decoder = GzDecoder::new(compressed_data);
-                let mut decompressed = String::new();
-                
-                match decoder.read_to_string(&mut decompressed) {
-                    Ok(_) => ProjectFileReadResult {
-                        success: true,
-                        content: Some(decompressed),
-                        compressed: Some(true),
-                        error: None,
-                    },
-                    Err(e) => ProjectFileReadResult {
-                        success: false,
-                        content: None,
-                        compressed: None,
-                        error: Some(format!("Failed to decompress: {}", e)),
-                    },
-                }
-            } else {
-                // Plain text file
-                match String::from_utf8(data) {
-                    Ok(content) => ProjectFileReadResult {
-                        success: true,
-                        content: Some(content),
-                        compressed: Some(false),
-                        error: None,
-                    },
-                    Err(e) => ProjectFileReadResult {
-                        success: false,
-                        content: None,
-                        compressed: None,
-                        error: Some(e.to_string()),
-                    },
-                }
-            }
-        }
-        Err(e) => ProjectFileReadResult {
-            success: false,
-            content: None,
-            compressed: None,
-            error: Some(e.to_string()),
-        },
-    }
+#[derive(Debug, Serialize)]
+pub struct DescribeProjectResult {
+    success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    keys: Option<Vec<ProjectKeySummary>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    compressed: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
 }
 
-#[tauri::command]
-async fn write_project_file(file_path: String, content: String) -> ProjectFileWriteResult {
-    let original_size = content.len();
-    
-    // Compress with gzip
-    let mut encoder = GzEncoder::new(Vec::new(), Compression::best());
-    if let Err(e) = encoder.write_all(content.as_bytes()) {
-        return ProjectFileWriteResult {
-            success: false,
-            original_size: None,
-            compressed_size: None,
-            error: Some(format!("Compression error: {}", e)),
-        };
-    }
-    
-    let compressed = match encoder.finish() {
-        Ok(data) => data,
-        Err(e) => {
-            return ProjectFileWriteResult {
-                success: false,
-                original_size: None,
-                compressed_size: None,
-                error: Some(format!("Compression finish error: {}", e)),
-            };
-        }
-    };
-    
-    // Prepend magic bytes
-    let mut final_data = MAGIC_BYTES.to_vec();
-    final_data.extend(compressed);
-    let compressed_size = final_data.len();
-    
-    match fs::write(&file_path, final_data) {
-        Ok(_) => ProjectFileWriteResult {
-            success: true,
-            original_size: Some(original_size),
-            compressed_size: Some(compressed_size),
-            error: None,
-        },
-        Err(e) => ProjectFileWriteResult {
-            success: false,
-            original_size: None,
-            compressed_size: None,
-            error: Some(e.to_string()),
-        },
-    }
+#[derive(Debug, Serialize)]
+pub struct ProjectChangelogResult {
+    success: bool,
+    // Set when both versions are JSON objects
+    #[serde(skip_serializing_if = "Option::is_none")]
+    changes: Option<Vec<project_diff::KeyChange>>,
+    // The line diff used otherwise
+    #[serde(skip_serializing_if = "Option::is_none")]
+    hunks: Option<Vec<diff::Hunk>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
 }
 
-#[tauri::command]
-async fn list_directory(dir_path: String) -> ListDirectoryResult {
-    match fs::read_dir(&dir_path) {
-        Ok(entries) => {
-            let mut items = Vec::new();
-            for entry in entries.flatten() {
-                let name = entry.file_name().to_string_lossy().to_string();
-                let path = entry.path().to_string_lossy().to_string();
-                let is_directory = entry.file_type().map(|t| t.is_dir()).unwrap_or(false);
-                items.push(DirectoryItem {
-                    name,
-                    is_directory,
-                    path,
-                });
-            }
-            ListDirectoryResult {
-                success: true,
-                items: Some(items),
-                error: None,
-            }
-        }
-        Err(e) => ListDirectoryResult {
-            success: false,
-            items: None,
-            error: Some(e.to_string()),
-        },
-    }
+#[derive(Debug, Serialize)]
+pub struct ProjectFileHeadResult {
+    success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    content: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    compressed: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    truncated: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
 }
 
-#[tauri::command]
-async fn create_directory(dir_path: String) -> WriteFileResult {
-    match fs::create_dir_all(&dir_path) {
-        Ok(_) => WriteFileResult {
-            success: true,
-            error: None,
-        },
-        Err(e) => WriteFileResult {
-            success: false,
-            error: Some(e.to_string()),
-        },
-    }
+#[derive(Debug, Serialize)]
+pub struct CompressFileResult {
+    success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    original_size: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    compressed_size: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
 }
 
-#[tauri::command]
-async fn delete_directory(dir_path: String) -> WriteFileResult {
-    let path = Path::new(&dir_path);
-    if !path.exists() {
-        return WriteFileResult {
-            success: true,
-            error: None,
-        };
-    }
-    
-    match fs::remove_dir_all(&dir_path) {
-        Ok(_) => WriteFileResult {
-            success: true,
-            error: None,
-        },
-        Err(e) => WriteFileResult {
-            success: false,
-            error: Some(e.to_string()),
-        },
-    }
+#[derive(Debug, Serialize)]
+pub struct DecompressGzipResult {
+    success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    content: Option<String>,
+    // Content isn't UTF-8 text and is returned as base64 instead
+    #[serde(skip_serializing_if = "Option::is_none")]
+    base64: Option<bool>,
+    // Decompressed size in bytes
+    #[serde(skip_serializing_if = "Option::is_none")]
+    size: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
 }
 
-fn build_file_tree(path: &Path, depth: usize, max_depth: usize) -> Vec<FileItem> {
-    if depth > max_depth {
-        return Vec::new();
-    }
-    
-    let mut items = Vec::new();
-    
-    if let Ok(entries) = fs::read_dir(path) {
-        let mut entries: Vec<_> = entries.flatten().collect();
-        // Sort: directories first, then by name
-        entries.sort_by(|a, b| {
-            let a_is_dir = a.file_type().map(|t| t.is_dir()).unwrap_or(false);
-            let b_is_dir = b.file_type().map(|t| t.is_dir()).unwrap_or(false);
-            match (a_is_dir, b_is_dir) {
-                (true, false) => std::cmp::Ordering::Less,
-                (false, true) => std::cmp::Ordering::Greater,
-                _ => a.file_name().cmp(&b.file_name()),
+#[derive(Debug, Serialize)]
+pub struct EncodeProjectResult {
+    success: bool,
+    // Base64 of exactly what write_project_file would write
+    #[serde(skip_serializing_if = "Option::is_none")]
+    data: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    original_size: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    compressed_size: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ProjectFileWriteResult {
+    success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    original_size: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    compressed_size: Option<usize>,
+    // With verify: the file read back and decompressed to the saved content
+    #[serde(skip_serializing_if = "Option::is_none")]
+    verified: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct VersionedWriteResult {
+    success: bool,
+    // The previous file, now archived; not set for a project's first save
+    #[serde(skip_serializing_if = "Option::is_none")]
+    archived: Option<versions::ProjectVersion>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    compressed_size: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ProjectVersionsResult {
+    success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    versions: Option<Vec<versions::ProjectVersion>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ModBackupResult {
+    success: bool,
+    // The backup made; on restore, the one holding the state it replaced
+    #[serde(skip_serializing_if = "Option::is_none")]
+    backup: Option<backups::ModBackup>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ModBackupsResult {
+    success: bool,
+    // Newest first; for pruning, the backups removed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    backups: Option<Vec<backups::ModBackup>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TemplateBundleResult {
+    success: bool,
+    // The written bundle on export, the installed template folder on import
+    #[serde(skip_serializing_if = "Option::is_none")]
+    path: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    template: Option<templates::TemplateDescriptor>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ListDirectoriesResult {
+    success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    items: Option<Vec<FolderItem>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ListDirectoryResult {
+    success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    items: Option<Vec<DirectoryItem>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct OpenModFolderResult {
+    success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tree: Option<Vec<FileItem>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    root_path: Option<String>,
+    // Present when the folder is a mod, i.e. has a mod.vdf
+    #[serde(skip_serializing_if = "Option::is_none")]
+    mod_data: Option<ModData>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    warnings: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DirectorySizeResult {
+    success: bool,
+    // Shippable size: everything not excluded
+    #[serde(skip_serializing_if = "Option::is_none")]
+    bytes: Option<u64>,
+    // Size of hidden and ignored entries
+    #[serde(skip_serializing_if = "Option::is_none")]
+    excluded_bytes: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    warnings: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MetadataDifference {
+    // "version" or "modId"
+    field: String,
+    // None when that mod has no readable metadata
+    a: Option<String>,
+    b: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CompareModsResult {
+    success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    files: Option<compare::FileComparison>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    metadata: Option<Vec<MetadataDifference>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct InstallModResult {
+    success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    path: Option<String>,
+    // Where a replaced install was moved to
+    #[serde(skip_serializing_if = "Option::is_none")]
+    backup_path: Option<String>,
+    // Linked installs: "symlink" or "junction"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    link_kind: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct UninstallModResult {
+    success: bool,
+    // Whether the install was a link, which is removed without a backup
+    #[serde(skip_serializing_if = "Option::is_none")]
+    was_link: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    backup_path: Option<String>,
+    // The mod was backed up but part of it couldn't be removed from the
+    // mods folder
+    #[serde(skip_serializing_if = "Option::is_none")]
+    warning: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ManifestFilesResult {
+    success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    files: Option<Vec<manifest::DeclaredFile>>,
+    // Declared in the manifest but not found in the mod
+    #[serde(skip_serializing_if = "Option::is_none")]
+    missing: Option<Vec<manifest::DeclaredFile>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct NormalizePermissionsResult {
+    success: bool,
+    // Changed, or with dry_run to be changed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    changes: Option<Vec<permissions::PermissionChange>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    failed: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ModContentHashResult {
+    success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    content: Option<content_hash::ContentHash>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CountTreeResult {
+    success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    files: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    directories: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    excluded_files: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    excluded_directories: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    warnings: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TreeNodeResult {
+    success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    node: Option<FileItem>,
+    // The path no longer exists, so its node should be dropped
+    #[serde(skip_serializing_if = "Option::is_none")]
+    removed: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    warnings: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+// A file as the editor last loaded it. With a hash, content decides; with
+// only a modification time, any change to it counts as modified.
+#[derive(Debug, Deserialize)]
+pub struct KnownFile {
+    path: String,
+    #[serde(default)]
+    modified: Option<u64>,
+    // SHA-256 of the content, hex
+    #[serde(default)]
+    hash: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FileChange {
+    path: String,
+    // "modified", "deleted" or "unchanged"
+    status: String,
+    // Current modification time, to remember for the next check
+    #[serde(skip_serializing_if = "Option::is_none")]
+    modified: Option<u64>,
+}
+
+// An entry of a folder as the UI last listed it
+#[derive(Debug, Deserialize)]
+pub struct ListedEntry {
+    name: String,
+    #[serde(default)]
+    modified: Option<u64>,
+    #[serde(default)]
+    size: Option<u64>,
+    // SHA-256 of the content, hex; only consulted when hashes are compared
+    #[serde(default)]
+    hash: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DirectoryEntry {
+    name: String,
+    is_directory: bool,
+    // Files only
+    #[serde(skip_serializing_if = "Option::is_none")]
+    size: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    modified: Option<u64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DirectoryDiffResult {
+    success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    added: Option<Vec<DirectoryEntry>>,
+    // Names no longer in the folder
+    #[serde(skip_serializing_if = "Option::is_none")]
+    removed: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    changed: Option<Vec<DirectoryEntry>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ExternallyModifiedResult {
+    success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    files: Option<Vec<FileChange>>,
+    // Files that couldn't be checked; they are left out of `files`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    warnings: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PathStat {
+    path: String,
+    exists: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    is_directory: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    size: Option<u64>,
+    // Milliseconds since the epoch
+    #[serde(skip_serializing_if = "Option::is_none")]
+    modified: Option<u64>,
+    // Set when the path exists but couldn't be read
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct StatPathsResult {
+    success: bool,
+    // One entry per requested path, in the order given
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stats: Option<Vec<PathStat>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CountLinesResult {
+    success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    lines: Option<u64>,
+    // In bytes
+    #[serde(skip_serializing_if = "Option::is_none")]
+    size: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FileTailResult {
+    success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    lines: Option<Vec<String>>,
+    // Byte offset in the file where the returned lines start
+    #[serde(skip_serializing_if = "Option::is_none")]
+    offset: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ByteRange {
+    offset: u64,
+    length: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BinaryRange {
+    offset: u64,
+    // Base64 of the bytes read
+    data: String,
+    // The range ran past the end of the file, so `data` is shorter than
+    // asked for (or empty)
+    truncated: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReadBinaryRangesResult {
+    success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    ranges: Option<Vec<BinaryRange>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    file_size: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FileSizeResult {
+    success: bool,
+    // File size after the change
+    #[serde(skip_serializing_if = "Option::is_none")]
+    size: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Utf8Problem {
+    // Byte offset of the first invalid sequence, and its 1-based line
+    offset: usize,
+    line: usize,
+    // The invalid sequence itself; empty when the file ends mid-character
+    bytes: Vec<u8>,
+    // Up to UTF8_CONTEXT_BYTES on either side, starting at context_start
+    context_start: usize,
+    context: Vec<u8>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Utf8ValidationResult {
+    success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    valid: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    problem: Option<Utf8Problem>,
+    // An encoding name read_file accepts, when the file isn't UTF-8
+    #[serde(skip_serializing_if = "Option::is_none")]
+    guessed_encoding: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct EncodedFile {
+    path: String,
+    encoding: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct EncodingScanResult {
+    success: bool,
+    // Text files per detected encoding
+    #[serde(skip_serializing_if = "Option::is_none")]
+    counts: Option<BTreeMap<String, usize>>,
+    // Sorted by path
+    #[serde(skip_serializing_if = "Option::is_none")]
+    non_utf8: Option<Vec<EncodedFile>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    failed: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ConvertToUtf8Result {
+    success: bool,
+    // Converted, or with dry_run to be converted, with their old encoding
+    #[serde(skip_serializing_if = "Option::is_none")]
+    converted: Option<Vec<EncodedFile>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    already_utf8: Option<Vec<String>>,
+    // Left alone because their encoding couldn't be told, as "path: reason"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    skipped: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    failed: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ListAllFilesResult {
+    success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    files: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    warnings: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FlatIndexResult {
+    success: bool,
+    // Root-relative, forward slashes; sizes[i] belongs to paths[i]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    paths: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    sizes: Option<Vec<u64>>,
+    // Changes whenever a path or size does
+    #[serde(skip_serializing_if = "Option::is_none")]
+    hash: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    warnings: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ExpandDirectoryResult {
+    success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    items: Option<Vec<FileItem>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    warnings: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CreateModResult {
+    success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    path: Option<String>,
+    // Preview only: whether the mod directory is already taken
+    #[serde(skip_serializing_if = "Option::is_none")]
+    already_exists: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    plan: Option<ModPlan>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CreateUniqueFileResult {
+    success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    path: Option<String>,
+    // The name chosen, which differs from the one asked for after a collision
+    #[serde(skip_serializing_if = "Option::is_none")]
+    name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct GitignoreResult {
+    success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    path: Option<String>,
+    // False when the mod already had a .gitignore, which is kept as is
+    #[serde(skip_serializing_if = "Option::is_none")]
+    written: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PlannedFile {
+    path: String,
+    content: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ModPlan {
+    root: String,
+    directories: Vec<String>,
+    files: Vec<PlannedFile>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ProjectMetadataResult {
+    success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    metadata: Option<ProjectMetadata>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    exists: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MovePathResult {
+    success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    path: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    warning: Option<String>,
+    // Include paths rewritten (or, on a dry run, that would be) after a folder move
+    #[serde(skip_serializing_if = "Option::is_none")]
+    reference_changes: Option<Vec<references::ReferenceChange>>,
+    // Set when only the casing changed and the rename went through a temp name
+    #[serde(skip_serializing_if = "Option::is_none")]
+    case_only_rename: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PlannedRename {
+    from: String,
+    to: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RenameExtensionResult {
+    success: bool,
+    // The renames planned (dry run) or carried out
+    #[serde(skip_serializing_if = "Option::is_none")]
+    renames: Option<Vec<PlannedRename>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    reference_changes: Option<Vec<references::ReferenceChange>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    warning: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FilesByTagResult {
+    success: bool,
+    // Tag -> absolute paths of the files carrying it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    groups: Option<BTreeMap<String, Vec<String>>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PruneTagsResult {
+    success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    removed: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DataDirResult {
+    success: bool,
+    // Holds settings.json
+    #[serde(skip_serializing_if = "Option::is_none")]
+    config_dir: Option<String>,
+    // Holds session.json
+    #[serde(skip_serializing_if = "Option::is_none")]
+    data_dir: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct LoadSessionResult {
+    success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    session: Option<SessionState>,
+    // One note per entry dropped because it no longer exists
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pruned: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ModDirectoriesResult {
+    success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    directories: Option<Vec<ModDirectoryInfo>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ScanModsResult {
+    success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    roots: Option<Vec<ModRootScan>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DetectGameInstallResult {
+    success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    candidates: Option<Vec<game_install::InstallCandidate>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SteamLibrariesResult {
+    success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    libraries: Option<Vec<game_install::LibraryInfo>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    warnings: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DiffResult {
+    success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    hunks: Option<Vec<diff::Hunk>>,
+    // The file no longer exists on disk, so every buffer line shows as removed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    deleted: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    binary: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ExportMarkdownResult {
+    success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    content: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    path: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReconcileManifestResult {
+    success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    drift: Option<manifest::ScriptDrift>,
+    // Whether manifest.json was rewritten
+    #[serde(skip_serializing_if = "Option::is_none")]
+    fixed: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ValidatePaksResult {
+    success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    warnings: Option<Vec<paks::PakWarning>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ExportModZipResult {
+    success: bool,
+    // Not set in report-only mode, where no archive is written
+    #[serde(skip_serializing_if = "Option::is_none")]
+    path: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    file_count: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    duplicates: Option<Vec<export::DuplicateGroup>>,
+    // True when the export was cancelled; no archive is left behind
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cancelled: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BomFilesResult {
+    success: bool,
+    // Mod-relative paths of the files that have (or had) a BOM
+    #[serde(skip_serializing_if = "Option::is_none")]
+    files: Option<Vec<String>>,
+    // Files that could not be rewritten, as "path: error"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    failed: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct NormalizedFile {
+    path: String,
+    #[serde(flatten)]
+    changes: normalize::NormalizeChanges,
+}
+
+#[derive(Debug, Serialize)]
+pub struct JsonIssue {
+    path: String,
+    // 1-based, where the parser gave up
+    line: usize,
+    column: usize,
+    message: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ValidateJsonResult {
+    success: bool,
+    // How many files were parsed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    checked: Option<usize>,
+    // Only the files that don't parse
+    #[serde(skip_serializing_if = "Option::is_none")]
+    files: Option<Vec<JsonIssue>>,
+    // Files that could not be read, as "path: error"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    failed: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct WhitespaceIssues {
+    path: String,
+    // Lines ending in spaces or tabs
+    trailing_whitespace: usize,
+    missing_final_newline: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CheckWhitespaceResult {
+    success: bool,
+    // Only the files with something to fix
+    #[serde(skip_serializing_if = "Option::is_none")]
+    files: Option<Vec<WhitespaceIssues>>,
+    // Files that could not be read, as "path: error"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    failed: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct NormalizeScriptsResult {
+    success: bool,
+    // Only the scripts that changed (or would change, for a dry run)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    files: Option<Vec<NormalizedFile>>,
+    // Scripts that could not be read or rewritten, as "path: error"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    failed: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TimingStatsResult {
+    success: bool,
+    enabled: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    commands: Option<Vec<timing::CommandStats>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FileLockResult {
+    success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    locked: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SnapshotResult {
+    success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    snapshot: Option<history::Snapshot>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SnapshotListResult {
+    success: bool,
+    // Newest first
+    #[serde(skip_serializing_if = "Option::is_none")]
+    snapshots: Option<Vec<history::Snapshot>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DiskSpaceResult {
+    success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    available_bytes: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    total_bytes: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ResolvePathResult {
+    success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    path: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct IsWithinResult {
+    success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    within: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchBatch {
+    operation_id: Option<String>,
+    matches: Vec<search::SearchMatch>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchSummary {
+    operation_id: Option<String>,
+    total_matches: usize,
+    files_searched: usize,
+    cancelled: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SearchResult {
+    success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    summary: Option<SearchSummary>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CancelOperationResult {
+    success: bool,
+    // False when no operation with that id was running
+    cancelled: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RepairManifestResult {
+    success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    fixes: Option<Vec<String>>,
+    // Copy of the broken manifest, taken before it was rewritten
+    #[serde(skip_serializing_if = "Option::is_none")]
+    backup_path: Option<String>,
+    // The manifest was rebuilt from the create_mod skeleton
+    #[serde(skip_serializing_if = "Option::is_none")]
+    regenerated: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FormatScriptResult {
+    success: bool,
+    content: String,
+    changed: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BraceBalanceResult {
+    success: bool,
+    balanced: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    issue: Option<squirrel::BraceIssue>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FindOrphansResult {
+    success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    orphans: Option<Vec<orphans::Orphan>>,
+    // Bytes the orphans take up together
+    #[serde(skip_serializing_if = "Option::is_none")]
+    total_size: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct LoadOrderResult {
+    success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    order: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    unreachable: Option<Vec<String>>,
+    // Include cycles, each listing the files around it; the order is still
+    // given, with each cycle broken where it was found
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cycles: Option<Vec<Vec<String>>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ExportPathsZipResult {
+    success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    path: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    file_count: Option<usize>,
+    // Size of the finished zip in bytes
+    #[serde(skip_serializing_if = "Option::is_none")]
+    archive_size: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ExportProgress {
+    operation_id: Option<String>,
+    files_done: usize,
+    total_files: usize,
+    // Uncompressed bytes of the files added so far
+    bytes_written: u64,
+    total_bytes: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ManifestUpdate {
+    mod_root: String,
+    manifest: serde_json::Value,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ManifestParseError {
+    mod_root: String,
+    message: String,
+    // 1-based; none when the file couldn't be read at all
+    #[serde(skip_serializing_if = "Option::is_none")]
+    line: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    column: Option<usize>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct WatchManifestResult {
+    success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TakeLaunchProjectResult {
+    success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    project: Option<String>,
+}
+
+// Commands
+
+// Runs blocking filesystem work off the async runtime. With a timeout the
+// command gives up waiting and returns a Timeout error; the blocking task is
+// abandoned and finishes (or stays stuck) on its own.
+async fn run_blocking<T, F>(timeout_ms: Option<u64>, work: F) -> Result<T, String>
+where
+    T: Send + 'static,
+    F: FnOnce() -> T + Send + 'static,
+{
+    let task = tauri::async_runtime::spawn_blocking(work);
+    let joined = match timeout_ms {
+        Some(ms) => tokio::time::timeout(Duration::from_millis(ms), task)
+            .await
+            .map_err(|_| format!("Timeout: operation did not complete within {} ms", ms))?,
+        None => task.await,
+    };
+    joined.map_err(|e| format!("Background task failed: {}", e))
+}
+
+// Writes through a sibling temp file and a rename, so readers never see a
+// half-written file and a failed write leaves the original intact
+fn write_atomic(path: &Path, data: &[u8]) -> std::io::Result<()> {
+    let file_name = path
+        .file_name()
+        .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::InvalidInput, "No file name"))?;
+    let mut temp_name = std::ffi::OsString::from(".");
+    temp_name.push(file_name);
+    temp_name.push(".tmp");
+    let temp_path = path.with_file_name(temp_name);
+
+    let result = fs::File::create(&temp_path)
+        .and_then(|mut file| file.write_all(data).and_then(|_| file.sync_all()))
+        .and_then(|_| fs::rename(&temp_path, path));
+    if result.is_err() {
+        let _ = fs::remove_file(&temp_path);
+    }
+    result
+}
+
+// File commands take either an absolute `file_path` or a `mod_root` plus a
+// `relative_path` inside it. References stored relative to the mod keep
+// working when the mod is moved to another folder or machine.
+fn resolve_file_arg(
+    file_path: Option<String>,
+    mod_root: Option<&str>,
+    relative_path: Option<&str>,
+) -> Result<String, String> {
+    match (mod_root, relative_path, file_path) {
+        (Some(root), Some(relative), _) => paths::resolve_in_root(Path::new(root), relative)
+            .map(|path| path.to_string_lossy().to_string()),
+        (_, _, Some(file_path)) => Ok(file_path),
+        _ => Err("Either file_path or mod_root and relative_path is required".to_string()),
+    }
+}
+
+// Opt-in guard for saves: when a workspace root is given, the destination
+// must resolve to somewhere inside it
+fn check_workspace(workspace_root: Option<&str>, file_path: &str) -> Result<(), String> {
+    match workspace_root {
+        Some(root) => paths::ensure_within(Path::new(root), Path::new(file_path))
+            .map(|_| ())
+            .map_err(|e| format!("Refusing to save outside the workspace: {}", e)),
+        None => Ok(()),
+    }
+}
+
+// Cuts every line longer than `max_chars` characters down to that length
+// plus a marker. Line endings are kept as they were
+fn truncate_long_lines(content: String, max_chars: usize) -> (String, bool) {
+    let mut truncated = false;
+    let mut out = String::with_capacity(content.len().min(1 << 20));
+    for line in content.split_inclusive('\n') {
+        let body = line.trim_end_matches(['\r', '\n']);
+        match body.char_indices().nth(max_chars) {
+            Some((cut, _)) => {
+                truncated = true;
+                let dropped = body[cut..].chars().count();
+                out.push_str(&body[..cut]);
+                out.push_str(&format!(" \u{2026} [{} characters truncated]", dropped));
+                out.push_str(&line[body.len()..]);
+            }
+            None => out.push_str(line),
+        }
+    }
+    if truncated {
+        (out, true)
+    } else {
+        (content, false)
+    }
+}
+
+fn read_file_blocking(
+    file_path: String,
+    encoding: Option<String>,
+    max_bytes: Option<u64>,
+    max_line_length: Option<usize>,
+) -> ReadFileResult {
+    let encoding = match TextEncoding::from_name(encoding.as_deref()) {
+        Ok(encoding) => encoding,
+        Err(e) => {
+            return ReadFileResult {
+                success: false,
+                content: None,
+                size: None,
+                lines_truncated: None,
+                read_only_snapshot: None,
+                error: Some(e),
+            };
+        }
+    };
+
+    // Checked before reading so a huge file is never loaded into memory
+    if let (Some(max_bytes), Ok(metadata)) = (max_bytes, fs::metadata(&file_path)) {
+        if metadata.len() > max_bytes {
+            return ReadFileResult {
+                success: false,
+                content: None,
+                size: Some(metadata.len()),
+                lines_truncated: None,
+                read_only_snapshot: None,
+                error: Some(format!(
+                    "FileTooLarge: {} is {} bytes, over the {} byte limit",
+                    file_path,
+                    metadata.len(),
+                    max_bytes
+                )),
+            };
+        }
+    }
+
+    match locking::read_shared(Path::new(&file_path)) {
+        Ok((data, locked)) => match encoding.decode(&data) {
+            Ok(content) => {
+                let (content, lines_truncated) = match max_line_length {
+                    Some(max) => {
+                        let (content, truncated) = truncate_long_lines(content, max);
+                        (content, Some(truncated))
+                    }
+                    None => (content, None),
+                };
+                ReadFileResult {
+                    success: true,
+                    content: Some(content),
+                    size: None,
+                    lines_truncated,
+                    read_only_snapshot: locked.then_some(true),
+                    error: None,
+                }
+            }
+            Err(e) => ReadFileResult {
+                success: false,
+                content: None,
+                size: None,
+                lines_truncated: None,
+                read_only_snapshot: None,
+                error: Some(e),
+            },
+        },
+        Err(e) => ReadFileResult {
+            success: false,
+            content: None,
+            size: None,
+            lines_truncated: None,
+            read_only_snapshot: None,
+            error: Some(e.to_string()),
+        },
+    }
+}
+
+// With `max_line_length`, overlong lines come back shortened so minified
+// files stay renderable; reading again without it gets the full content.
+// Arguments arrive by name from the frontend, so they stay flat
+#[allow(clippy::too_many_arguments)]
+#[tauri::command]
+async fn read_file(
+    app: AppHandle,
+    file_path: Option<String>,
+    encoding: Option<String>,
+    timeout_ms: Option<u64>,
+    force: Option<bool>,
+    mod_root: Option<String>,
+    relative_path: Option<String>,
+    max_line_length: Option<usize>,
+) -> timing::Timed<ReadFileResult> {
+    let max_bytes = if force.unwrap_or(false) {
+        None
+    } else {
+        let settings = load_settings(&app).unwrap_or_default();
+        Some(settings.max_read_file_bytes.unwrap_or(DEFAULT_MAX_READ_BYTES))
+    };
+    timing::measure(app, "read_file", async move {
+        let file_path =
+            match resolve_file_arg(file_path, mod_root.as_deref(), relative_path.as_deref()) {
+                Ok(path) => path,
+                Err(e) => {
+                    return ReadFileResult {
+                        success: false,
+                        content: None,
+                        size: None,
+                        lines_truncated: None,
+                        read_only_snapshot: None,
+                        error: Some(e),
+                    }
+                }
+            };
+        run_blocking(timeout_ms, move || {
+            read_file_blocking(file_path, encoding, max_bytes, max_line_length)
+        })
+        .await
+        .unwrap_or_else(|e| ReadFileResult {
+            success: false,
+            content: None,
+            size: None,
+            lines_truncated: None,
+            read_only_snapshot: None,
+            error: Some(e),
+        })
+    })
+    .await
+}
+
+// Reads a file like read_file and, for squirrel scripts, lexes it so the
+// editor can highlight without tokenizing in JS. Other files come back with
+// no tokens
+#[tauri::command]
+async fn read_file_tokenized(
+    app: AppHandle,
+    file_path: String,
+    encoding: Option<String>,
+    timeout_ms: Option<u64>,
+) -> timing::Timed<ReadFileTokenizedResult> {
+    let settings = load_settings(&app).unwrap_or_default();
+    let max_bytes = Some(settings.max_read_file_bytes.unwrap_or(DEFAULT_MAX_READ_BYTES));
+    timing::measure(app, "read_file_tokenized", async move {
+        let result = run_blocking(timeout_ms, move || {
+            let is_script = file_type::detect_file_type(Path::new(&file_path))
+                == file_type::FileKind::Script;
+            let read = read_file_blocking(file_path, encoding, max_bytes, None);
+            let tokens = match (&read.content, is_script) {
+                (Some(content), true) => squirrel::highlight_tokens(content),
+                _ => Vec::new(),
+            };
+            (read, tokens)
+        })
+        .await;
+        match result {
+            Ok((read, tokens)) => ReadFileTokenizedResult {
+                success: read.success,
+                tokens: read.success.then_some(tokens),
+                content: read.content,
+                size: read.size,
+                error: read.error,
+            },
+            Err(e) => ReadFileTokenizedResult {
+                success: false,
+                content: None,
+                tokens: None,
+                size: None,
+                error: Some(e),
+            },
+        }
+    })
+    .await
+}
+
+// Lines in the file and its size, read in chunks. A last line without a
+// line ending still counts
+fn count_lines_blocking(path: &Path) -> std::io::Result<(u64, u64)> {
+    let mut file = fs::File::open(path)?;
+    let mut buf = vec![0u8; 64 * 1024];
+    let (mut lines, mut size, mut last) = (0u64, 0u64, b'\n');
+    loop {
+        let read = match file.read(&mut buf) {
+            Ok(0) => break,
+            Ok(read) => read,
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        };
+        lines += buf[..read].iter().filter(|&&b| b == b'\n').count() as u64;
+        size += read as u64;
+        last = buf[read - 1];
+    }
+    if last != b'\n' {
+        lines += 1;
+    }
+    Ok((lines, size))
+}
+
+fn stat_path_blocking(path: String) -> PathStat {
+    let stat = |exists, metadata: Option<&fs::Metadata>, error| PathStat {
+        path: path.clone(),
+        exists,
+        is_directory: metadata.map(|m| m.is_dir()),
+        size: metadata.filter(|m| m.is_file()).map(|m| m.len()),
+        modified: metadata.and_then(modified_ms),
+        error,
+    };
+    match fs::metadata(&path) {
+        Ok(metadata) => stat(true, Some(&metadata), None),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => stat(false, None, None),
+        Err(e) => stat(true, None, Some(e.to_string())),
+    }
+}
+
+// Metadata for a set of paths in one call, e.g. to refresh the open tabs.
+// Missing paths come back with `exists: false` rather than failing the batch
+#[tauri::command]
+async fn stat_paths(app: AppHandle, paths: Vec<String>) -> timing::Timed<StatPathsResult> {
+    timing::measure(app, "stat_paths", async move {
+        let result = run_blocking(None, move || {
+            paths
+                .into_par_iter()
+                .map(stat_path_blocking)
+                .collect::<Vec<_>>()
+        })
+        .await;
+        match result {
+            Ok(stats) => StatPathsResult {
+                success: true,
+                stats: Some(stats),
+                error: None,
+            },
+            Err(e) => StatPathsResult {
+                success: false,
+                stats: None,
+                error: Some(e),
+            },
+        }
+    })
+    .await
+}
+
+// Line count and size of a file without loading it, so the editor can
+// decide whether to virtualize before opening it
+#[tauri::command]
+async fn count_lines(app: AppHandle, file_path: String) -> timing::Timed<CountLinesResult> {
+    timing::measure(app, "count_lines", async move {
+        let result = run_blocking(None, move || {
+            count_lines_blocking(Path::new(&file_path))
+                .map_err(|e| format!("Failed to read {}: {}", file_path, e))
+        })
+        .await;
+        match result.and_then(|r| r) {
+            Ok((lines, size)) => CountLinesResult {
+                success: true,
+                lines: Some(lines),
+                size: Some(size),
+                error: None,
+            },
+            Err(e) => CountLinesResult {
+                success: false,
+                lines: None,
+                size: None,
+                error: Some(e),
+            },
+        }
+    })
+    .await
+}
+
+// How far read_file_tail reads back at a time
+const TAIL_CHUNK_BYTES: u64 = 8192;
+
+// Byte offset where the last `count` lines of `file` start, found by reading
+// backwards from the end. A final line ending doesn't start another line.
+fn tail_offset(file: &mut fs::File, len: u64, count: usize) -> std::io::Result<u64> {
+    let mut end = len;
+    let mut newlines = 0;
+    let mut skip_final = true;
+    let mut chunk = vec![0u8; TAIL_CHUNK_BYTES as usize];
+    while end > 0 {
+        let start = end.saturating_sub(TAIL_CHUNK_BYTES);
+        let buf = &mut chunk[..(end - start) as usize];
+        file.seek(SeekFrom::Start(start))?;
+        file.read_exact(buf)?;
+        for (i, &byte) in buf.iter().enumerate().rev() {
+            if byte != b'\n' {
+                skip_final = false;
+                continue;
+            }
+            if std::mem::take(&mut skip_final) {
+                continue;
+            }
+            newlines += 1;
+            if newlines == count {
+                return Ok(start + i as u64 + 1);
+            }
+        }
+        end = start;
+    }
+    Ok(0)
+}
+
+fn read_file_tail_blocking(path: &Path, count: usize) -> std::io::Result<(Vec<String>, u64)> {
+    let mut file = fs::File::open(path)?;
+    let len = file.metadata()?.len();
+    if count == 0 {
+        return Ok((Vec::new(), len));
+    }
+    let offset = tail_offset(&mut file, len, count)?;
+    let mut data = Vec::with_capacity((len - offset) as usize);
+    file.seek(SeekFrom::Start(offset))?;
+    file.read_to_end(&mut data)?;
+    // lines() also drops the \r of CRLF endings
+    let lines = String::from_utf8_lossy(&data).lines().map(str::to_string).collect();
+    Ok((lines, offset))
+}
+
+// The last `lines` lines of a text file, such as a growing log, without
+// reading the rest of it. `offset` is the byte where they start, so a later
+// call can tell what is new
+#[tauri::command]
+async fn read_file_tail(
+    app: AppHandle,
+    file_path: String,
+    lines: usize,
+) -> timing::Timed<FileTailResult> {
+    timing::measure(app, "read_file_tail", async move {
+        let result = run_blocking(None, move || {
+            read_file_tail_blocking(Path::new(&file_path), lines)
+                .map_err(|e| format!("Failed to read {}: {}", file_path, e))
+        })
+        .await;
+        match result.and_then(|r| r) {
+            Ok((lines, offset)) => FileTailResult {
+                success: true,
+                lines: Some(lines),
+                offset: Some(offset),
+                error: None,
+            },
+            Err(e) => FileTailResult {
+                success: false,
+                lines: None,
+                offset: None,
+                error: Some(e),
+            },
+        }
+    })
+    .await
+}
+
+fn read_binary_ranges_blocking(
+    path: &Path,
+    ranges: &[ByteRange],
+) -> Result<(Vec<BinaryRange>, u64), String> {
+    let read_error = |e: std::io::Error| format!("Failed to read {}: {}", path.display(), e);
+    let mut file = fs::File::open(path).map_err(read_error)?;
+    let len = file.metadata().map_err(read_error)?.len();
+    let available = |range: &ByteRange| len.saturating_sub(range.offset).min(range.length);
+
+    // Checked before anything is read or allocated
+    let mut total: u64 = 0;
+    for range in ranges {
+        let size = available(range);
+        if size > MAX_BINARY_RANGE_BYTES {
+            return Err(format!(
+                "RangeTooLarge: the range at {} is {} bytes, over the {} byte limit",
+                range.offset, size, MAX_BINARY_RANGE_BYTES
+            ));
+        }
+        total += size;
+    }
+    if total > MAX_BINARY_RANGES_TOTAL_BYTES {
+        return Err(format!(
+            "RangeTooLarge: the ranges total {} bytes, over the {} byte limit",
+            total, MAX_BINARY_RANGES_TOTAL_BYTES
+        ));
+    }
+
+    let mut read = Vec::with_capacity(ranges.len());
+    for range in ranges {
+        let available = available(range);
+        let mut data = vec![0u8; available as usize];
+        if available > 0 {
+            file.seek(SeekFrom::Start(range.offset))
+                .and_then(|_| file.read_exact(&mut data))
+                .map_err(read_error)?;
+        }
+        read.push(BinaryRange {
+            offset: range.offset,
+            data: BASE64.encode(&data),
+            truncated: available < range.length,
+        });
+    }
+    Ok((read, len))
+}
+
+// Reads several windows of a binary file, such as a pak's header and
+// directory, in one call. Results come back in the order asked for
+#[tauri::command]
+async fn read_binary_ranges(
+    app: AppHandle,
+    file_path: String,
+    ranges: Vec<ByteRange>,
+) -> timing::Timed<ReadBinaryRangesResult> {
+    timing::measure(app, "read_binary_ranges", async move {
+        let result = run_blocking(None, move || {
+            read_binary_ranges_blocking(Path::new(&file_path), &ranges)
+        })
+        .await;
+        match result.and_then(|r| r) {
+            Ok((ranges, file_size)) => ReadBinaryRangesResult {
+                success: true,
+                ranges: Some(ranges),
+                file_size: Some(file_size),
+                error: None,
+            },
+            Err(e) => ReadBinaryRangesResult {
+                success: false,
+                ranges: None,
+                file_size: None,
+                error: Some(e),
+            },
+        }
+    })
+    .await
+}
+
+// Patches bytes of a fixed-layout file in place instead of rewriting it
+// whole. `data` is base64; the file must already exist
+#[tauri::command]
+async fn write_file_at(
+    app: AppHandle,
+    file_path: String,
+    offset: u64,
+    data: String,
+    durable: Option<bool>,
+) -> timing::Timed<FileSizeResult> {
+    timing::measure(app, "write_file_at", async move {
+        let durable = durable.unwrap_or(false);
+        let result = match BASE64.decode(data.trim()) {
+            Ok(bytes) => run_blocking(None, move || {
+                locking::write_at_locked(Path::new(&file_path), offset, &bytes, durable)
+            })
+            .await
+            .and_then(|r| r),
+            Err(e) => Err(format!("Invalid base64: {}", e)),
+        };
+        match result {
+            Ok(size) => FileSizeResult {
+                success: true,
+                size: Some(size),
+                error: None,
+            },
+            Err(e) => FileSizeResult {
+                success: false,
+                size: None,
+                error: Some(e),
+            },
+        }
+    })
+    .await
+}
+
+// Empties a file, or cuts it to `length` bytes, in place, so a log another
+// program has open isn't swapped out from under it
+#[tauri::command]
+async fn truncate_file(
+    app: AppHandle,
+    file_path: String,
+    length: Option<u64>,
+) -> timing::Timed<FileSizeResult> {
+    timing::measure(app, "truncate_file", async move {
+        let length = length.unwrap_or(0);
+        let result = run_blocking(None, move || {
+            locking::set_len_locked(Path::new(&file_path), length)
+        })
+        .await;
+        match result.and_then(|r| r) {
+            Ok(()) => FileSizeResult {
+                success: true,
+                size: Some(length),
+                error: None,
+            },
+            Err(e) => FileSizeResult {
+                success: false,
+                size: None,
+                error: Some(e),
+            },
+        }
+    })
+    .await
+}
+
+const UTF8_CONTEXT_BYTES: usize = 16;
+
+fn utf8_problem(data: &[u8]) -> Option<Utf8Problem> {
+    let error = std::str::from_utf8(data).err()?;
+    let offset = error.valid_up_to();
+    let bad_len = error.error_len().unwrap_or(0);
+    let context_start = offset.saturating_sub(UTF8_CONTEXT_BYTES);
+    let context_end = (offset + bad_len + UTF8_CONTEXT_BYTES).min(data.len());
+    Some(Utf8Problem {
+        offset,
+        line: data[..offset].iter().filter(|&&b| b == b'\n').count() + 1,
+        bytes: data[offset..offset + bad_len].to_vec(),
+        context_start,
+        context: data[context_start..context_end].to_vec(),
+    })
+}
+
+// Checks a file is valid UTF-8. A file that isn't is still a success, with
+// valid false, where the first bad sequence is and a guess at the encoding
+#[tauri::command]
+async fn validate_utf8(app: AppHandle, file_path: String) -> timing::Timed<Utf8ValidationResult> {
+    timing::measure(app, "validate_utf8", async move {
+        let result = run_blocking(None, move || fs::read(&file_path).map_err(|e| e.to_string()))
+            .await
+            .and_then(|r| r);
+        match result {
+            Ok(data) => {
+                let problem = utf8_problem(&data);
+                Utf8ValidationResult {
+                    success: true,
+                    valid: Some(problem.is_none()),
+                    guessed_encoding: problem
+                        .as_ref()
+                        .map(|_| TextEncoding::guess(&data).name().to_string()),
+                    problem,
+                    error: None,
+                }
+            }
+            Err(e) => Utf8ValidationResult {
+                success: false,
+                valid: None,
+                problem: None,
+                guessed_encoding: None,
+                error: Some(e),
+            },
+        }
+    })
+    .await
+}
+
+fn scan_encodings_blocking(root: &Path) -> Result<EncodingScanResult, String> {
+    let mut files = paths::walk_files(root).map_err(|e| e.to_string())?;
+    files.sort();
+    let mut counts = BTreeMap::new();
+    let mut non_utf8 = Vec::new();
+    let mut failed = Vec::new();
+    for path in files {
+        if !file_type::detect_file_type(&path).is_text() {
+            continue;
+        }
+        let data = match fs::read(&path) {
+            Ok(data) => data,
+            Err(e) => {
+                failed.push(format!("{}: {}", path.display(), e));
+                continue;
+            }
+        };
+        let encoding = TextEncoding::guess(&data);
+        *counts.entry(encoding.name().to_string()).or_insert(0) += 1;
+        if encoding != TextEncoding::Utf8 {
+            non_utf8.push(EncodedFile {
+                path: path.to_string_lossy().to_string(),
+                encoding: encoding.name().to_string(),
+            });
+        }
+    }
+    Ok(EncodingScanResult {
+        success: true,
+        counts: Some(counts),
+        non_utf8: Some(non_utf8),
+        failed: if failed.is_empty() { None } else { Some(failed) },
+        error: None,
+    })
+}
+
+// How consistently a folder's text files are encoded, before a bulk change.
+// Binary files are skipped; encodings are guessed as in validate_utf8
+#[tauri::command]
+async fn scan_encodings(app: AppHandle, root: String) -> timing::Timed<EncodingScanResult> {
+    timing::measure(app, "scan_encodings", async move {
+        run_blocking(None, move || scan_encodings_blocking(Path::new(&root)))
+            .await
+            .and_then(|r| r)
+            .unwrap_or_else(|e| EncodingScanResult {
+                success: false,
+                counts: None,
+                non_utf8: None,
+                failed: None,
+                error: Some(e),
+            })
+    })
+    .await
+}
+
+fn convert_to_utf8_blocking(files: Vec<PathBuf>, dry_run: bool) -> ConvertToUtf8Result {
+    let mut converted = Vec::new();
+    let mut already_utf8 = Vec::new();
+    let mut skipped = Vec::new();
+    let mut failed = Vec::new();
+    for path in files {
+        let path_str = path.to_string_lossy().to_string();
+        let data = match fs::read(&path) {
+            Ok(data) => data,
+            Err(e) => {
+                failed.push(format!("{}: {}", path_str, e));
+                continue;
+            }
+        };
+        let encoding = match TextEncoding::detect(&data) {
+            Some(TextEncoding::Utf8) => {
+                already_utf8.push(path_str);
+                continue;
+            }
+            Some(encoding) => encoding,
+            None => {
+                skipped.push(format!("{}: encoding could not be determined", path_str));
+                continue;
+            }
+        };
+        if !dry_run {
+            let written = encoding
+                .decode(&data)
+                .and_then(|text| write_atomic(&path, text.as_bytes()).map_err(|e| e.to_string()));
+            if let Err(e) = written {
+                failed.push(format!("{}: {}", path_str, e));
+                continue;
+            }
+        }
+        converted.push(EncodedFile {
+            path: path_str,
+            encoding: encoding.name().to_string(),
+        });
+    }
+    ConvertToUtf8Result {
+        success: true,
+        converted: Some(converted),
+        already_utf8: Some(already_utf8),
+        skipped: if skipped.is_empty() { None } else { Some(skipped) },
+        failed: if failed.is_empty() { None } else { Some(failed) },
+        error: None,
+    }
+}
+
+// Rewrites files as UTF-8: either `paths`, or the text files under `root`
+// matching the `include` globs (.r5vignore syntax; all when omitted). Files
+// whose encoding can't be told with confidence are reported, never guessed
+#[tauri::command]
+async fn convert_to_utf8(
+    app: AppHandle,
+    paths: Option<Vec<String>>,
+    root: Option<String>,
+    include: Option<Vec<String>>,
+    dry_run: Option<bool>,
+) -> timing::Timed<ConvertToUtf8Result> {
+    timing::measure(app, "convert_to_utf8", async move {
+        let dry_run = dry_run.unwrap_or(false);
+        let result = run_blocking(None, move || {
+            let files: Vec<PathBuf> = match (paths, root) {
+                (Some(paths), _) => paths.into_iter().map(PathBuf::from).collect(),
+                (None, Some(root)) => {
+                    let root = PathBuf::from(root);
+                    let include = include
+                        .filter(|globs| !globs.is_empty())
+                        .map(|globs| ignore::IgnoreRules::parse(&globs.join("\n")));
+                    let mut files = paths::walk_files(&root).map_err(|e| e.to_string())?;
+                    files.retain(|path| {
+                        let relative = paths::relative_slash_path(&root, path).unwrap_or_default();
+                        // "Ignored" by the include rules means listed in them
+                        include.as_ref().is_none_or(|rules| rules.is_file_ignored(&relative))
+                            && file_type::detect_file_type(path).is_text()
+                    });
+                    files.sort();
+                    files
+                }
+                (None, None) => return Err("Either paths or root is required".to_string()),
+            };
+            Ok(convert_to_utf8_blocking(files, dry_run))
+        })
+        .await;
+        result.and_then(|r| r).unwrap_or_else(|e| ConvertToUtf8Result {
+            success: false,
+            converted: None,
+            already_utf8: None,
+            skipped: None,
+            failed: None,
+            error: Some(e),
+        })
+    })
+    .await
+}
+
+fn write_file_blocking(
+    file_path: String,
+    content: String,
+    encoding: Option<String>,
+    snapshot_retention: Option<usize>,
+    durable: bool,
+) -> WriteFileResult {
+    let data = match TextEncoding::from_name(encoding.as_deref()).and_then(|e| e.encode(&content)) {
+        Ok(data) => data,
+        Err(e) => {
+            return WriteFileResult {
+                success: false,
+                error: Some(e),
+            };
+        }
+    };
+
+    // The previous content goes into local history before it is overwritten
+    if let (Some(retention), Ok(previous)) = (snapshot_retention, fs::read(&file_path)) {
+        if let Err(e) = history::snapshot(Path::new(&file_path), &previous, retention) {
+            return WriteFileResult {
+                success: false,
+                error: Some(format!("Not saved, snapshot failed: {}", e)),
+            };
+        }
+    }
+
+    match locking::write_locked(Path::new(&file_path), &data, durable) {
+        Ok(_) => WriteFileResult {
+            success: true,
+            error: None,
+        },
+        Err(e) => WriteFileResult {
+            success: false,
+            error: Some(e),
+        },
+    }
+}
+
+// Arguments arrive by name from the frontend, so they stay flat
+#[allow(clippy::too_many_arguments)]
+#[tauri::command]
+async fn write_file(
+    app: AppHandle,
+    file_path: Option<String>,
+    content: String,
+    encoding: Option<String>,
+    workspace_root: Option<String>,
+    timeout_ms: Option<u64>,
+    snapshot: Option<bool>,
+    durable: Option<bool>,
+    mod_root: Option<String>,
+    relative_path: Option<String>,
+    refuse_case_collision: Option<bool>,
+) -> timing::Timed<WriteFileResult> {
+    let snapshot_retention = snapshot.unwrap_or(false).then(|| snapshot_retention(&app));
+    timing::measure(app, "write_file", async move {
+        let file_path = resolve_file_arg(file_path, mod_root.as_deref(), relative_path.as_deref())
+            .and_then(|path| check_workspace(workspace_root.as_deref(), &path).map(|_| path));
+        let file_path = match file_path {
+            Ok(path) => path,
+            Err(e) => {
+                return WriteFileResult {
+                    success: false,
+                    error: Some(e),
+                };
+            }
+        };
+
+        let durable = durable.unwrap_or(false);
+        let refuse_case_collision = refuse_case_collision.unwrap_or(false);
+        run_blocking(timeout_ms, move || {
+            if refuse_case_collision {
+                if let Err(e) = check_case_collision(Path::new(&file_path)) {
+                    return WriteFileResult {
+                        success: false,
+                        error: Some(e),
+                    };
+                }
+            }
+            write_file_blocking(file_path, content, encoding, snapshot_retention, durable)
+        })
+        .await
+        .unwrap_or_else(|e| WriteFileResult {
+            success: false,
+            error: Some(e),
+        })
+    })
+    .await
+}
+
+// Fails when writing `path` would land on an existing file whose name only
+// differs in case, which Windows and macOS would silently overwrite
+fn check_case_collision(path: &Path) -> Result<(), String> {
+    let (Some(dir), Some(name)) = (path.parent(), path.file_name()) else {
+        return Ok(());
+    };
+    let name = name.to_string_lossy();
+    match paths::case_collision(dir, &name).map_err(|e| e.to_string())? {
+        Some(existing) => Err(format!(
+            "Exists: {} differs from the existing {} only in case",
+            name, existing
+        )),
+        None => Ok(()),
+    }
+}
+
+// Whether creating `name` in `dir_path` would clash with an entry that only
+// differs in case, so the UI can warn before creating it
+#[tauri::command]
+async fn check_name_collision(
+    app: AppHandle,
+    dir_path: String,
+    name: String,
+) -> timing::Timed<NameCollisionResult> {
+    timing::measure(app, "check_name_collision", async move {
+        let result = run_blocking(None, move || {
+            paths::case_collision(Path::new(&dir_path), &name)
+                .map_err(|e| format!("Failed to read {}: {}", dir_path, e))
+        })
+        .await;
+        match result.and_then(|r| r) {
+            Ok(existing) => NameCollisionResult {
+                success: true,
+                collides: Some(existing.is_some()),
+                existing,
+                error: None,
+            },
+            Err(e) => NameCollisionResult {
+                success: false,
+                existing: None,
+                collides: None,
+                error: Some(e),
+            },
+        }
+    })
+    .await
+}
+
+fn snapshot_retention(app: &AppHandle) -> usize {
+    load_settings(app)
+        .ok()
+        .and_then(|settings| settings.snapshot_retention)
+        .unwrap_or(DEFAULT_SNAPSHOT_RETENTION)
+}
+
+#[tauri::command]
+async fn snapshot_file(
+    app: AppHandle,
+    file_path: String,
+    retention: Option<usize>,
+) -> timing::Timed<SnapshotResult> {
+    let retention = retention.unwrap_or_else(|| snapshot_retention(&app));
+    timing::measure(app, "snapshot_file", async move {
+        let result = run_blocking(None, move || {
+            let path = Path::new(&file_path);
+            fs::read(path)
+                .map_err(|e| e.to_string())
+                .and_then(|content| history::snapshot(path, &content, retention))
+        })
+        .await;
+        match result.and_then(|r| r) {
+            Ok(snapshot) => SnapshotResult {
+                success: true,
+                snapshot: Some(snapshot),
+                error: None,
+            },
+            Err(e) => SnapshotResult {
+                success: false,
+                snapshot: None,
+                error: Some(e),
+            },
+        }
+    })
+    .await
+}
+
+#[tauri::command]
+async fn list_snapshots(app: AppHandle, file_path: String) -> timing::Timed<SnapshotListResult> {
+    timing::measure(app, "list_snapshots", async move {
+        let result = run_blocking(None, move || history::list(Path::new(&file_path))).await;
+        match result.and_then(|r| r) {
+            Ok(snapshots) => SnapshotListResult {
+                success: true,
+                snapshots: Some(snapshots),
+                error: None,
+            },
+            Err(e) => SnapshotListResult {
+                success: false,
+                snapshots: None,
+                error: Some(e),
+            },
+        }
+    })
+    .await
+}
+
+// The content being replaced is snapshotted first, so a restore can itself
+// be undone
+#[tauri::command]
+async fn restore_snapshot(
+    app: AppHandle,
+    file_path: String,
+    timestamp: u64,
+) -> timing::Timed<WriteFileResult> {
+    let retention = snapshot_retention(&app);
+    timing::measure(app, "restore_snapshot", async move {
+        let result = run_blocking(None, move || {
+            let path = Path::new(&file_path);
+            history::read(path, timestamp).and_then(|content| {
+                if let Ok(current) = fs::read(path) {
+                    history::snapshot(path, &current, retention)?;
+                }
+                write_atomic(path, &content).map_err(|e| e.to_string())
+            })
+        })
+        .await;
+        match result.and_then(|r| r) {
+            Ok(()) => WriteFileResult {
+                success: true,
+                error: None,
+            },
+            Err(e) => WriteFileResult {
+                success: false,
+                error: Some(e),
+            },
+        }
+    })
+    .await
+}
+
+fn is_compressed_project(data: &[u8]) -> bool {
+    data.len() >= 4 && data[0..4] == MAGIC_BYTES
+}
+
+// Project formats by version: 0 is the legacy plain JSON text, 1 the magic
+// bytes followed by gzip. write_project_file always writes the current one.
+const LEGACY_PROJECT_FORMAT: u32 = 0;
+const CURRENT_PROJECT_FORMAT: u32 = 1;
+
+fn project_format_version(compressed: bool) -> u32 {
+    if compressed {
+        CURRENT_PROJECT_FORMAT
+    } else {
+        LEGACY_PROJECT_FORMAT
+    }
+}
+
+// Decodes a project's bytes: gzip after the magic bytes, or plain UTF-8 text.
+// Returns the content and whether it was compressed.
+fn decode_project_data(data: Vec<u8>) -> Result<(String, bool), String> {
+    if is_compressed_project(&data) {
+        let mut decoder = GzDecoder::new(&data[MAGIC_BYTES.len()..]);
+        let mut decompressed = String::new();
+        decoder
+            .read_to_string(&mut decompressed)
+            .map_err(|e| format!("Failed to decompress: {}", e))?;
+        Ok((decompressed, true))
+    } else {
+        String::from_utf8(data)
+            .map(|content| (content, false))
+            .map_err(|e| e.to_string())
+    }
+}
+
+fn project_read_result(decoded: Result<(String, bool), String>) -> ProjectFileReadResult {
+    match decoded {
+        Ok((content, compressed)) => {
+            let format_version = project_format_version(compressed);
+            ProjectFileReadResult {
+                success: true,
+                content: Some(content),
+                compressed: Some(compressed),
+                format_version: Some(format_version),
+                needs_migration: Some(format_version < CURRENT_PROJECT_FORMAT),
+                error: None,
+            }
+        }
+        Err(e) => ProjectFileReadResult {
+            success: false,
+            content: None,
+            compressed: None,
+            format_version: None,
+            needs_migration: None,
+            error: Some(e),
+        },
+    }
+}
+
+fn read_project_file_blocking(file_path: String) -> ProjectFileReadResult {
+    project_read_result(
+        fs::read(&file_path)
+            .map_err(|e| e.to_string())
+            .and_then(decode_project_data),
+    )
+}
+
+#[tauri::command]
+async fn read_project_file(
+    app: AppHandle,
+    file_path: String,
+    timeout_ms: Option<u64>,
+) -> timing::Timed<ProjectFileReadResult> {
+    timing::measure(app, "read_project_file", async move {
+        run_blocking(timeout_ms, move || read_project_file_blocking(file_path))
+            .await
+            .unwrap_or_else(|e| ProjectFileReadResult {
+                success: false,
+                content: None,
+                compressed: None,
+                format_version: None,
+                needs_migration: None,
+                error: Some(e),
+            })
+    })
+    .await
+}
+
+fn summarize_project_keys(content: &str) -> Result<Vec<ProjectKeySummary>, String> {
+    let value: serde_json::Value =
+        serde_json::from_str(content).map_err(|e| format!("Invalid project JSON: {}", e))?;
+    let serde_json::Value::Object(map) = value else {
+        return Err("Invalid project JSON: the top level is not an object".to_string());
+    };
+    Ok(map
+        .iter()
+        .map(|(key, value)| {
+            let (kind, size) = project_diff::shape(value);
+            ProjectKeySummary {
+                key: key.clone(),
+                kind,
+                size,
+            }
+        })
+        .collect())
+}
+
+// The top-level keys of a project with their types and sizes, for an
+// overview without sending the whole content to the frontend
+#[tauri::command]
+async fn describe_project(
+    app: AppHandle,
+    file_path: String,
+    timeout_ms: Option<u64>,
+) -> timing::Timed<DescribeProjectResult> {
+    timing::measure(app, "describe_project", async move {
+        let result = run_blocking(timeout_ms, move || {
+            let (content, compressed) = fs::read(&file_path)
+                .map_err(|e| e.to_string())
+                .and_then(decode_project_data)?;
+            Ok((summarize_project_keys(&content)?, compressed))
+        })
+        .await;
+        match result.and_then(|r| r) {
+            Ok((keys, compressed)) => DescribeProjectResult {
+                success: true,
+                keys: Some(keys),
+                compressed: Some(compressed),
+                error: None,
+            },
+            Err(e) => DescribeProjectResult {
+                success: false,
+                keys: None,
+                compressed: None,
+                error: Some(e),
+            },
+        }
+    })
+    .await
+}
+
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+// Streams `input` into a standard .gz at `output`, so large files never sit
+// in memory. Returns the original and compressed sizes. Like export_mod_zip,
+// the archive is written to a `.part` file first, so a failure leaves any
+// existing `output` as it was.
+fn compress_file_gzip_blocking(
+    input: &Path,
+    output: &Path,
+    level: Compression,
+) -> std::io::Result<(u64, u64)> {
+    let mut source = fs::File::open(input)?;
+    let part = export::part_path(output);
+    let written = fs::File::create(&part).and_then(|file| {
+        let mut encoder = GzEncoder::new(std::io::BufWriter::new(file), level);
+        let original_size = std::io::copy(&mut source, &mut encoder)?;
+        let mut target = encoder.finish()?;
+        target.flush()?;
+        let compressed_size = target.get_ref().metadata()?.len();
+        drop(target);
+        fs::rename(&part, output)?;
+        Ok((original_size, compressed_size))
+    });
+    if written.is_err() {
+        let _ = fs::remove_file(&part);
+    }
+    written
+}
+
+// Gzips a single file, for build steps that ship scripts compressed. This is
+// plain gzip, not the project format; decompress_gzip reverses it. `level`
+// (0-9) trades size for speed.
+#[tauri::command]
+async fn compress_file_gzip(
+    app: AppHandle,
+    input_path: String,
+    output_path: String,
+    level: Option<u32>,
+) -> timing::Timed<CompressFileResult> {
+    timing::measure(app, "compress_file_gzip", async move {
+        let level = level.map_or(Compression::default(), |l| Compression::new(l.min(9)));
+        let result = run_blocking(None, move || {
+            let (input, output) = (Path::new(&input_path), Path::new(&output_path));
+            if paths::canonicalize_lenient(input) == paths::canonicalize_lenient(output) {
+                return Err("The output would overwrite the input".to_string());
+            }
+            compress_file_gzip_blocking(input, output, level)
+                .map_err(|e| format!("Failed to compress {}: {}", input_path, e))
+        })
+        .await
+        .and_then(|r| r);
+        match result {
+            Ok((original_size, compressed_size)) => CompressFileResult {
+                success: true,
+                original_size: Some(original_size),
+                compressed_size: Some(compressed_size),
+                error: None,
+            },
+            Err(e) => CompressFileResult {
+                success: false,
+                original_size: None,
+                compressed_size: None,
+                error: Some(e),
+            },
+        }
+    })
+    .await
+}
+
+// Decompresses a plain .gz file. The limit applies to the decompressed size,
+// since a small archive can expand enormously.
+fn decompress_gzip_blocking(file_path: &str, max_bytes: Option<u64>) -> Result<Vec<u8>, String> {
+    let mut file = fs::File::open(file_path).map_err(|e| e.to_string())?;
+    let mut magic = Vec::new();
+    (&mut file)
+        .take(MAGIC_BYTES.len() as u64)
+        .read_to_end(&mut magic)
+        .map_err(|e| e.to_string())?;
+    if is_compressed_project(&magic) {
+        return Err(format!("{} is an R5V project, open it as a project", file_path));
+    }
+    if !magic.starts_with(&GZIP_MAGIC) {
+        return Err(format!("{} is not a gzip file", file_path));
+    }
+
+    let mut data = Vec::new();
+    let stream = std::io::Cursor::new(magic).chain(file);
+    // Concatenated members are valid gzip, as produced by `cat a.gz b.gz`
+    // One byte past the limit tells whether there is more
+    flate2::read::MultiGzDecoder::new(stream)
+        .take(max_bytes.map_or(u64::MAX, |max| max + 1))
+        .read_to_end(&mut data)
+        .map_err(|e| format!("Failed to decompress: {}", e))?;
+    if let Some(max_bytes) = max_bytes.filter(|&max| data.len() as u64 > max) {
+        return Err(format!(
+            "FileTooLarge: {} decompresses to over the {} byte limit",
+            file_path, max_bytes
+        ));
+    }
+    Ok(data)
+}
+
+// Opens a standard .gz file for inspection: text when the content is UTF-8,
+// base64 otherwise. `force` lifts the read size limit as in read_file.
+#[tauri::command]
+async fn decompress_gzip(
+    app: AppHandle,
+    file_path: String,
+    force: Option<bool>,
+) -> timing::Timed<DecompressGzipResult> {
+    let max_bytes = if force.unwrap_or(false) {
+        None
+    } else {
+        let settings = load_settings(&app).unwrap_or_default();
+        Some(settings.max_read_file_bytes.unwrap_or(DEFAULT_MAX_READ_BYTES))
+    };
+    timing::measure(app, "decompress_gzip", async move {
+        let result = run_blocking(None, move || decompress_gzip_blocking(&file_path, max_bytes))
+            .await
+            .and_then(|r| r);
+        match result {
+            Ok(data) => {
+                let size = data.len() as u64;
+                let (content, base64) = match String::from_utf8(data) {
+                    Ok(text) => (text, false),
+                    Err(e) => (BASE64.encode(e.as_bytes()), true),
+                };
+                DecompressGzipResult {
+                    success: true,
+                    content: Some(content),
+                    base64: Some(base64),
+                    size: Some(size),
+                    error: None,
+                }
+            }
+            Err(e) => DecompressGzipResult {
+                success: false,
+                content: None,
+                base64: None,
+                size: None,
+                error: Some(e),
+            },
+        }
+    })
+    .await
+}
+
+// Decodes project bytes that never touched the disk, such as a dropped or
+// pasted project, sent as base64
+#[tauri::command]
+async fn decode_project_bytes(
+    app: AppHandle,
+    data: String,
+) -> timing::Timed<ProjectFileReadResult> {
+    timing::measure(app, "decode_project_bytes", async move {
+        let decoded = BASE64
+            .decode(data.trim())
+            .map_err(|e| format!("Invalid base64: {}", e))
+            .and_then(decode_project_data);
+        project_read_result(decoded)
+    })
+    .await
+}
+
+// Reads at most `max_bytes` of uncompressed content, decompressing only as
+// much of the gzip stream as needed. Returns (content, compressed, truncated).
+fn read_project_head(file_path: &str, max_bytes: usize) -> Result<(String, bool, bool), String> {
+    let mut file = fs::File::open(file_path).map_err(|e| e.to_string())?;
+
+    let mut data = Vec::new();
+    (&mut file)
+        .take(MAGIC_BYTES.len() as u64)
+        .read_to_end(&mut data)
+        .map_err(|e| e.to_string())?;
+    let compressed = is_compressed_project(&data);
+
+    // Read one byte past the limit to find out whether there is more
+    let limit = max_bytes as u64 + 1;
+    if compressed {
+        data.clear();
+        GzDecoder::new(file)
+            .take(limit)
+            .read_to_end(&mut data)
+            .map_err(|e| format!("Failed to decompress: {}", e))?;
+    } else {
+        file.take(limit.saturating_sub(data.len() as u64))
+            .read_to_end(&mut data)
+            .map_err(|e| e.to_string())?;
+    }
+
+    let truncated = data.len() > max_bytes;
+    data.truncate(max_bytes);
+
+    let content = match String::from_utf8(data) {
+        Ok(content) => content,
+        // The cut may have landed inside a multi-byte character
+        Err(e) if truncated && e.utf8_error().error_len().is_none() => {
+            let valid_up_to = e.utf8_error().valid_up_to();
+            let mut data = e.into_bytes();
+            data.truncate(valid_up_to);
+            String::from_utf8(data).map_err(|e| e.to_string())?
+        }
+        Err(e) => return Err(e.to_string()),
+    };
+
+    Ok((content, compressed, truncated))
+}
+
+#[tauri::command]
+async fn read_project_file_head(
+    app: AppHandle,
+    file_path: String,
+    max_bytes: usize,
+) -> timing::Timed<ProjectFileHeadResult> {
+    timing::measure(app, "read_project_file_head", async move {
+        match read_project_head(&file_path, max_bytes) {
+            Ok((content, compressed, truncated)) => ProjectFileHeadResult {
+                success: true,
+                content: Some(content),
+                compressed: Some(compressed),
+                truncated: Some(truncated),
+                error: None,
+            },
+            Err(e) => ProjectFileHeadResult {
+                success: false,
+                content: None,
+                compressed: None,
+                truncated: None,
+                error: Some(e),
+            },
+        }
+    })
+    .await
+}
+
+// The on-disk project format: magic bytes followed by gzip of the content.
+// write_project_file always uses the best level.
+fn encode_project_data(content: &str, level: Compression) -> Result<Vec<u8>, String> {
+    let mut encoder = GzEncoder::new(MAGIC_BYTES.to_vec(), level);
+    encoder
+        .write_all(content.as_bytes())
+        .map_err(|e| format!("Compression error: {}", e))?;
+    encoder
+        .finish()
+        .map_err(|e| format!("Compression finish error: {}", e))
+}
+
+// Reads a just-written project back and checks it decodes to `content`, so
+// corruption shows up at save time instead of on the next open
+fn verify_project_file(file_path: &str, content: &str) -> Result<(), String> {
+    let data = fs::read(file_path)
+        .map_err(|e| format!("Saved, but reading it back failed: {}", e))?;
+    match decode_project_data(data) {
+        Ok((read_back, _)) if read_back == content => Ok(()),
+        Ok(_) => Err("Saved file does not match what was written".to_string()),
+        Err(e) => Err(format!("Saved file can't be read back: {}", e)),
+    }
+}
+
+fn write_project_file_blocking(
+    file_path: String,
+    content: String,
+    check_space: bool,
+    durable: bool,
+    verify: bool,
+) -> ProjectFileWriteResult {
+    let original_size = content.len();
+    let final_data = match encode_project_data(&content, Compression::best()) {
+        Ok(data) => data,
+        Err(e) => {
+            return ProjectFileWriteResult {
+                success: false,
+                original_size: None,
+                compressed_size: None,
+                verified: None,
+                error: Some(e),
+            };
+        }
+    };
+    let compressed_size = final_data.len();
+
+    if check_space {
+        if let Err(e) = disk::ensure_space(Path::new(&file_path), compressed_size as u64) {
+            return ProjectFileWriteResult {
+                success: false,
+                original_size: None,
+                compressed_size: None,
+                verified: None,
+                error: Some(e),
+            };
+        }
+    }
+    
+    let written = locking::write_locked(Path::new(&file_path), &final_data, durable)
+        .and_then(|_| {
+            if verify {
+                verify_project_file(&file_path, &content)
+            } else {
+                Ok(())
+            }
+        });
+    match written {
+        Ok(_) => ProjectFileWriteResult {
+            success: true,
+            original_size: Some(original_size),
+            compressed_size: Some(compressed_size),
+            verified: verify.then_some(true),
+            error: None,
+        },
+        Err(e) => ProjectFileWriteResult {
+            success: false,
+            original_size: None,
+            compressed_size: None,
+            verified: None,
+            error: Some(e),
+        },
+    }
+}
+
+// Encodes project content in memory, for the clipboard or uploads. With no
+// options the bytes match write_project_file; `compressed: false` gives the
+// plain text form and `level` (0-9) trades size for speed.
+#[tauri::command]
+async fn encode_project_bytes(
+    app: AppHandle,
+    content: String,
+    compressed: Option<bool>,
+    level: Option<u32>,
+) -> timing::Timed<EncodeProjectResult> {
+    timing::measure(app, "encode_project_bytes", async move {
+        let original_size = content.len();
+        let encoded = if compressed.unwrap_or(true) {
+            let level = level.map_or(Compression::best(), |l| Compression::new(l.min(9)));
+            run_blocking(None, move || encode_project_data(&content, level))
+                .await
+                .and_then(|r| r)
+        } else {
+            Ok(content.into_bytes())
+        };
+        match encoded {
+            Ok(data) => EncodeProjectResult {
+                success: true,
+                original_size: Some(original_size),
+                compressed_size: Some(data.len()),
+                data: Some(BASE64.encode(&data)),
+                error: None,
+            },
+            Err(e) => EncodeProjectResult {
+                success: false,
+                data: None,
+                original_size: None,
+                compressed_size: None,
+                error: Some(e),
+            },
+        }
+    })
+    .await
+}
+
+// With validate_json the content must parse as JSON, or nothing is written.
+// With verify the file is read back after writing, and the save fails if it
+// doesn't decode to the same content
+#[allow(clippy::too_many_arguments)]
+#[tauri::command]
+async fn write_project_file(
+    app: AppHandle,
+    file_path: String,
+    content: String,
+    workspace_root: Option<String>,
+    timeout_ms: Option<u64>,
+    check_space: Option<bool>,
+    durable: Option<bool>,
+    validate_json: Option<bool>,
+    verify: Option<bool>,
+) -> timing::Timed<ProjectFileWriteResult> {
+    timing::measure(app, "write_project_file", async move {
+        if let Err(e) = check_workspace(workspace_root.as_deref(), &file_path) {
+            return ProjectFileWriteResult {
+                success: false,
+                original_size: None,
+                compressed_size: None,
+                verified: None,
+                error: Some(e),
+            };
+        }
+
+        let check_space = check_space.unwrap_or(false);
+        let durable = durable.unwrap_or(false);
+        let validate_json = validate_json.unwrap_or(false);
+        let verify = verify.unwrap_or(false);
+        run_blocking(timeout_ms, move || {
+            if validate_json {
+                if let Err(e) = serde_json::from_str::<serde_json::Value>(&content) {
+                    return ProjectFileWriteResult {
+                        success: false,
+                        original_size: None,
+                        compressed_size: None,
+                        verified: None,
+                        error: Some(format!("Not saved, the project is not valid JSON: {}", e)),
+                    };
+                }
+            }
+            write_project_file_blocking(file_path, content, check_space, durable, verify)
+        })
+        .await
+        .unwrap_or_else(|e| ProjectFileWriteResult {
+            success: false,
+            original_size: None,
+            compressed_size: None,
+            verified: None,
+            error: Some(e),
+        })
+    })
+    .await
+}
+
+// Re-saves an older-format project in the current format. The new bytes are
+// decoded in memory before anything is written and the saved file is read
+// back, so a migration can't lose content. Returns the version it came from
+// and whether anything was rewritten.
+fn migrate_project_blocking(file_path: String) -> Result<(u32, bool), String> {
+    let data = fs::read(&file_path).map_err(|e| e.to_string())?;
+    let (content, compressed) = decode_project_data(data)?;
+    let previous_version = project_format_version(compressed);
+    if previous_version >= CURRENT_PROJECT_FORMAT {
+        return Ok((previous_version, false));
+    }
+
+    let encoded = encode_project_data(&content, Compression::best())?;
+    match decode_project_data(encoded) {
+        Ok((round_trip, _)) if round_trip == content => {}
+        _ => return Err("Not migrated, the converted project didn't read back".to_string()),
+    }
+    let written = write_project_file_blocking(file_path, content, true, true, true);
+    match written.error {
+        Some(e) => Err(e),
+        None => Ok((previous_version, true)),
+    }
+}
+
+// Upgrades a project file to the format write_project_file produces, for the
+// prompt shown when read_project_file reports needs_migration
+#[tauri::command]
+async fn migrate_project(
+    app: AppHandle,
+    file_path: String,
+    workspace_root: Option<String>,
+) -> timing::Timed<MigrateProjectResult> {
+    timing::measure(app, "migrate_project", async move {
+        let result = match check_workspace(workspace_root.as_deref(), &file_path) {
+            Ok(()) => run_blocking(None, move || migrate_project_blocking(file_path))
+                .await
+                .and_then(|r| r),
+            Err(e) => Err(e),
+        };
+        match result {
+            Ok((previous_version, migrated)) => MigrateProjectResult {
+                success: true,
+                previous_version: Some(previous_version),
+                format_version: Some(CURRENT_PROJECT_FORMAT),
+                migrated: Some(migrated),
+                error: None,
+            },
+            Err(e) => MigrateProjectResult {
+                success: false,
+                previous_version: None,
+                format_version: None,
+                migrated: None,
+                error: Some(e),
+            },
+        }
+    })
+    .await
+}
+
+// Archives the project's current file as a version, compressing it first if
+// it was saved as plain text. Nothing to archive before the first save.
+fn archive_project_version(
+    file_path: &str,
+    retention: &versions::VersionRetention,
+) -> Result<Option<versions::ProjectVersion>, String> {
+    let previous = match fs::read(file_path) {
+        Ok(data) => data,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e.to_string()),
+    };
+    let data = if is_compressed_project(&previous) {
+        previous
+    } else {
+        let (content, _) = decode_project_data(previous)?;
+        encode_project_data(&content, Compression::best())?
+    };
+    versions::archive(Path::new(file_path), &data, retention).map(Some)
+}
+
+// Like write_project_file, but the file being replaced is kept in
+// <name>.versions/ and pruned by `retention` (keepLast, keepDays)
+#[tauri::command]
+async fn write_project_versioned(
+    app: AppHandle,
+    file_path: String,
+    content: String,
+    workspace_root: Option<String>,
+    retention: Option<versions::VersionRetention>,
+) -> timing::Timed<VersionedWriteResult> {
+    timing::measure(app, "write_project_versioned", async move {
+        let failed = |error: String| VersionedWriteResult {
+            success: false,
+            archived: None,
+            compressed_size: None,
+            error: Some(error),
+        };
+        if let Err(e) = check_workspace(workspace_root.as_deref(), &file_path) {
+            return failed(e);
+        }
+
+        let retention = retention.unwrap_or_default();
+        let result = run_blocking(None, move || {
+            let archived = archive_project_version(&file_path, &retention)
+                .map_err(|e| format!("Not saved, archiving the previous version failed: {}", e))?;
+            let written = write_project_file_blocking(file_path, content, false, false, false);
+            match written.error {
+                Some(e) => Err(e),
+                None => Ok((archived, written.compressed_size)),
+            }
+        })
+        .await;
+        match result.and_then(|r| r) {
+            Ok((archived, compressed_size)) => VersionedWriteResult {
+                success: true,
+                archived,
+                compressed_size,
+                error: None,
+            },
+            Err(e) => failed(e),
+        }
+    })
+    .await
+}
+
+#[tauri::command]
+async fn list_project_versions(
+    app: AppHandle,
+    file_path: String,
+) -> timing::Timed<ProjectVersionsResult> {
+    timing::measure(app, "list_project_versions", async move {
+        let result = run_blocking(None, move || versions::list(Path::new(&file_path))).await;
+        match result.and_then(|r| r) {
+            Ok(versions) => ProjectVersionsResult {
+                success: true,
+                versions: Some(versions),
+                error: None,
+            },
+            Err(e) => ProjectVersionsResult {
+                success: false,
+                versions: None,
+                error: Some(e),
+            },
+        }
+    })
+    .await
+}
+
+// Opens an archived version read-only; restoring it is an ordinary save
+#[tauri::command]
+async fn open_project_version(
+    app: AppHandle,
+    file_path: String,
+    timestamp: u64,
+) -> timing::Timed<ProjectFileReadResult> {
+    timing::measure(app, "open_project_version", async move {
+        let result = run_blocking(None, move || {
+            versions::read(Path::new(&file_path), timestamp).and_then(decode_project_data)
+        })
+        .await;
+        project_read_result(result.and_then(|r| r))
+    })
+    .await
+}
+
+// A saved version's content, or the current file's for None
+fn project_version_content(project: &Path, timestamp: Option<u64>) -> Result<String, String> {
+    let data = match timestamp {
+        Some(timestamp) => versions::read(project, timestamp)?,
+        None => fs::read(project).map_err(|e| e.to_string())?,
+    };
+    decode_project_data(data).map(|(content, _)| content)
+}
+
+// What changed between two versions of a project, `from` and `to` being
+// version timestamps or none for the current file. JSON projects get the
+// top-level keys that were added, removed or modified; anything else gets a
+// line diff
+#[tauri::command]
+async fn project_version_changelog(
+    app: AppHandle,
+    file_path: String,
+    from: Option<u64>,
+    to: Option<u64>,
+) -> timing::Timed<ProjectChangelogResult> {
+    timing::measure(app, "project_version_changelog", async move {
+        let result = run_blocking(None, move || {
+            let project = Path::new(&file_path);
+            let old = project_version_content(project, from)?;
+            let new = project_version_content(project, to)?;
+            let parse = |text: &str| serde_json::from_str::<serde_json::Value>(text).ok();
+            let changes = parse(&old)
+                .zip(parse(&new))
+                .and_then(|(old, new)| project_diff::top_level_changes(&old, &new));
+            Ok(match changes {
+                Some(changes) => (Some(changes), None),
+                None => (None, Some(diff::diff_hunks(&old, &new, 3))),
+            })
+        })
+        .await;
+        match result.and_then(|r| r) {
+            Ok((changes, hunks)) => ProjectChangelogResult {
+                success: true,
+                changes,
+                hunks,
+                error: None,
+            },
+            Err(e) => ProjectChangelogResult {
+                success: false,
+                changes: None,
+                hunks: None,
+                error: Some(e),
+            },
+        }
+    })
+    .await
+}
+
+fn templates_root(app: &AppHandle) -> Result<PathBuf, String> {
+    app_dirs(app).map(|(_, data_dir)| data_dir.join(templates::TEMPLATES_DIR_NAME))
+}
+
+// Bundles an installed template into a single .r5vtpl file, compressed the
+// same way as project files
+#[tauri::command]
+async fn export_template(
+    app: AppHandle,
+    name: String,
+    output_path: String,
+) -> timing::Timed<TemplateBundleResult> {
+    timing::measure(app.clone(), "export_template", async move {
+        let result = match templates_root(&app) {
+            Ok(root) => run_blocking(None, move || {
+                let dir = root.join(&name);
+                let descriptor = templates::load_descriptor(&dir)?;
+                let data = encode_project_data(&templates::bundle(&dir)?, Compression::best())?;
+                write_atomic(Path::new(&output_path), &data)
+                    .map_err(|e| format!("Failed to write {}: {}", output_path, e))?;
+                Ok((descriptor, output_path))
+            })
+            .await
+            .and_then(|r| r),
+            Err(e) => Err(e),
+        };
+        match result {
+            Ok((descriptor, path)) => TemplateBundleResult {
+                success: true,
+                path: Some(path),
+                template: Some(descriptor),
+                error: None,
+            },
+            Err(e) => TemplateBundleResult {
+                success: false,
+                path: None,
+                template: None,
+                error: Some(e),
+            },
+        }
+    })
+    .await
+}
+
+// Installs a .r5vtpl into the templates directory. If a template of that
+// name exists the error starts with "Exists:"; calling again with `rename`
+// installs it under another name
+#[tauri::command]
+async fn import_template(
+    app: AppHandle,
+    file_path: String,
+    rename: Option<String>,
+) -> timing::Timed<TemplateBundleResult> {
+    timing::measure(app.clone(), "import_template", async move {
+        let result = match templates_root(&app) {
+            Ok(root) => run_blocking(None, move || {
+                let data = fs::read(&file_path)
+                    .map_err(|e| format!("Failed to read {}: {}", file_path, e))?;
+                let (bundle, _) = decode_project_data(data)?;
+                templates::install(&bundle, &root, rename.as_deref())
+            })
+            .await
+            .and_then(|r| r),
+            Err(e) => Err(e),
+        };
+        match result {
+            Ok((descriptor, path)) => TemplateBundleResult {
+                success: true,
+                path: Some(path.to_string_lossy().to_string()),
+                template: Some(descriptor),
+                error: None,
+            },
+            Err(e) => TemplateBundleResult {
+                success: false,
+                path: None,
+                template: None,
+                error: Some(e),
+            },
+        }
+    })
+    .await
+}
+
+fn project_metadata_path(file_path: &str) -> String {
+    format!("{}{}", file_path, PROJECT_METADATA_SUFFIX)
+}
+
+#[tauri::command]
+async fn read_project_metadata(
+    app: AppHandle,
+    file_path: String,
+) -> timing::Timed<ProjectMetadataResult> {
+    timing::measure(app, "read_project_metadata", async move {
+        let meta_path = project_metadata_path(&file_path);
+        let content = match fs::read_to_string(&meta_path) {
+            Ok(content) => content,
+            // A missing sidecar just means no metadata has been saved yet
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                return ProjectMetadataResult {
+                    success: true,
+                    metadata: Some(ProjectMetadata::default()),
+                    exists: Some(false),
+                    error: None,
+                };
+            }
+            Err(e) => {
+                return ProjectMetadataResult {
+                    success: false,
+                    metadata: None,
+                    exists: None,
+                    error: Some(e.to_string()),
+                };
+            }
+        };
+
+        match serde_json::from_str::<ProjectMetadata>(&content) {
+            Ok(metadata) => ProjectMetadataResult {
+                success: true,
+                metadata: Some(metadata),
+                exists: Some(true),
+                error: None,
+            },
+            Err(e) => ProjectMetadataResult {
+                success: false,
+                metadata: None,
+                exists: Some(true),
+                error: Some(format!("Invalid metadata file: {}", e)),
+            },
+        }
+    })
+    .await
+}
+
+#[tauri::command]
+async fn write_project_metadata(
+    app: AppHandle,
+    file_path: String,
+    metadata: ProjectMetadata,
+) -> timing::Timed<WriteFileResult> {
+    timing::measure(app, "write_project_metadata", async move {
+        let content = match serde_json::to_string_pretty(&metadata) {
+            Ok(content) => content,
+            Err(e) => {
+                return WriteFileResult {
+                    success: false,
+                    error: Some(e.to_string()),
+                };
+            }
+        };
+
+        match fs::write(project_metadata_path(&file_path), content) {
+            Ok(_) => WriteFileResult {
+                success: true,
+                error: None,
+            },
+            Err(e) => WriteFileResult {
+                success: false,
+                error: Some(e.to_string()),
+            },
+        }
+    })
+    .await
+}
+
+#[tauri::command]
+async fn list_directory(app: AppHandle, dir_path: String) -> timing::Timed<ListDirectoryResult> {
+    timing::measure(app, "list_directory", async move {
+        match fs::read_dir(&dir_path) {
+            Ok(entries) => {
+                let mut items = Vec::new();
+                for entry in entries.flatten() {
+                    let name = entry.file_name().to_string_lossy().to_string();
+                    let path = entry.path().to_string_lossy().to_string();
+                    let is_directory = entry.file_type().map(|t| t.is_dir()).unwrap_or(false);
+                    items.push(DirectoryItem {
+                        name,
+                        is_directory,
+                        path,
+                    });
+                }
+                ListDirectoryResult {
+                    success: true,
+                    items: Some(items),
+                    error: None,
+                }
+            }
+            Err(e) => ListDirectoryResult {
+                success: false,
+                items: None,
+                error: Some(e.to_string()),
+            },
+        }
+    })
+    .await
+}
+
+// Symlinks count when they point at a folder, as a picker would follow them
+fn entry_is_dir(entry: &fs::DirEntry) -> bool {
+    match entry.file_type() {
+        Ok(t) if t.is_symlink() => fs::metadata(entry.path()).is_ok_and(|m| m.is_dir()),
+        Ok(t) => t.is_dir(),
+        Err(_) => false,
+    }
+}
+
+// Stops at the first subfolder, so folders full of files stay cheap
+fn has_subdirectory(path: &Path) -> bool {
+    fs::read_dir(path).is_ok_and(|mut entries| entries.any(|e| e.is_ok_and(|e| entry_is_dir(&e))))
+}
+
+// Only the folders of a directory, sorted by name, for folder pickers
+#[tauri::command]
+async fn list_directories(
+    app: AppHandle,
+    dir_path: String,
+) -> timing::Timed<ListDirectoriesResult> {
+    timing::measure(app, "list_directories", async move {
+        let result = run_blocking(None, move || {
+            let entries = fs::read_dir(&dir_path).map_err(|e| e.to_string())?;
+            let mut items: Vec<FolderItem> = entries
+                .flatten()
+                .filter(entry_is_dir)
+                .map(|entry| {
+                    let path = entry.path();
+                    FolderItem {
+                        name: entry.file_name().to_string_lossy().to_string(),
+                        has_children: has_subdirectory(&path),
+                        path: path.to_string_lossy().to_string(),
+                    }
+                })
+                .collect();
+            items.sort_by_key(|item| item.name.to_lowercase());
+            Ok::<_, String>(items)
+        })
+        .await;
+        match result.and_then(|r| r) {
+            Ok(items) => ListDirectoriesResult {
+                success: true,
+                items: Some(items),
+                error: None,
+            },
+            Err(e) => ListDirectoriesResult {
+                success: false,
+                items: None,
+                error: Some(e),
+            },
+        }
+    })
+    .await
+}
+
+// Lets the UI warn before saving over a file another program holds
+#[tauri::command]
+async fn is_file_locked(app: AppHandle, file_path: String) -> timing::Timed<FileLockResult> {
+    timing::measure(app, "is_file_locked", async move {
+        match locking::is_locked(Path::new(&file_path)) {
+            Ok(locked) => FileLockResult {
+                success: true,
+                locked: Some(locked),
+                error: None,
+            },
+            Err(e) => FileLockResult {
+                success: false,
+                locked: None,
+                error: Some(e.to_string()),
+            },
+        }
+    })
+    .await
+}
+
+// Joins `relative` onto `base` and returns the normalized absolute path,
+// resolving symlinks for the parts that exist. With a root, results outside
+// of it are rejected.
+#[tauri::command]
+async fn resolve_path(
+    app: AppHandle,
+    base: String,
+    relative: String,
+    root: Option<String>,
+) -> timing::Timed<ResolvePathResult> {
+    timing::measure(app, "resolve_path", async move {
+        let joined = Path::new(&base).join(&relative);
+        let joined = std::path::absolute(&joined).unwrap_or(joined);
+        let resolved = match &root {
+            Some(root) => paths::ensure_within(Path::new(root), &joined),
+            None => Ok(paths::canonicalize_lenient(&joined)),
+        };
+        match resolved {
+            Ok(path) => ResolvePathResult {
+                success: true,
+                path: Some(path.to_string_lossy().to_string()),
+                error: None,
+            },
+            Err(e) => ResolvePathResult {
+                success: false,
+                path: None,
+                error: Some(e),
+            },
+        }
+    })
+    .await
+}
+
+// `target` relative to `base` with forward slashes, as manifests store paths.
+// Both are resolved first, so separators, `..` and symlinks don't matter;
+// a target outside of base is an error.
+#[tauri::command]
+async fn relative_path(
+    app: AppHandle,
+    base: String,
+    target: String,
+) -> timing::Timed<ResolvePathResult> {
+    timing::measure(app, "relative_path", async move {
+        let base = paths::canonicalize_lenient(Path::new(&base));
+        let relative = paths::ensure_within(&base, Path::new(&target))
+            .and_then(|target| {
+                paths::relative_slash_path(&base, &target)
+                    .ok_or_else(|| format!("{} is outside of {}", target.display(), base.display()))
+            });
+        match relative {
+            Ok(path) => ResolvePathResult {
+                success: true,
+                path: Some(path),
+                error: None,
+            },
+            Err(e) => ResolvePathResult {
+                success: false,
+                path: None,
+                error: Some(e),
+            },
+        }
+    })
+    .await
+}
+
+// Whether `path` lies inside `root` (or is `root` itself) once both are
+// resolved, so `..` segments and symlinks can't fake containment. Paths that
+// don't exist yet are resolved as far as they do
+#[tauri::command]
+async fn is_within(app: AppHandle, path: String, root: String) -> timing::Timed<IsWithinResult> {
+    timing::measure(app, "is_within", async move {
+        let path = std::path::absolute(&path).unwrap_or_else(|_| PathBuf::from(&path));
+        IsWithinResult {
+            success: true,
+            within: Some(paths::ensure_within(Path::new(&root), &path).is_ok()),
+            error: None,
+        }
+    })
+    .await
+}
+
+// The deepest directory holding every selected path, for scoping an export
+// or search to a scattered selection. Errors when they span drives
+#[tauri::command]
+async fn common_root(
+    app: AppHandle,
+    selection: Vec<String>,
+) -> timing::Timed<ResolvePathResult> {
+    timing::measure(app, "common_root", async move {
+        let selected: Vec<PathBuf> = selection.iter().map(PathBuf::from).collect();
+        match paths::common_root(&selected) {
+            Ok(path) => ResolvePathResult {
+                success: true,
+                path: Some(path.to_string_lossy().to_string()),
+                error: None,
+            },
+            Err(e) => ResolvePathResult {
+                success: false,
+                path: None,
+                error: Some(e),
+            },
+        }
+    })
+    .await
+}
+
+// Free and total bytes on the filesystem holding `path`, which need not exist
+#[tauri::command]
+async fn disk_space(app: AppHandle, path: String) -> timing::Timed<DiskSpaceResult> {
+    timing::measure(app, "disk_space", async move {
+        match disk::space(Path::new(&path)) {
+            Ok(space) => DiskSpaceResult {
+                success: true,
+                available_bytes: Some(space.available),
+                total_bytes: Some(space.total),
+                error: None,
+            },
+            Err(e) => DiskSpaceResult {
+                success: false,
+                available_bytes: None,
+                total_bytes: None,
+                error: Some(format!("Failed to query free space for {}: {}", path, e)),
+            },
+        }
+    })
+    .await
+}
+
+#[tauri::command]
+async fn open_external(app: AppHandle, file_path: String) -> timing::Timed<WriteFileResult> {
+    timing::measure(app, "open_external", async move {
+        if !Path::new(&file_path).exists() {
+            return WriteFileResult {
+                success: false,
+                error: Some(format!("{} does not exist", file_path)),
+            };
+        }
+
+        let path = PathBuf::from(&file_path);
+        let result = run_blocking(None, move || external::open_with_default(&path)).await;
+        match result.and_then(|r| r) {
+            Ok(()) => WriteFileResult {
+                success: true,
+                error: None,
+            },
+            Err(e) => WriteFileResult {
+                success: false,
+                error: Some(e),
+            },
+        }
+    })
+    .await
+}
+
+#[tauri::command]
+async fn create_directory(app: AppHandle, dir_path: String) -> timing::Timed<WriteFileResult> {
+    timing::measure(app, "create_directory", async move {
+        match fs::create_dir_all(&dir_path) {
+            Ok(_) => WriteFileResult {
+                success: true,
+                error: None,
+            },
+            Err(e) => WriteFileResult {
+                success: false,
+                error: Some(e.to_string()),
+            },
+        }
+    })
+    .await
+}
+
+// Removes one file, or moves it to the OS recycle bin with `to_trash`. A
+// file that is already gone counts as deleted
+#[tauri::command]
+async fn delete_file(
+    app: AppHandle,
+    file_path: String,
+    to_trash: Option<bool>,
+) -> timing::Timed<WriteFileResult> {
+    timing::measure(app, "delete_file", async move {
+        let result = run_blocking(None, move || {
+            let path = Path::new(&file_path);
+            // symlink_metadata so a dangling link still gets removed
+            let metadata = match fs::symlink_metadata(path) {
+                Ok(metadata) => metadata,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+                Err(e) => return Err(e.to_string()),
+            };
+            if metadata.is_dir() {
+                return Err(format!("{} is a folder", file_path));
+            }
+            if to_trash.unwrap_or(false) {
+                trash::delete(path).map_err(|e| format!("Failed to trash {}: {}", file_path, e))
+            } else {
+                fs::remove_file(path).map_err(|e| e.to_string())
+            }
+        })
+        .await;
+        match result.and_then(|r| r) {
+            Ok(()) => WriteFileResult {
+                success: true,
+                error: None,
+            },
+            Err(e) => WriteFileResult {
+                success: false,
+                error: Some(e),
+            },
+        }
+    })
+    .await
+}
+
+#[tauri::command]
+async fn delete_directory(app: AppHandle, dir_path: String) -> timing::Timed<WriteFileResult> {
+    timing::measure(app, "delete_directory", async move {
+        let path = Path::new(&dir_path);
+        if !path.exists() {
+            return WriteFileResult {
+                success: true,
+                error: None,
+            };
+        }
+        
+        match fs::remove_dir_all(&dir_path) {
+            Ok(_) => WriteFileResult {
+                success: true,
+                error: None,
+            },
+            Err(e) => WriteFileResult {
+                success: false,
+                error: Some(e.to_string()),
+            },
+        }
+    })
+    .await
+}
+
+// Rewrites include paths pointing into a moved folder across the mod's text
+// files. Binary files and files that aren't valid UTF-8 are left alone.
+fn update_directory_references(
+    mod_root: &Path,
+    old_dir: &Path,
+    new_dir: &Path,
+    dry_run: bool,
+) -> Result<Vec<references::ReferenceChange>, String> {
+    let old_key = mod_relative_key(mod_root, old_dir)?;
+    let new_key = mod_relative_key(mod_root, new_dir)?;
+    update_path_references(mod_root, &[(old_key, new_key)], dry_run)
+}
+
+// Rewrites include references for mod-relative (old, new) path pairs in every
+// text file under the mod
+fn update_path_references(
+    mod_root: &Path,
+    renames: &[(String, String)],
+    dry_run: bool,
+) -> Result<Vec<references::ReferenceChange>, String> {
+    let files = paths::walk_files(mod_root).map_err(|e| e.to_string())?;
+
+    let mut changes = Vec::new();
+    for path in files {
+        if !file_type::detect_file_type(&path).is_text() {
+            continue;
+        }
+        let Ok(content) = fs::read_to_string(&path) else {
+            continue;
+        };
+        let Some(relative) = paths::relative_slash_path(mod_root, &path) else {
+            continue;
+        };
+        let Some((rewritten, file_changes)) =
+            references::rewrite_references(&relative, &content, renames)
+        else {
+            continue;
+        };
+        if !dry_run {
+            write_atomic(&path, rewritten.as_bytes())
+                .map_err(|e| format!("Failed to update {}: {}", relative, e))?;
+        }
+        changes.extend(file_changes);
+    }
+    Ok(changes)
+}
+
+fn move_path_blocking(
+    from_path: String,
+    to_path: String,
+    mod_root: Option<String>,
+    update_references: bool,
+    dry_run: bool,
+) -> MovePathResult {
+    let failed = |error: String| MovePathResult {
+        success: false,
+        path: None,
+        warning: None,
+        reference_changes: None,
+        case_only_rename: None,
+        error: Some(error),
+    };
+
+    let case_only = paths::is_case_only_rename(Path::new(&from_path), Path::new(&to_path));
+    if !case_only && Path::new(&to_path).exists() {
+        return failed("Destination already exists".to_string());
+    }
+
+    let from_dir = Path::new(&from_path).is_dir();
+    let references_root = match (update_references && from_dir, &mod_root) {
+        (false, _) => None,
+        (true, Some(root)) => Some(PathBuf::from(root)),
+        (true, None) => return failed("Updating references requires a mod root".to_string()),
+    };
+    let rewrite_references = |dry_run: bool| match &references_root {
+        Some(root) => {
+            let (from, to) = (Path::new(&from_path), Path::new(&to_path));
+            update_directory_references(root, from, to, dry_run).map(Some)
+        }
+        None => Ok(None),
+    };
+
+    if dry_run {
+        return match rewrite_references(true) {
+            Ok(changes) => MovePathResult {
+                success: true,
+                path: None,
+                warning: None,
+                reference_changes: changes,
+                case_only_rename: case_only.then_some(true),
+                error: None,
+            },
+            Err(e) => failed(e),
+        };
+    }
+
+    let moved = if case_only {
+        paths::rename_case_only(Path::new(&from_path), Path::new(&to_path))
+    } else {
+        fs::rename(&from_path, &to_path)
+    };
+    if let Err(e) = moved {
+        return failed(e.to_string());
+    }
+
+    // Tags and references are follow-ups; the move itself already succeeded
+    let mut warnings = Vec::new();
+    if let Some(root) = &mod_root {
+        let (root, from, to) = (Path::new(root), Path::new(&from_path), Path::new(&to_path));
+        if let Err(e) = move_file_tags(root, from, to) {
+            warnings.push(format!("Moved, but failed to update tags: {}", e));
+        }
+    }
+    let reference_changes = rewrite_references(false).unwrap_or_else(|e| {
+        warnings.push(format!("Moved, but failed to update references: {}", e));
+        None
+    });
+
+    MovePathResult {
+        success: true,
+        path: Some(to_path),
+        warning: if warnings.is_empty() { None } else { Some(warnings.join("; ")) },
+        reference_changes,
+        case_only_rename: case_only.then_some(true),
+        error: None,
+    }
+}
+
+// With update_references, include paths into a moved folder are rewritten
+// (needs mod_root). A dry run only reports those rewrites and moves nothing.
+#[tauri::command]
+async fn move_path(
+    app: AppHandle,
+    from_path: String,
+    to_path: String,
+    mod_root: Option<String>,
+    update_references: Option<bool>,
+    dry_run: Option<bool>,
+) -> timing::Timed<MovePathResult> {
+    timing::measure(app, "move_path", async move {
+        let (update_references, dry_run) =
+            (update_references.unwrap_or(false), dry_run.unwrap_or(false));
+        run_blocking(None, move || {
+            move_path_blocking(from_path, to_path, mod_root, update_references, dry_run)
+        })
+        .await
+        .unwrap_or_else(|e| MovePathResult {
+            success: false,
+            path: None,
+            warning: None,
+            reference_changes: None,
+            case_only_rename: None,
+            error: Some(e),
+        })
+    })
+    .await
+}
+
+// Every file under `root` with extension `from_ext`, paired with its new path.
+// Fails, listing them, if any target is already taken.
+fn plan_extension_renames(
+    root: &Path,
+    from_ext: &str,
+    to_ext: &str,
+) -> Result<Vec<(PathBuf, PathBuf)>, String> {
+    let mut planned: Vec<(PathBuf, PathBuf)> = paths::walk_files(root)
+        .map_err(|e| format!("Failed to read {}: {}", root.display(), e))?
+        .into_iter()
+        .filter(|path| path.extension().is_some_and(|e| e.eq_ignore_ascii_case(from_ext)))
+        .map(|path| {
+            let target = path.with_extension(to_ext);
+            (path, target)
+        })
+        .collect();
+    planned.sort();
+
+    // A target is taken if it exists, or if two files (say a.NUT and a.nut)
+    // would both be renamed to it
+    let mut targets = std::collections::HashSet::new();
+    let collisions: Vec<String> = planned
+        .iter()
+        .filter(|(from, to)| {
+            !targets.insert(to.clone()) || (to.exists() && !paths::is_case_only_rename(from, to))
+        })
+        .map(|(_, to)| to.display().to_string())
+        .collect();
+    if !collisions.is_empty() {
+        return Err(format!(
+            "Renaming would overwrite existing files: {}",
+            collisions.join(", ")
+        ));
+    }
+    Ok(planned)
+}
+
+fn rename_extension_blocking(
+    root: String,
+    from_ext: String,
+    to_ext: String,
+    mod_root: Option<String>,
+    update_references: bool,
+    dry_run: bool,
+) -> RenameExtensionResult {
+    let failed = |error: String| RenameExtensionResult {
+        success: false,
+        renames: None,
+        reference_changes: None,
+        warning: None,
+        error: Some(error),
+    };
+
+    let (from_ext, to_ext) = (from_ext.trim_start_matches('.'), to_ext.trim_start_matches('.'));
+    if from_ext.is_empty() || to_ext.is_empty() || from_ext == to_ext {
+        return failed("Give two different, non-empty extensions".to_string());
+    }
+    let references_root = match (update_references, &mod_root) {
+        (false, _) => None,
+        (true, Some(root)) => Some(PathBuf::from(root)),
+        (true, None) => return failed("Updating references requires a mod root".to_string()),
+    };
+
+    let planned = match plan_extension_renames(Path::new(&root), from_ext, to_ext) {
+        Ok(planned) => planned,
+        Err(e) => return failed(e),
+    };
+    let keys = |renamed: &[(PathBuf, PathBuf)], root: &Path| {
+        renamed
+            .iter()
+            .map(|(from, to)| Ok((mod_relative_key(root, from)?, mod_relative_key(root, to)?)))
+            .collect::<Result<Vec<_>, String>>()
+    };
+    let to_result = |renamed: &[(PathBuf, PathBuf)]| {
+        renamed
+            .iter()
+            .map(|(from, to)| PlannedRename {
+                from: from.to_string_lossy().to_string(),
+                to: to.to_string_lossy().to_string(),
+            })
+            .collect::<Vec<_>>()
+    };
+
+    if dry_run {
+        let reference_changes = match &references_root {
+            Some(root) => match keys(&planned, root)
+                .and_then(|keys| update_path_references(root, &keys, true))
+            {
+                Ok(changes) => Some(changes),
+                Err(e) => return failed(e),
+            },
+            None => None,
+        };
+        return RenameExtensionResult {
+            success: true,
+            renames: Some(to_result(&planned)),
+            reference_changes,
+            warning: None,
+            error: None,
+        };
+    }
+
+    let mut renamed = Vec::new();
+    let mut error = None;
+    for (from, to) in planned {
+        let moved = if paths::is_case_only_rename(&from, &to) {
+            paths::rename_case_only(&from, &to)
+        } else {
+            fs::rename(&from, &to)
+        };
+        match moved {
+            Ok(()) => renamed.push((from, to)),
+            Err(e) => {
+                error = Some(format!("Failed to rename {}: {}", from.display(), e));
+                break;
+            }
+        }
+    }
+
+    // As in move_path, tags and references follow whatever was renamed
+    let mut warnings = Vec::new();
+    if let Some(root) = &mod_root {
+        let root = Path::new(root);
+        for (from, to) in &renamed {
+            if let Err(e) = move_file_tags(root, from, to) {
+                warnings.push(format!("Failed to update tags: {}", e));
+                break;
+            }
+        }
+    }
+    let reference_changes = references_root.as_ref().and_then(|root| {
+        keys(&renamed, root)
+            .and_then(|keys| update_path_references(root, &keys, false))
+            .map_err(|e| warnings.push(format!("Failed to update references: {}", e)))
+            .ok()
+    });
+
+    RenameExtensionResult {
+        success: error.is_none(),
+        renames: Some(to_result(&renamed)),
+        reference_changes,
+        warning: if warnings.is_empty() { None } else { Some(warnings.join("; ")) },
+        error,
+    }
+}
+
+// Renames every `.from_ext` file under root to `.to_ext`. Nothing is renamed
+// if any target exists. With update_references (needs mod_root) includes of
+// the renamed files are rewritten; a dry run only reports the plan.
+#[tauri::command]
+async fn rename_extension(
+    app: AppHandle,
+    root: String,
+    from_ext: String,
+    to_ext: String,
+    mod_root: Option<String>,
+    update_references: Option<bool>,
+    dry_run: Option<bool>,
+) -> timing::Timed<RenameExtensionResult> {
+    timing::measure(app, "rename_extension", async move {
+        let (update_references, dry_run) =
+            (update_references.unwrap_or(false), dry_run.unwrap_or(false));
+        run_blocking(None, move || {
+            rename_extension_blocking(root, from_ext, to_ext, mod_root, update_references, dry_run)
+        })
+        .await
+        .unwrap_or_else(|e| RenameExtensionResult {
+            success: false,
+            renames: None,
+            reference_changes: None,
+            warning: None,
+            error: Some(e),
+        })
+    })
+    .await
+}
+
+// Volume serial and file index, Windows' equivalent of device and inode.
+// The link itself is opened rather than its target, as lstat does on Unix.
+#[cfg(windows)]
+fn windows_file_id(path: &Path) -> Option<String> {
+    use std::os::windows::fs::OpenOptionsExt;
+    use std::os::windows::io::AsRawHandle;
+    use windows_sys::Win32::Storage::FileSystem::{
+        GetFileInformationByHandle, BY_HANDLE_FILE_INFORMATION, FILE_FLAG_BACKUP_SEMANTICS,
+        FILE_FLAG_OPEN_REPARSE_POINT,
+    };
+
+    // No access rights are needed to query, and folders only open with
+    // backup semantics
+    let file = fs::OpenOptions::new()
+        .access_mode(0)
+        .custom_flags(FILE_FLAG_BACKUP_SEMANTICS | FILE_FLAG_OPEN_REPARSE_POINT)
+        .open(path)
+        .ok()?;
+    let mut info: BY_HANDLE_FILE_INFORMATION = unsafe { std::mem::zeroed() };
+    if unsafe { GetFileInformationByHandle(file.as_raw_handle(), &mut info) } == 0 {
+        return None;
+    }
+    let index = (u64::from(info.nFileIndexHigh) << 32) | u64::from(info.nFileIndexLow);
+    Some(format!("{:x}-{:x}", info.dwVolumeSerialNumber, index))
+}
+
+// Stable id for a tree node. The device and inode on Unix, and the volume
+// serial and file index on Windows, identify the file across renames. When
+// neither is available it falls back to a hash of the path, which is fixed
+// across builds but changes with a rename.
+fn file_item_id(path: &Path, metadata: Option<&fs::Metadata>) -> String {
+    #[cfg(unix)]
+    if let Some(metadata) = metadata {
+        use std::os::unix::fs::MetadataExt;
+        return format!("{:x}-{:x}", metadata.dev(), metadata.ino());
+    }
+    #[cfg(not(unix))]
+    let _ = metadata;
+    #[cfg(windows)]
+    if let Some(id) = windows_file_id(path) {
+        return id;
+    }
+
+    let hash = format!("{:x}", Sha256::digest(path.to_string_lossy().as_bytes()));
+    format!("p{}", &hash[..16])
+}
+
+// One tree entry. `file_type` and `metadata` don't follow symlinks, as with
+// fs::DirEntry; children are listed while depth < max_depth.
+fn build_file_item(
+    entry_path: &Path,
+    name: String,
+    file_type: std::io::Result<fs::FileType>,
+    metadata: Option<fs::Metadata>,
+    depth: usize,
+    max_depth: usize,
+    warnings: &mut Vec<String>,
+) -> FileItem {
+    let path_str = entry_path.to_string_lossy().to_string();
+    let entry_is_dir = file_type.as_ref().map(|t| t.is_dir()).unwrap_or(false);
+
+    let mut error = match &file_type {
+        Err(e) => Some(e.to_string()),
+        Ok(t) if t.is_symlink() && fs::metadata(entry_path).is_err() => {
+            Some("Broken symbolic link".to_string())
+        }
+        Ok(_) => None,
+    };
+
+    let children = if entry_is_dir && depth < max_depth {
+        match build_file_tree(entry_path, depth + 1, max_depth, warnings) {
+            Ok(children) => Some(children),
+            Err(e) => {
+                error = Some(e.to_string());
+                Some(Vec::new())
+            }
+        }
+    } else {
+        None
+    };
+    // Only peeks at the first entry, so an empty folder still looks empty
+    let truncated = (entry_is_dir && depth >= max_depth)
+        .then(|| fs::read_dir(entry_path).is_ok_and(|mut entries| entries.next().is_some()))
+        .filter(|&has_entries| has_entries);
+
+    if let Some(e) = &error {
+        warnings.push(format!("{}: {}", path_str, e));
+    }
+
+    // Sized through symlinks, so a linked file shows its target's size
+    let file_metadata = match (&file_type, &metadata) {
+        (Ok(t), _) if t.is_symlink() => fs::metadata(entry_path).ok(),
+        (_, Some(metadata)) => Some(metadata.clone()),
+        _ => None,
+    }
+    .filter(|m| m.is_file());
+    let modified = file_metadata.as_ref().and_then(modified_ms);
+
+    FileItem {
+        id: file_item_id(entry_path, metadata.as_ref()),
+        name,
+        path: path_str,
+        item_type: if entry_is_dir { "folder".to_string() } else { "file".to_string() },
+        children,
+        size: file_metadata.as_ref().map(|m| m.len()),
+        modified,
+        truncated,
+        error,
+    }
+}
+
+// Last modification in milliseconds since the epoch, as reported to the UI
+fn modified_ms(metadata: &fs::Metadata) -> Option<u64> {
+    metadata
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+        .map(|d| d.as_millis() as u64)
+}
+
+// Text files under `mod_root`, newest first, at most `limit` of them. Only
+// the files that make the cut are classified, since that can mean reading
+// their first bytes
+fn recent_files_blocking(mod_root: &Path, limit: usize) -> std::io::Result<Vec<RecentFile>> {
+    let rules = ignore::IgnoreRules::load(mod_root);
+    let mut candidates: Vec<(u64, PathBuf, String)> = paths::walk_files(mod_root)?
+        .into_iter()
+        .filter_map(|path| {
+            let relative = paths::relative_slash_path(mod_root, &path)?;
+            if rules.is_file_ignored(&relative) {
+                return None;
+            }
+            let modified = fs::metadata(&path).ok().as_ref().and_then(modified_ms)?;
+            Some((modified, path, relative))
+        })
+        .collect();
+    candidates.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.2.cmp(&b.2)));
+    Ok(candidates
+        .into_iter()
+        .filter(|(_, path, _)| file_type::detect_file_type(path).is_text())
+        .take(limit)
+        .map(|(modified, path, relative)| RecentFile {
+            path: path.to_string_lossy().to_string(),
+            relative,
+            modified,
+        })
+        .collect())
+}
+
+// The most recently modified text files of a mod, for a "recently edited"
+// panel. Binaries and .r5vignore matches are left out
+#[tauri::command]
+async fn recent_files_in_mod(
+    app: AppHandle,
+    mod_root: String,
+    limit: Option<usize>,
+) -> timing::Timed<RecentFilesResult> {
+    timing::measure(app, "recent_files_in_mod", async move {
+        let limit = limit.unwrap_or(DEFAULT_RECENT_FILES);
+        let result = run_blocking(None, move || {
+            recent_files_blocking(Path::new(&mod_root), limit)
+                .map_err(|e| format!("Failed to read {}: {}", mod_root, e))
+        })
+        .await;
+        match result.and_then(|r| r) {
+            Ok(files) => RecentFilesResult {
+                success: true,
+                files: Some(files),
+                error: None,
+            },
+            Err(e) => RecentFilesResult {
+                success: false,
+                files: None,
+                error: Some(e),
+            },
+        }
+    })
+    .await
+}
+
+// Entries that can't be read are still listed, flagged with an `error`, and
+// every problem is also collected into `warnings` for the caller to report
+fn build_file_tree(
+    path: &Path,
+    depth: usize,
+    max_depth: usize,
+    warnings: &mut Vec<String>,
+) -> std::io::Result<Vec<FileItem>> {
+    if depth > max_depth {
+        return Ok(Vec::new());
+    }
+
+    let mut entries = Vec::new();
+    for entry in fs::read_dir(path)? {
+        match entry {
+            Ok(entry) => {
+                let file_type = entry.file_type();
+                entries.push((entry, file_type));
+            }
+            Err(e) => warnings.push(format!("{}: {}", path.display(), e)),
+        }
+    }
+
+    // Sort: directories first, then by name
+    let is_dir = |file_type: &std::io::Result<fs::FileType>| {
+        file_type.as_ref().map(|t| t.is_dir()).unwrap_or(false)
+    };
+    entries.sort_by(|(a, a_type), (b, b_type)| match (is_dir(a_type), is_dir(b_type)) {
+        (true, false) => std::cmp::Ordering::Less,
+        (false, true) => std::cmp::Ordering::Greater,
+        _ => a.file_name().cmp(&b.file_name()),
+    });
+
+    // Siblings are walked concurrently; collecting an indexed parallel iterator
+    // keeps the sorted order, and each entry carries its own warnings so they
+    // can be merged back in that same order
+    let built: Vec<(FileItem, Vec<String>)> = entries
+        .into_par_iter()
+        .map(|(entry, file_type)| {
+            let mut entry_warnings = Vec::new();
+            let item = build_file_item(
+                &entry.path(),
+                entry.file_name().to_string_lossy().to_string(),
+                file_type,
+                entry.metadata().ok(),
+                depth,
+                max_depth,
+                &mut entry_warnings,
+            );
+            (item, entry_warnings)
+        })
+        .collect();
+
+    let mut items = Vec::with_capacity(built.len());
+    for (item, entry_warnings) in built {
+        warnings.extend(entry_warnings);
+        items.push(item);
+    }
+
+    Ok(items)
+}
+
+// The mod's details from mod.vdf and manifest.json, with manifest fields
+// winning as in the markdown export. None when the folder has no mod.vdf.
+fn read_mod_data(mod_root: &Path) -> Option<ModData> {
+    let (vdf_key, vdf) = manifest::load_mod_vdf_entry(mod_root)?;
+    let manifest = manifest::load(mod_root).ok();
+    let field = |key: &str, vdf_key: &str| {
+        manifest
+            .as_ref()
+            .and_then(|m| m.get(key))
+            .and_then(|v| v.as_str())
+            .or_else(|| vdf.get_str(vdf_key))
+            .unwrap_or_default()
+            .to_string()
+    };
+    // mod.vdf holds the id as its block's key rather than as a value
+    let mod_id = manifest
+        .as_ref()
+        .and_then(|m| m.get("modId"))
+        .and_then(|v| v.as_str())
+        .filter(|id| !id.is_empty())
+        .map_or(vdf_key, str::to_string);
+    Some(ModData {
+        name: field("name", "Name"),
+        description: field("description", "Description"),
+        author: field("author", "Author"),
+        version: field("version", "Version"),
+        mod_id,
+        // As in create_mod, the folder the mod lives in
+        path: mod_root
+            .parent()
+            .map(|p| p.to_string_lossy().to_string())
+            .unwrap_or_default(),
+    })
+}
+
+fn open_mod_folder_blocking(folder_path: String) -> OpenModFolderResult {
+    let path = Path::new(&folder_path);
+    if !path.exists() {
+        return OpenModFolderResult {
+            success: false,
+            tree: None,
+            root_path: None,
+            mod_data: None,
+            warnings: None,
+            error: Some("Folder does not exist".to_string()),
+        };
+    }
+
+    let mut warnings = Vec::new();
+    match build_file_tree(path, 0, 3, &mut warnings) {
+        Ok(tree) => OpenModFolderResult {
+            success: true,
+            tree: Some(tree),
+            mod_data: read_mod_data(path),
+            root_path: Some(folder_path),
+            warnings: if warnings.is_empty() { None } else { Some(warnings) },
+            error: None,
+        },
+        Err(e) => OpenModFolderResult {
+            success: false,
+            tree: None,
+            root_path: None,
+            mod_data: None,
+            warnings: None,
+            error: Some(format!("Failed to read folder: {}", e)),
+        },
+    }
+}
+
+#[tauri::command]
+async fn open_mod_folder(
+    app: AppHandle,
+    folder_path: String,
+    timeout_ms: Option<u64>,
+) -> timing::Timed<OpenModFolderResult> {
+    timing::measure(app, "open_mod_folder", async move {
+        run_blocking(timeout_ms, move || open_mod_folder_blocking(folder_path))
+            .await
+            .unwrap_or_else(|e| OpenModFolderResult {
+                success: false,
+                tree: None,
+                root_path: None,
+                mod_data: None,
+                warnings: None,
+                error: Some(e),
+            })
+    })
+    .await
+}
+
+// Lists one level of a folder for lazy tree loading, with the same ids as
+// open_mod_folder
+#[tauri::command]
+async fn expand_directory(
+    app: AppHandle,
+    dir_path: String,
+) -> timing::Timed<ExpandDirectoryResult> {
+    timing::measure(app, "expand_directory", async move {
+        let mut warnings = Vec::new();
+        match build_file_tree(Path::new(&dir_path), 0, 0, &mut warnings) {
+            Ok(items) => ExpandDirectoryResult {
+                success: true,
+                items: Some(items),
+                warnings: if warnings.is_empty() { None } else { Some(warnings) },
+                error: None,
+            },
+            Err(e) => ExpandDirectoryResult {
+                success: false,
+                items: None,
+                warnings: None,
+                error: Some(e.to_string()),
+            },
+        }
+    })
+    .await
+}
+
+#[derive(Default)]
+struct TreeCounts {
+    files: u64,
+    directories: u64,
+    bytes: u64,
+    excluded_files: u64,
+    excluded_directories: u64,
+    excluded_bytes: u64,
+    // Entries that could not be read, left out of the totals
+    warnings: Vec<String>,
+}
+
+// Totals for everything under `root`. Hidden entries (which exports leave
+// out) and those matching .r5vignore or `extra_ignores` count as excluded,
+// along with everything inside them. Symlinks count as a single file sized by
+// the link itself and are never followed. Only an unreadable `root` is an
+// error; anything unreadable below it ends up in `warnings`.
+fn count_tree_blocking(root: &Path, extra_ignores: &[String]) -> std::io::Result<TreeCounts> {
+    let mut rules = ignore::IgnoreRules::load(root);
+    rules.extend(ignore::IgnoreRules::parse(&extra_ignores.join("\n")));
+
+    let mut counts = TreeCounts::default();
+    let mut pending = vec![(root.to_path_buf(), false)];
+    while let Some((dir, excluded)) = pending.pop() {
+        let entries = match fs::read_dir(&dir) {
+            Ok(entries) => entries,
+            Err(e) if dir == root => return Err(e),
+            Err(e) => {
+                counts.warnings.push(format!("{}: {}", dir.display(), e));
+                continue;
+            }
+        };
+        for entry in entries {
+            let entry = match entry {
+                Ok(entry) => entry,
+                Err(e) => {
+                    counts.warnings.push(format!("{}: {}", dir.display(), e));
+                    continue;
+                }
+            };
+            let path = entry.path();
+            let file_type = match entry.file_type() {
+                Ok(file_type) => file_type,
+                Err(e) => {
+                    counts.warnings.push(format!("{}: {}", path.display(), e));
+                    continue;
+                }
+            };
+            let relative = paths::relative_slash_path(root, &path).unwrap_or_default();
+            let excluded = excluded
+                || entry.file_name().to_string_lossy().starts_with('.')
+                || rules.is_ignored(&relative, file_type.is_dir());
+
+            if file_type.is_dir() {
+                if excluded {
+                    counts.excluded_directories += 1;
+                } else {
+                    counts.directories += 1;
+                }
+                pending.push((path, excluded));
+            } else {
+                let size = match fs::symlink_metadata(&path) {
+                    Ok(metadata) => metadata.len(),
+                    Err(e) => {
+                        counts.warnings.push(format!("{}: {}", path.display(), e));
+                        0
+                    }
+                };
+                if excluded {
+                    counts.excluded_files += 1;
+                    counts.excluded_bytes += size;
+                } else {
+                    counts.files += 1;
+                    counts.bytes += size;
+                }
+            }
+        }
+    }
+    Ok(counts)
+}
+
+// Size of a folder as it would ship, with ignored entries totalled apart.
+// `ignore` takes .r5vignore-style patterns on top of the folder's own file.
+#[tauri::command]
+async fn directory_size(
+    app: AppHandle,
+    path: String,
+    ignore: Option<Vec<String>>,
+) -> timing::Timed<DirectorySizeResult> {
+    timing::measure(app, "directory_size", async move {
+        let root = PathBuf::from(&path);
+        let ignore = ignore.unwrap_or_default();
+        let result = run_blocking(None, move || {
+            count_tree_blocking(&root, &ignore)
+                .map_err(|e| format!("Failed to read {}: {}", path, e))
+        })
+        .await;
+        match result.and_then(|r| r) {
+            Ok(counts) => DirectorySizeResult {
+                success: true,
+                bytes: Some(counts.bytes),
+                excluded_bytes: Some(counts.excluded_bytes),
+                warnings: if counts.warnings.is_empty() { None } else { Some(counts.warnings) },
+                error: None,
+            },
+            Err(e) => DirectorySizeResult {
+                success: false,
+                bytes: None,
+                excluded_bytes: None,
+                warnings: None,
+                error: Some(e),
+            },
+        }
+    })
+    .await
+}
+
+// File and folder counts under a path, excluding the same entries as
+// directory_size
+#[tauri::command]
+async fn count_tree(
+    app: AppHandle,
+    path: String,
+    ignore: Option<Vec<String>>,
+) -> timing::Timed<CountTreeResult> {
+    timing::measure(app, "count_tree", async move {
+        let root = PathBuf::from(&path);
+        let ignore = ignore.unwrap_or_default();
+        let result = run_blocking(None, move || {
+            count_tree_blocking(&root, &ignore)
+                .map_err(|e| format!("Failed to read {}: {}", path, e))
+        })
+        .await;
+        match result.and_then(|r| r) {
+            Ok(counts) => CountTreeResult {
+                success: true,
+                files: Some(counts.files),
+                directories: Some(counts.directories),
+                excluded_files: Some(counts.excluded_files),
+                excluded_directories: Some(counts.excluded_directories),
+                warnings: if counts.warnings.is_empty() { None } else { Some(counts.warnings) },
+                error: None,
+            },
+            Err(e) => CountTreeResult {
+                success: false,
+                files: None,
+                directories: None,
+                excluded_files: None,
+                excluded_directories: None,
+                warnings: None,
+                error: Some(e),
+            },
+        }
+    })
+    .await
+}
+
+// Refreshes a single node after a change, with one level of children for a
+// folder. A path that is gone is reported as removed rather than as an error.
+#[tauri::command]
+async fn get_tree_node(app: AppHandle, path: String) -> timing::Timed<TreeNodeResult> {
+    timing::measure(app, "get_tree_node", async move {
+        let entry_path = PathBuf::from(&path);
+        let metadata = match fs::symlink_metadata(&entry_path) {
+            Ok(metadata) => metadata,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                return TreeNodeResult {
+                    success: true,
+                    node: None,
+                    removed: Some(true),
+                    warnings: None,
+                    error: None,
+                };
+            }
+            Err(e) => {
+                return TreeNodeResult {
+                    success: false,
+                    node: None,
+                    removed: None,
+                    warnings: None,
+                    error: Some(e.to_string()),
+                };
+            }
+        };
+        let name = entry_path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| path.clone());
+
+        let mut warnings = Vec::new();
+        let node = build_file_item(
+            &entry_path,
+            name,
+            Ok(metadata.file_type()),
+            Some(metadata),
+            0,
+            1,
+            &mut warnings,
+        );
+        TreeNodeResult {
+            success: true,
+            node: Some(node),
+            removed: Some(false),
+            warnings: if warnings.is_empty() { None } else { Some(warnings) },
+            error: None,
+        }
+    })
+    .await
+}
+
+// How deep list_all_files goes when not told; deep enough for any real mod
+const DEFAULT_LIST_MAX_DEPTH: usize = 32;
+
+// Depth counts like build_file_tree: entries directly under the root are at
+// depth 0. Returns the files with their sizes, sorted by path.
+fn list_all_files_blocking(
+    root: &Path,
+    max_depth: Option<usize>,
+    include_hidden: bool,
+    respect_ignore: bool,
+    warnings: &mut Vec<String>,
+) -> std::io::Result<Vec<(String, u64)>> {
+    let rules = if respect_ignore {
+        ignore::IgnoreRules::load(root)
+    } else {
+        ignore::IgnoreRules::default()
+    };
+    let max_depth = max_depth.unwrap_or(DEFAULT_LIST_MAX_DEPTH);
+    let mut files = Vec::new();
+    let mut visited = paths::VisitedDirs::default();
+    visited.first_visit(root);
+    let mut pending = vec![(root.to_path_buf(), 0)];
+    while let Some((dir, depth)) = pending.pop() {
+        let entries = match fs::read_dir(&dir) {
+            Ok(entries) => entries,
+            // Only the root itself failing is an error
+            Err(e) if dir == root => return Err(e),
+            Err(e) => {
+                warnings.push(format!("{}: {}", dir.display(), e));
+                continue;
+            }
+        };
+        for entry in entries {
+            let entry = match entry {
+                Ok(entry) => entry,
+                Err(e) => {
+                    warnings.push(format!("{}: {}", dir.display(), e));
+                    continue;
+                }
+            };
+            if !include_hidden && entry.file_name().to_string_lossy().starts_with('.') {
+                continue;
+            }
+            let path = entry.path();
+            // Follows symlinks, so a linked folder is walked like a real one,
+            // but only once however many links lead to it
+            let metadata = fs::metadata(&path).ok();
+            let is_dir = metadata.as_ref().is_some_and(|m| m.is_dir());
+            let relative = paths::relative_slash_path(root, &path).unwrap_or_default();
+            if rules.is_ignored(&relative, is_dir) {
+                continue;
+            }
+            if !is_dir {
+                let size = metadata.map_or(0, |m| m.len());
+                files.push((path.to_string_lossy().to_string(), size));
+            } else if depth < max_depth && visited.first_visit(&path) {
+                pending.push((path, depth + 1));
+            }
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+// Every file under a root as a flat list, for views that don't need the
+// tree. Skips what .r5vignore lists and, unless asked, hidden entries.
+// max_depth defaults to DEFAULT_LIST_MAX_DEPTH.
+#[tauri::command]
+async fn list_all_files(
+    app: AppHandle,
+    root: String,
+    max_depth: Option<usize>,
+    include_hidden: Option<bool>,
+) -> timing::Timed<ListAllFilesResult> {
+    timing::measure(app, "list_all_files", async move {
+        let include_hidden = include_hidden.unwrap_or(false);
+        let result = run_blocking(None, move || {
+            let mut warnings = Vec::new();
+            let root_path = Path::new(&root);
+            list_all_files_blocking(root_path, max_depth, include_hidden, true, &mut warnings)
+                .map(|files| (files.into_iter().map(|(path, _)| path).collect(), warnings))
+                .map_err(|e| format!("Failed to read {}: {}", root, e))
+        })
+        .await;
+        match result.and_then(|r| r) {
+            Ok((files, warnings)) => ListAllFilesResult {
+                success: true,
+                files: Some(files),
+                warnings: if warnings.is_empty() { None } else { Some(warnings) },
+                error: None,
+            },
+            Err(e) => ListAllFilesResult {
+                success: false,
+                files: None,
+                warnings: None,
+                error: Some(e),
+            },
+        }
+    })
+    .await
+}
+
+// The files under a root packed for a fuzzy finder: root-relative paths and
+// their sizes as parallel arrays, sorted by path, plus a hash of both so the
+// client can keep a cached copy until it changes. Takes the same options as
+// list_all_files; respect_ignore (default true) applies .r5vignore
+#[tauri::command]
+async fn build_flat_index(
+    app: AppHandle,
+    root: String,
+    max_depth: Option<usize>,
+    include_hidden: Option<bool>,
+    respect_ignore: Option<bool>,
+) -> timing::Timed<FlatIndexResult> {
+    timing::measure(app, "build_flat_index", async move {
+        let include_hidden = include_hidden.unwrap_or(false);
+        let respect_ignore = respect_ignore.unwrap_or(true);
+        let result = run_blocking(None, move || {
+            let root_path = Path::new(&root);
+            let mut warnings = Vec::new();
+            let files = list_all_files_blocking(
+                root_path,
+                max_depth,
+                include_hidden,
+                respect_ignore,
+                &mut warnings,
+            )
+            .map_err(|e| format!("Failed to read {}: {}", root, e))?;
+
+            let mut hasher = Sha256::new();
+            let mut paths_out = Vec::with_capacity(files.len());
+            let mut sizes = Vec::with_capacity(files.len());
+            for (path, size) in files {
+                let relative =
+                    paths::relative_slash_path(root_path, Path::new(&path)).unwrap_or(path);
+                hasher.update(relative.as_bytes());
+                hasher.update([0]);
+                hasher.update(size.to_le_bytes());
+                paths_out.push(relative);
+                sizes.push(size);
+            }
+            Ok((paths_out, sizes, format!("{:x}", hasher.finalize()), warnings))
+        })
+        .await;
+        match result.and_then(|r| r) {
+            Ok((paths, sizes, hash, warnings)) => FlatIndexResult {
+                success: true,
+                paths: Some(paths),
+                sizes: Some(sizes),
+                hash: Some(hash),
+                warnings: if warnings.is_empty() { None } else { Some(warnings) },
+                error: None,
+            },
+            Err(e) => FlatIndexResult {
+                success: false,
+                paths: None,
+                sizes: None,
+                hash: None,
+                warnings: None,
+                error: Some(e),
+            },
+        }
+    })
+    .await
+}
+
+const GITIGNORE_FILE_NAME: &str = ".gitignore";
+
+// Files the studio and the OS leave in a mod folder that don't belong in
+// version control
+const GITIGNORE_TEMPLATE: &str = r#"# Local edit history kept by the studio
+.r5vhistory/
+
+# Temporary files from saves and renames
+.*.tmp
+.*.rename
+
+# Exported release archives, finished or interrupted
+*.zip
+*.zip.part
+
+# Streamed pak data is large; track it with Git LFS or ship it separately
+*.starpak
+
+# OS files
+.DS_Store
+Thumbs.db
+desktop.ini
+"#;
+
+// Everything create_mod writes, in creation order
+fn create_mod_plan(mod_data: &ModData, gitignore: bool) -> ModPlan {
+    let mod_dir = format!("{}/{}", mod_data.path, mod_data.mod_id);
+
+    let directories = vec![
+        mod_dir.clone(),
+        format!("{}/scripts", mod_dir),
+        format!("{}/scripts/vscripts", mod_dir),
+        format!("{}/paks", mod_dir),
+        format!("{}/audio", mod_dir),
+        format!("{}/resource", mod_dir),
+    ];
+
+    let vdf_content = format!(
+        r#""{}"
+{{
+    "Name"              "{}"
+    "Description"       "{}"
+    "Version"           "{}"
+    "RequiredOnClient"  "1"
+}}"#,
+        mod_data.mod_id, mod_data.name, mod_data.description, mod_data.version
+    );
+
+    let manifest = manifest::skeleton(
+        &mod_data.name,
+        &mod_data.description,
+        &mod_data.version,
+        &mod_data.author,
+        &mod_data.mod_id,
+    );
+
+    let readme = format!(
+        r#"# {}
+
+{}
+
+## Author
+{}
+
+## Version
+{}
+
+## Installation
+Place this mod in your mods directory.
+"#,
+        mod_data.name, mod_data.description, mod_data.author, mod_data.version
+    );
+
+    let mut files = vec![
+        PlannedFile {
+            path: format!("{}/mod.vdf", mod_dir),
+            content: vdf_content,
+        },
+        PlannedFile {
+            path: format!("{}/manifest.json", mod_dir),
+            content: serde_json::to_string_pretty(&manifest).unwrap(),
+        },
+        PlannedFile {
+            path: format!("{}/README.md", mod_dir),
+            content: readme,
+        },
+    ];
+    if gitignore {
+        files.push(PlannedFile {
+            path: format!("{}/{}", mod_dir, GITIGNORE_FILE_NAME),
+            content: GITIGNORE_TEMPLATE.to_string(),
+        });
+    }
+
+    ModPlan {
+        root: mod_dir,
+        directories,
+        files,
+    }
+}
+
+// With preview, returns what would be created without touching the disk,
+// even if the mod directory already exists. `gitignore` adds a .gitignore
+// for mods kept under version control
+#[tauri::command]
+async fn create_mod(
+    app: AppHandle,
+    mod_data: ModData,
+    preview: Option<bool>,
+    gitignore: Option<bool>,
+) -> timing::Timed<CreateModResult> {
+    timing::measure(app, "create_mod", async move {
+        let plan = create_mod_plan(&mod_data, gitignore.unwrap_or(false));
+        let exists = Path::new(&plan.root).exists();
+
+        if preview.unwrap_or(false) {
+            return CreateModResult {
+                success: true,
+                path: Some(plan.root.clone()),
+                already_exists: Some(exists),
+                plan: Some(plan),
+                error: None,
+            };
+        }
+
+        let failed = |error: String| CreateModResult {
+            success: false,
+            path: None,
+            already_exists: None,
+            plan: None,
+            error: Some(error),
+        };
+
+        if exists {
+            return failed("Mod directory already exists".to_string());
+        }
+
+        for dir in &plan.directories {
+            if let Err(e) = fs::create_dir_all(dir) {
+                return failed(format!("Failed to create directory: {}", e));
+            }
+        }
+
+        for file in &plan.files {
+            if let Err(e) = fs::write(&file.path, &file.content) {
+                let name = Path::new(&file.path)
+                    .file_name()
+                    .map(|n| n.to_string_lossy().to_string())
+                    .unwrap_or_default();
+                return failed(format!("Failed to write {}: {}", name, e));
+            }
+        }
+
+        CreateModResult {
+            success: true,
+            path: Some(plan.root),
+            already_exists: None,
+            plan: None,
+            error: None,
+        }
+    })
+    .await
+}
+
+// Gives up rather than probing a folder forever
+const MAX_UNIQUE_NAME_ATTEMPTS: u32 = 10_000;
+
+// `name` with " (n)" before its extension: "Script.nut" -> "Script (2).nut".
+// Dotfiles such as ".gitignore" count as having no extension.
+fn numbered_name(name: &str, n: u32) -> String {
+    let path = Path::new(name);
+    match (path.file_stem(), path.extension()) {
+        (Some(stem), Some(ext)) => {
+            format!("{} ({}).{}", stem.to_string_lossy(), n, ext.to_string_lossy())
+        }
+        _ => format!("{} ({})", name, n),
+    }
+}
+
+// Creates an empty file named `name` in `dir`, or the first free numbered
+// variant of it. Each candidate is created with create_new, so a file that
+// appears between the check and the create is never overwritten.
+fn create_unique_file_blocking(dir: &Path, name: &str) -> Result<(PathBuf, String), String> {
+    if Path::new(name).file_name() != Some(std::ffi::OsStr::new(name)) {
+        return Err(format!("{:?} is not a file name", name));
+    }
+    for n in 1..=MAX_UNIQUE_NAME_ATTEMPTS {
+        let candidate = if n == 1 { name.to_string() } else { numbered_name(name, n) };
+        let path = dir.join(&candidate);
+        match fs::OpenOptions::new().write(true).create_new(true).open(&path) {
+            Ok(_) => return Ok((path, candidate)),
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(format!("Failed to create {}: {}", path.display(), e)),
+        }
+    }
+    Err(format!("No free name for {} in {}", name, dir.display()))
+}
+
+// Creates a new empty file without clobbering anything, e.g. as the target of
+// a duplicate. Returns where it ended up
+#[tauri::command]
+async fn create_unique_file(
+    app: AppHandle,
+    dir: String,
+    name: String,
+) -> timing::Timed<CreateUniqueFileResult> {
+    timing::measure(app, "create_unique_file", async move {
+        let result = run_blocking(None, move || create_unique_file_blocking(Path::new(&dir), &name))
+            .await
+            .and_then(|r| r);
+        match result {
+            Ok((path, name)) => CreateUniqueFileResult {
+                success: true,
+                path: Some(path.to_string_lossy().to_string()),
+                name: Some(name),
+                error: None,
+            },
+            Err(e) => CreateUniqueFileResult {
+                success: false,
+                path: None,
+                name: None,
+                error: Some(e),
+            },
+        }
+    })
+    .await
+}
+
+// Adds the studio's .gitignore to an existing mod. An existing .gitignore
+// is never overwritten; `written` is false when one was already there
+#[tauri::command]
+async fn write_gitignore(app: AppHandle, mod_root: String) -> timing::Timed<GitignoreResult> {
+    timing::measure(app, "write_gitignore", async move {
+        let path = Path::new(&mod_root).join(GITIGNORE_FILE_NAME);
+        let path_str = path.to_string_lossy().to_string();
+        let result = fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&path)
+            .and_then(|mut file| file.write_all(GITIGNORE_TEMPLATE.as_bytes()));
+        match result {
+            Ok(()) => GitignoreResult {
+                success: true,
+                path: Some(path_str),
+                written: Some(true),
+                error: None,
+            },
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => GitignoreResult {
+                success: true,
+                path: Some(path_str),
+                written: Some(false),
+                error: None,
+            },
+            Err(e) => GitignoreResult {
+                success: false,
+                path: None,
+                written: None,
+                error: Some(format!("Failed to write {}: {}", path_str, e)),
+            },
+        }
+    })
+    .await
+}
+
+#[tauri::command]
+async fn format_script(
+    app: AppHandle,
+    content: String,
+    use_tabs: Option<bool>,
+    indent_size: Option<usize>,
+) -> timing::Timed<FormatScriptResult> {
+    timing::measure(app, "format_script", async move {
+        let indent_unit = if use_tabs.unwrap_or(true) {
+            "\t".to_string()
+        } else {
+            " ".repeat(indent_size.unwrap_or(4))
+        };
+
+        let formatted = squirrel::format_source(&content, &indent_unit);
+        FormatScriptResult {
+            success: true,
+            changed: formatted != content,
+            content: formatted,
+        }
+    })
+    .await
+}
+
+// Quick bracket check for the editor buffer, cheap enough to run on every save
+#[tauri::command]
+async fn check_brace_balance(
+    app: AppHandle,
+    content: String,
+) -> timing::Timed<BraceBalanceResult> {
+    timing::measure(app, "check_brace_balance", async move {
+        let issue = squirrel::check_brace_balance(&content);
+        BraceBalanceResult {
+            success: true,
+            balanced: issue.is_none(),
+            issue,
+        }
+    })
+    .await
+}
+
+// Mod-relative key with forward slashes, so tags survive moving the mod itself
+fn mod_relative_key(mod_root: &Path, path: &Path) -> Result<String, String> {
+    let relative = path
+        .strip_prefix(mod_root)
+        .map_err(|_| format!("{} is not inside the mod", path.display()))?;
+    Ok(relative
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/"))
+}
+
+fn load_file_tags(mod_root: &Path) -> Result<BTreeMap<String, Vec<String>>, String> {
+    match fs::read_to_string(mod_root.join(TAGS_FILE_NAME)) {
+        Ok(content) => serde_json::from_str(&content)
+            .map_err(|e| format!("Invalid {}: {}", TAGS_FILE_NAME, e)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(BTreeMap::new()),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+fn save_file_tags(mod_root: &Path, tags: &BTreeMap<String, Vec<String>>) -> Result<(), String> {
+    let content = serde_json::to_string_pretty(tags).map_err(|e| e.to_string())?;
+    write_atomic(&mod_root.join(TAGS_FILE_NAME), content.as_bytes()).map_err(|e| e.to_string())
+}
+
+// Rewrites the tag keys of a moved file, or of everything under a moved folder
+fn move_file_tags(mod_root: &Path, from: &Path, to: &Path) -> Result<(), String> {
+    let mut tags = load_file_tags(mod_root)?;
+    let from_key = mod_relative_key(mod_root, from)?;
+    let to_key = mod_relative_key(mod_root, to)?;
+    let folder_prefix = format!("{}/", from_key);
+
+    let moved: Vec<String> = tags
+        .keys()
+        .filter(|key| **key == from_key || key.starts_with(&folder_prefix))
+        .cloned()
+        .collect();
+    if moved.is_empty() {
+        return Ok(());
+    }
+
+    for key in moved {
+        if let Some(file_tags) = tags.remove(&key) {
+            let new_key = format!("{}{}", to_key, &key[from_key.len()..]);
+            tags.insert(new_key, file_tags);
+        }
+    }
+    save_file_tags(mod_root, &tags)
+}
+
+#[tauri::command]
+async fn set_file_tags(
+    app: AppHandle,
+    mod_root: String,
+    file_path: String,
+    tags: Vec<String>,
+) -> timing::Timed<WriteFileResult> {
+    timing::measure(app, "set_file_tags", async move {
+        let result = run_blocking(None, move || {
+            let root = Path::new(&mod_root);
+            mod_relative_key(root, Path::new(&file_path)).and_then(|key| {
+                let mut all_tags = load_file_tags(root)?;
+
+                let mut file_tags: Vec<String> = tags
+                    .iter()
+                    .map(|t| t.trim().to_string())
+                    .filter(|t| !t.is_empty())
+                    .collect();
+                file_tags.sort();
+                file_tags.dedup();
+
+                if file_tags.is_empty() {
+                    all_tags.remove(&key);
+                } else {
+                    all_tags.insert(key, file_tags);
+                }
+                save_file_tags(root, &all_tags)
+            })
+        })
+        .await;
+
+        match result.and_then(|r| r) {
+            Ok(_) => WriteFileResult {
+                success: true,
+                error: None,
+            },
+            Err(e) => WriteFileResult {
+                success: false,
+                error: Some(e),
+            },
+        }
+    })
+    .await
+}
+
+#[tauri::command]
+async fn get_files_by_tag(
+    app: AppHandle,
+    mod_root: String,
+    tag: Option<String>,
+) -> timing::Timed<FilesByTagResult> {
+    timing::measure(app, "get_files_by_tag", async move {
+        let result = run_blocking(None, move || {
+            let root = Path::new(&mod_root);
+            let all_tags = load_file_tags(root)?;
+
+            let mut groups: BTreeMap<String, Vec<String>> = BTreeMap::new();
+            for (key, file_tags) in &all_tags {
+                let file_path = root.join(key).to_string_lossy().to_string();
+                for file_tag in file_tags {
+                    if tag.as_ref().is_some_and(|t| t != file_tag) {
+                        continue;
+                    }
+                    groups.entry(file_tag.clone()).or_default().push(file_path.clone());
+                }
+            }
+            Ok(groups)
+        })
+        .await;
+
+        match result.and_then(|r| r) {
+            Ok(groups) => FilesByTagResult {
+                success: true,
+                groups: Some(groups),
+                error: None,
+            },
+            Err(e) => FilesByTagResult {
+                success: false,
+                groups: None,
+                error: Some(e),
+            },
+        }
+    })
+    .await
+}
+
+// Drops tags of files that no longer exist on disk
+#[tauri::command]
+async fn prune_file_tags(app: AppHandle, mod_root: String) -> timing::Timed<PruneTagsResult> {
+    timing::measure(app, "prune_file_tags", async move {
+        let result = run_blocking(None, move || {
+            let root = Path::new(&mod_root);
+            load_file_tags(root).and_then(|mut all_tags| {
+                let removed: Vec<String> = all_tags
+                    .keys()
+                    .filter(|key| !root.join(key).exists())
+                    .cloned()
+                    .collect();
+                if !removed.is_empty() {
+                    all_tags.retain(|key, _| !removed.contains(key));
+                    save_file_tags(root, &all_tags)?;
+                }
+                Ok(removed)
+            })
+        })
+        .await;
+
+        match result.and_then(|r| r) {
+            Ok(removed) => PruneTagsResult {
+                success: true,
+                removed: Some(removed),
+                error: None,
+            },
+            Err(e) => PruneTagsResult {
+                success: false,
+                removed: None,
+                error: Some(e),
+            },
+        }
+    })
+    .await
+}
+
+fn settings_path(app: &AppHandle) -> Result<PathBuf, String> {
+    app.path()
+        .app_config_dir()
+        .map(|dir| dir.join(SETTINGS_FILE_NAME))
+        .map_err(|e| format!("Failed to resolve config directory: {}", e))
+}
+
+fn load_settings(app: &AppHandle) -> Result<AppSettings, String> {
+    match fs::read_to_string(settings_path(app)?) {
+        Ok(content) => {
+            serde_json::from_str(&content).map_err(|e| format!("Invalid settings file: {}", e))
+        }
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(AppSettings::default()),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+fn save_settings(app: &AppHandle, settings: &AppSettings) -> Result<(), String> {
+    let path = settings_path(app)?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let content = serde_json::to_string_pretty(settings).map_err(|e| e.to_string())?;
+    fs::write(path, content).map_err(|e| e.to_string())
+}
+
+fn app_dirs(app: &AppHandle) -> Result<(PathBuf, PathBuf), String> {
+    let config_dir = app
+        .path()
+        .app_config_dir()
+        .map_err(|e| format!("Failed to resolve config directory: {}", e))?;
+    let data_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to resolve data directory: {}", e))?;
+    Ok((config_dir, data_dir))
+}
+
+// Where the app keeps its files on this platform, for support and debugging.
+// The folders may not exist yet if nothing has been saved.
+#[tauri::command]
+async fn get_data_dir(app: AppHandle) -> timing::Timed<DataDirResult> {
+    timing::measure(app.clone(), "get_data_dir", async move {
+        match app_dirs(&app) {
+            Ok((config_dir, data_dir)) => DataDirResult {
+                success: true,
+                config_dir: Some(config_dir.to_string_lossy().to_string()),
+                data_dir: Some(data_dir.to_string_lossy().to_string()),
+                error: None,
+            },
+            Err(e) => DataDirResult {
+                success: false,
+                config_dir: None,
+                data_dir: None,
+                error: Some(e),
+            },
+        }
+    })
+    .await
+}
+
+// Reveals the config directory (or the data directory, with kind "data") in
+// the file manager, creating it first so there is something to show
+#[tauri::command]
+async fn open_data_dir(app: AppHandle, kind: Option<String>) -> timing::Timed<WriteFileResult> {
+    timing::measure(app.clone(), "open_data_dir", async move {
+        let result = app_dirs(&app).and_then(|(config_dir, data_dir)| {
+            let dir = match kind.as_deref() {
+                None | Some("config") => config_dir,
+                Some("data") => data_dir,
+                Some(other) => return Err(format!("Unknown directory kind: {}", other)),
+            };
+            fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+            Ok(dir)
+        });
+        let result = match result {
+            Ok(dir) => run_blocking(None, move || external::open_with_default(&dir))
+                .await
+                .and_then(|r| r),
+            Err(e) => Err(e),
+        };
+        match result {
+            Ok(()) => WriteFileResult {
+                success: true,
+                error: None,
+            },
+            Err(e) => WriteFileResult {
+                success: false,
+                error: Some(e),
+            },
+        }
+    })
+    .await
+}
+
+fn session_path(app: &AppHandle) -> Result<PathBuf, String> {
+    app.path()
+        .app_data_dir()
+        .map(|dir| dir.join(SESSION_FILE_NAME))
+        .map_err(|e| format!("Failed to resolve data directory: {}", e))
+}
+
+// Drops what no longer exists on disk, returning a note for each
+fn prune_session(session: &mut SessionState) -> Vec<String> {
+    let mut pruned = Vec::new();
+    if let Some(root) = session.mod_root.take_if(|root| !Path::new(root).is_dir()) {
+        pruned.push(format!("Mod folder {} no longer exists", root));
+        // Node ids belonged to that mod's tree
+        session.expanded_nodes.clear();
+    }
+    session.open_files.retain(|file| {
+        let exists = Path::new(file).is_file();
+        if !exists {
+            pruned.push(format!("Closed {}, which no longer exists", file));
+        }
+        exists
+    });
+    let open_files = &session.open_files;
+    session.active_file.take_if(|active| !open_files.contains(active));
+    pruned
+}
+
+#[tauri::command]
+async fn save_session(app: AppHandle, session: SessionState) -> timing::Timed<WriteFileResult> {
+    timing::measure(app.clone(), "save_session", async move {
+        let result = session_path(&app).and_then(|path| {
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+            }
+            let content = serde_json::to_string_pretty(&session).map_err(|e| e.to_string())?;
+            write_atomic(&path, content.as_bytes()).map_err(|e| e.to_string())
+        });
+        match result {
+            Ok(()) => WriteFileResult {
+                success: true,
+                error: None,
+            },
+            Err(e) => WriteFileResult {
+                success: false,
+                error: Some(e),
+            },
+        }
+    })
+    .await
+}
+
+// Restores the last saved session, pruned of files and folders that have
+// since disappeared. No saved session gives an empty one.
+#[tauri::command]
+async fn load_session(app: AppHandle) -> timing::Timed<LoadSessionResult> {
+    timing::measure(app.clone(), "load_session", async move {
+        let loaded = session_path(&app).and_then(|path| match fs::read_to_string(path) {
+            Ok(content) => serde_json::from_str::<SessionState>(&content)
+                .map_err(|e| format!("Invalid session file: {}", e)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(SessionState::default()),
+            Err(e) => Err(e.to_string()),
+        });
+        match loaded {
+            Ok(mut session) => {
+                let pruned = prune_session(&mut session);
+                LoadSessionResult {
+                    success: true,
+                    session: Some(session),
+                    pruned: if pruned.is_empty() { None } else { Some(pruned) },
+                    error: None,
+                }
+            }
+            Err(e) => LoadSessionResult {
+                success: false,
+                session: None,
+                pruned: None,
+                error: Some(e),
+            },
+        }
+    })
+    .await
+}
+
+fn mod_directories_result(result: Result<Vec<String>, String>) -> ModDirectoriesResult {
+    match result {
+        Ok(directories) => ModDirectoriesResult {
+            success: true,
+            directories: Some(
+                directories
+                    .into_iter()
+                    .map(|path| ModDirectoryInfo {
+                        exists: Path::new(&path).is_dir(),
+                        path,
+                    })
+                    .collect(),
+            ),
+            error: None,
+        },
+        Err(e) => ModDirectoriesResult {
+            success: false,
+            directories: None,
+            error: Some(e),
+        },
+    }
+}
+
+#[tauri::command]
+async fn get_mod_directories(app: AppHandle) -> timing::Timed<ModDirectoriesResult> {
+    timing::measure(app.clone(), "get_mod_directories", async move {
+        mod_directories_result(load_settings(&app).map(|settings| settings.mod_directories))
+    })
+    .await
+}
+
+#[tauri::command]
+async fn add_mod_directory(
+    app: AppHandle,
+    dir_path: String,
+) -> timing::Timed<ModDirectoriesResult> {
+    timing::measure(app.clone(), "add_mod_directory", async move {
+        mod_directories_result(load_settings(&app).and_then(|mut settings| {
+            if !settings.mod_directories.contains(&dir_path) {
+                settings.mod_directories.push(dir_path);
+                save_settings(&app, &settings)?;
+            }
+            Ok(settings.mod_directories)
+        }))
+    })
+    .await
+}
+
+#[tauri::command]
+async fn remove_mod_directory(
+    app: AppHandle,
+    dir_path: String,
+) -> timing::Timed<ModDirectoriesResult> {
+    timing::measure(app.clone(), "remove_mod_directory", async move {
+        mod_directories_result(load_settings(&app).and_then(|mut settings| {
+            settings.mod_directories.retain(|dir| *dir != dir_path);
+            save_settings(&app, &settings)?;
+            Ok(settings.mod_directories)
+        }))
+    })
+    .await
+}
+
+// Lists the mods (folders containing a mod.vdf) directly inside a mods root
+fn scan_mod_root(root: &str) -> ModRootScan {
+    let root_path = Path::new(root);
+    let mut scan = ModRootScan {
+        root: root.to_string(),
+        exists: root_path.is_dir(),
+        mods: Vec::new(),
+        error: None,
+    };
+    if !scan.exists {
+        return scan;
+    }
+
+    let entries = match fs::read_dir(root_path) {
+        Ok(entries) => entries,
+        Err(e) => {
+            scan.error = Some(e.to_string());
+            return scan;
+        }
+    };
+
+    for entry in entries.flatten() {
+        let mod_path = entry.path();
+        if !mod_path.join("mod.vdf").is_file() {
+            continue;
+        }
+
+        let folder_name = entry.file_name().to_string_lossy().to_string();
+        let manifest: serde_json::Value = fs::read_to_string(mod_path.join("manifest.json"))
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+        let field = |key: &str| manifest.get(key).and_then(|v| v.as_str()).map(str::to_string);
+
+        scan.mods.push(ModSummary {
+            name: field("name").unwrap_or_else(|| folder_name.clone()),
+            mod_id: field("modId").unwrap_or_else(|| folder_name.clone()),
+            version: field("version").unwrap_or_default(),
+            path: mod_path.to_string_lossy().to_string(),
+        });
+    }
+
+    scan.mods.sort_by_key(|m| m.name.to_lowercase());
+    scan
+}
+
+#[tauri::command]
+async fn export_manifest_markdown(
+    app: AppHandle,
+    mod_root: String,
+    output_path: String,
+) -> timing::Timed<ExportMarkdownResult> {
+    timing::measure(app, "export_manifest_markdown", async move {
+        let root = Path::new(&mod_root);
+        let manifest = match manifest::load(root) {
+            Ok(manifest) => manifest,
+            Err(e) => {
+                return ExportMarkdownResult {
+                    success: false,
+                    content: None,
+                    path: None,
+                    error: Some(e),
+                }
+            }
+        };
+
+        let content = manifest::to_markdown(&manifest, manifest::load_mod_vdf(root).as_ref());
+        if let Err(e) = fs::write(&output_path, &content) {
+            return ExportMarkdownResult {
+                success: false,
+                content: Some(content),
+                path: None,
+                error: Some(format!("Failed to write {}: {}", output_path, e)),
+            };
+        }
+
+        ExportMarkdownResult {
+            success: true,
+            content: Some(content),
+            path: Some(output_path),
+            error: None,
+        }
+    })
+    .await
+}
+
+fn reconcile_manifest_blocking(
+    mod_root: &Path,
+    auto_fix: bool,
+) -> Result<(manifest::ScriptDrift, bool), String> {
+    let mut manifest = manifest::load(mod_root)?;
+    let on_disk = manifest::script_files_on_disk(mod_root);
+    let drift = manifest::script_drift(&manifest, &on_disk);
+
+    let needs_fix = !drift.unlisted.is_empty() || !drift.missing.is_empty();
+    if !auto_fix || !needs_fix {
+        return Ok((drift, false));
+    }
+
+    manifest::apply_script_drift(&mut manifest, &drift)?;
+    let content = serde_json::to_string_pretty(&manifest).map_err(|e| e.to_string())?;
+    write_atomic(&mod_root.join(manifest::MANIFEST_FILE_NAME), content.as_bytes())
+        .map_err(|e| format!("Failed to write manifest.json: {}", e))?;
+    Ok((drift, true))
+}
+
+// Reports scripts that drifted between disk and manifest.json, optionally
+// rewriting the manifest to match the disk
+#[tauri::command]
+async fn reconcile_manifest(
+    app: AppHandle,
+    mod_root: String,
+    auto_fix: Option<bool>,
+) -> timing::Timed<ReconcileManifestResult> {
+    timing::measure(app, "reconcile_manifest", async move {
+        let root = PathBuf::from(mod_root);
+        let auto_fix = auto_fix.unwrap_or(false);
+        let result = run_blocking(None, move || reconcile_manifest_blocking(&root, auto_fix)).await;
+        match result.and_then(|r| r) {
+            Ok((drift, fixed)) => ReconcileManifestResult {
+                success: true,
+                drift: Some(drift),
+                fixed: Some(fixed),
+                error: None,
+            },
+            Err(e) => ReconcileManifestResult {
+                success: false,
+                drift: None,
+                fixed: None,
+                error: Some(e),
+            },
+        }
+    })
+    .await
+}
+
+// Exactly what the manifest declares, resolved to absolute paths: the input
+// for external packaging tools, as opposed to everything in the folder
+#[tauri::command]
+async fn collect_manifest_files(
+    app: AppHandle,
+    mod_root: String,
+) -> timing::Timed<ManifestFilesResult> {
+    timing::measure(app, "collect_manifest_files", async move {
+        let root = PathBuf::from(mod_root);
+        let result = run_blocking(None, move || {
+            manifest::load(&root).map(|manifest| manifest::declared_files(&manifest, &root))
+        })
+        .await;
+        match result.and_then(|r| r) {
+            Ok((files, missing)) => ManifestFilesResult {
+                success: true,
+                files: Some(files),
+                missing: Some(missing),
+                error: None,
+            },
+            Err(e) => ManifestFilesResult {
+                success: false,
+                files: None,
+                missing: None,
+                error: Some(e),
+            },
+        }
+    })
+    .await
+}
+
+fn validate_paks_blocking(mod_root: &Path) -> Result<Vec<paks::PakWarning>, String> {
+    // Without a manifest there is nothing to compare the paks with
+    let manifest = if mod_root.join(manifest::MANIFEST_FILE_NAME).is_file() {
+        Some(manifest::load(mod_root)?)
+    } else {
+        None
+    };
+    paks::validate(mod_root, manifest.as_ref())
+}
+
+// Checks pak naming, starpaks without an rpak, and the manifest's rpaks list
+// against paks/ on disk
+#[tauri::command]
+async fn validate_paks(app: AppHandle, mod_root: String) -> timing::Timed<ValidatePaksResult> {
+    timing::measure(app, "validate_paks", async move {
+        let root = PathBuf::from(mod_root);
+        match run_blocking(None, move || validate_paks_blocking(&root)).await.and_then(|r| r) {
+            Ok(warnings) => ValidatePaksResult {
+                success: true,
+                warnings: Some(warnings),
+                error: None,
+            },
+            Err(e) => ValidatePaksResult {
+                success: false,
+                warnings: None,
+                error: Some(e),
+            },
+        }
+    })
+    .await
+}
+
+// Returns the duplicate groups, the number of files and whether the export
+// was cancelled before the archive was finished
+fn export_mod_zip_blocking(
+    app: &AppHandle,
+    mod_root: &Path,
+    output: &Path,
+    report_only: bool,
+    check_space: bool,
+    operation_id: Option<String>,
+    token: &operations::CancelToken,
+) -> Result<(Vec<export::DuplicateGroup>, usize, bool), String> {
+    let mut files = export::collect_files(mod_root)
+        .map_err(|e| format!("Failed to read {}: {}", mod_root.display(), e))?;
+    // An archive saved inside the mod must not end up containing itself,
+    // nor a leftover from an interrupted export
+    let skipped = [
+        paths::canonicalize_lenient(output),
+        paths::canonicalize_lenient(&export::part_path(output)),
+    ];
+    files.retain(|f| !skipped.contains(&paths::canonicalize_lenient(&f.path)));
+
+    let duplicates = export::find_duplicates(&files).map_err(|e| e.to_string())?;
+    let mut cancelled = false;
+    if !report_only {
+        // Uncompressed size: an upper bound, since entries are only ever
+        // deflated or stored
+        let total_bytes = files.iter().map(|f| f.size).sum();
+        if check_space {
+            disk::ensure_space(output, total_bytes)?;
+        }
+        let folder_name = mod_root
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| "mod".to_string());
+        let total_files = files.len();
+        let mut progress = |files_done, bytes_written| {
+            let _ = app.emit(
+                EXPORT_PROGRESS_EVENT,
+                ExportProgress {
+                    operation_id: operation_id.clone(),
+                    files_done,
+                    total_files,
+                    bytes_written,
+                    total_bytes,
+                },
+            );
+            if token.is_cancelled() {
+                ControlFlow::Break(())
+            } else {
+                ControlFlow::Continue(())
+            }
+        };
+        cancelled = !export::write_zip(&files, Some(&folder_name), output, &mut progress)?;
+    }
+    Ok((duplicates, files.len(), cancelled))
+}
+
+// Zips a mod for release. Byte-identical files are always reported; with
+// report_only nothing is written. Progress is emitted as export-progress
+// events, and passing an operation_id makes the export cancellable via
+// cancel_operation
+#[tauri::command]
+async fn export_mod_zip(
+    app: AppHandle,
+    mod_root: String,
+    output_path: String,
+    report_only: Option<bool>,
+    check_space: Option<bool>,
+    operation_id: Option<String>,
+) -> timing::Timed<ExportModZipResult> {
+    timing::measure(app.clone(), "export_mod_zip", async move {
+        let report_only = report_only.unwrap_or(false);
+        let check_space = check_space.unwrap_or(false);
+        let root = PathBuf::from(&mod_root);
+        let output = PathBuf::from(&output_path);
+
+        let operations = app.state::<operations::Operations>();
+        let token = match &operation_id {
+            Some(id) => operations.start(id),
+            None => operations::CancelToken::default(),
+        };
+        let handle = app.clone();
+        let id = operation_id.clone();
+        let result = run_blocking(None, move || {
+            export_mod_zip_blocking(
+                &handle,
+                &root,
+                &output,
+                report_only,
+                check_space,
+                id,
+                &token,
+            )
+        })
+        .await;
+        if let Some(id) = &operation_id {
+            operations.finish(id);
+        }
+
+        match result.and_then(|r| r) {
+            Ok((duplicates, file_count, cancelled)) => ExportModZipResult {
+                success: true,
+                path: if report_only || cancelled {
+                    None
+                } else {
+                    Some(output_path)
+                },
+                file_count: Some(file_count),
+                duplicates: Some(duplicates),
+                cancelled: Some(cancelled),
+                error: None,
+            },
+            Err(e) => ExportModZipResult {
+                success: false,
+                path: None,
+                file_count: None,
+                duplicates: None,
+                cancelled: None,
+                error: Some(e),
+            },
+        }
+    })
+    .await
+}
+
+// Zips just the files and folders in `selection`, stored by their paths
+// relative to `base`, e.g. a mod's scripts folder to share on its own
+#[tauri::command]
+async fn export_paths_zip(
+    app: AppHandle,
+    base: String,
+    selection: Vec<String>,
+    output_path: String,
+) -> timing::Timed<ExportPathsZipResult> {
+    timing::measure(app, "export_paths_zip", async move {
+        let output = PathBuf::from(&output_path);
+        let result = run_blocking(None, move || {
+            let mut files = export::collect_selection(Path::new(&base), &selection)?;
+            if files.is_empty() {
+                return Err("Nothing to export".to_string());
+            }
+            // Never archive the archive itself
+            let skipped = [
+                paths::canonicalize_lenient(&output),
+                paths::canonicalize_lenient(&export::part_path(&output)),
+            ];
+            files.retain(|f| !skipped.contains(&paths::canonicalize_lenient(&f.path)));
+            export::write_zip(&files, None, &output, &mut |_, _| ControlFlow::Continue(()))?;
+            let archive_size = fs::metadata(&output).map_err(|e| e.to_string())?.len();
+            Ok((files.len(), archive_size))
+        })
+        .await;
+        match result.and_then(|r| r) {
+            Ok((file_count, archive_size)) => ExportPathsZipResult {
+                success: true,
+                path: Some(output_path),
+                file_count: Some(file_count),
+                archive_size: Some(archive_size),
+                error: None,
+            },
+            Err(e) => ExportPathsZipResult {
+                success: false,
+                path: None,
+                file_count: None,
+                archive_size: None,
+                error: Some(e),
+            },
+        }
+    })
+    .await
+}
+
+fn mod_backup_result(result: Result<backups::ModBackup, String>) -> ModBackupResult {
+    match result {
+        Ok(backup) => ModBackupResult {
+            success: true,
+            backup: Some(backup),
+            error: None,
+        },
+        Err(e) => ModBackupResult {
+            success: false,
+            backup: None,
+            error: Some(e),
+        },
+    }
+}
+
+fn mod_backups_result(result: Result<Vec<backups::ModBackup>, String>) -> ModBackupsResult {
+    match result {
+        Ok(backups) => ModBackupsResult {
+            success: true,
+            backups: Some(backups),
+            error: None,
+        },
+        Err(e) => ModBackupsResult {
+            success: false,
+            backups: None,
+            error: Some(e),
+        },
+    }
+}
+
+// Snapshots the whole mod into .r5vbackups/ before a batch operation, so it
+// can be rolled back with restore_mod_backup. Older backups are pruned by
+// `retention` (keepLast, keepDays)
+#[tauri::command]
+async fn backup_mod(
+    app: AppHandle,
+    mod_root: String,
+    retention: Option<versions::VersionRetention>,
+) -> timing::Timed<ModBackupResult> {
+    timing::measure(app, "backup_mod", async move {
+        let retention = retention.unwrap_or_default();
+        let result =
+            run_blocking(None, move || backups::create(Path::new(&mod_root), &retention)).await;
+        mod_backup_result(result.and_then(|r| r))
+    })
+    .await
+}
+
+// Rolls the mod back to a backup. The state being replaced is backed up
+// first and returned, so the restore can be undone the same way; a failed
+// restore leaves the mod as it was and names that backup in the error
+#[tauri::command]
+async fn restore_mod_backup(
+    app: AppHandle,
+    mod_root: String,
+    timestamp: u64,
+) -> timing::Timed<ModBackupResult> {
+    timing::measure(app, "restore_mod_backup", async move {
+        let result =
+            run_blocking(None, move || backups::restore(Path::new(&mod_root), timestamp)).await;
+        mod_backup_result(result.and_then(|r| r))
+    })
+    .await
+}
+
+#[tauri::command]
+async fn list_mod_backups(app: AppHandle, mod_root: String) -> timing::Timed<ModBackupsResult> {
+    timing::measure(app, "list_mod_backups", async move {
+        let result = run_blocking(None, move || backups::list(Path::new(&mod_root))).await;
+        mod_backups_result(result.and_then(|r| r))
+    })
+    .await
+}
+
+// Applies `retention` to the existing backups without making a new one
+#[tauri::command]
+async fn prune_mod_backups(
+    app: AppHandle,
+    mod_root: String,
+    retention: versions::VersionRetention,
+) -> timing::Timed<ModBackupsResult> {
+    timing::measure(app, "prune_mod_backups", async move {
+        let result =
+            run_blocking(None, move || backups::prune(Path::new(&mod_root), &retention)).await;
+        mod_backups_result(result.and_then(|r| r))
+    })
+    .await
+}
+
+// Files under the mod that neither the manifest nor any include leads to,
+// for cleanup. `entry_points` names further files the game loads directly,
+// as .r5vignore patterns
+#[tauri::command]
+async fn find_orphans(
+    app: AppHandle,
+    mod_root: String,
+    entry_points: Option<Vec<String>>,
+) -> timing::Timed<FindOrphansResult> {
+    timing::measure(app, "find_orphans", async move {
+        let result = run_blocking(None, move || {
+            let root = Path::new(&mod_root);
+            // A manifest that doesn't parse would make every script look
+            // unused, so that is an error rather than no manifest
+            let manifest = if root.join(manifest::MANIFEST_FILE_NAME).is_file() {
+                Some(manifest::load(root)?)
+            } else {
+                None
+            };
+            orphans::find(root, manifest.as_ref(), &entry_points.unwrap_or_default())
+                .map_err(|e| format!("Failed to read {}: {}", mod_root, e))
+        })
+        .await;
+        match result.and_then(|r| r) {
+            Ok(orphans) => FindOrphansResult {
+                success: true,
+                total_size: Some(orphans.iter().map(|o| o.size).sum()),
+                orphans: Some(orphans),
+                error: None,
+            },
+            Err(e) => FindOrphansResult {
+                success: false,
+                orphans: None,
+                total_size: None,
+                error: Some(e),
+            },
+        }
+    })
+    .await
+}
+
+// The order the mod's scripts load in, from the manifest's script list and
+// the includes between scripts, for debugging load problems. Include cycles
+// fail the check but still come with an order
+#[tauri::command]
+async fn compute_load_order(app: AppHandle, mod_root: String) -> timing::Timed<LoadOrderResult> {
+    timing::measure(app, "compute_load_order", async move {
+        let result = run_blocking(None, move || {
+            let root = Path::new(&mod_root);
+            let manifest = manifest::load(root)?;
+            load_order::compute(root, &manifest)
+                .map_err(|e| format!("Failed to read {}: {}", mod_root, e))
+        })
+        .await;
+        match result.and_then(|r| r) {
+            Ok(load_order) => {
+                let cycles = load_order.cycles;
+                LoadOrderResult {
+                    success: cycles.is_empty(),
+                    order: Some(load_order.order),
+                    unreachable: Some(load_order.unreachable),
+                    error: (!cycles.is_empty())
+                        .then(|| format!("{} include cycle(s) found", cycles.len())),
+                    cycles: if cycles.is_empty() { None } else { Some(cycles) },
+                }
+            }
+            Err(e) => LoadOrderResult {
+                success: false,
+                order: None,
+                unreachable: None,
+                cycles: None,
+                error: Some(e),
+            },
+        }
+    })
+    .await
+}
+
+// Checks mod.vdf and the manifest against what `game_version` expects,
+// reporting each problem under the id of the rule that found it
+#[tauri::command]
+async fn validate_for_version(
+    app: AppHandle,
+    mod_root: String,
+    game_version: String,
+) -> timing::Timed<ValidateForVersionResult> {
+    timing::measure(app, "validate_for_version", async move {
+        let result = run_blocking(None, move || {
+            compat::validate(Path::new(&mod_root), &game_version)
+        })
+        .await;
+        match result.and_then(|r| r) {
+            Ok(findings) => ValidateForVersionResult {
+                success: true,
+                loadable: Some(
+                    !findings
+                        .iter()
+                        .any(|f| matches!(f.severity, compat::Severity::Error)),
+                ),
+                findings: Some(findings),
+                error: None,
+            },
+            Err(e) => ValidateForVersionResult {
+                success: false,
+                findings: None,
+                loadable: None,
+                error: Some(e),
+            },
+        }
+    })
+    .await
+}
+
+// Traces the images, font files and #base includes the mod's .res/.menu UI
+// files reference, so missing assets show up before the game complains
+#[tauri::command]
+async fn scan_ui_resources(
+    app: AppHandle,
+    mod_root: String,
+) -> timing::Timed<ScanUiResourcesResult> {
+    timing::measure(app, "scan_ui_resources", async move {
+        let result = run_blocking(None, move || {
+            ui_resources::scan(Path::new(&mod_root))
+                .map_err(|e| format!("Failed to read {}: {}", mod_root, e))
+        })
+        .await;
+        match result.and_then(|r| r) {
+            Ok(scan) => ScanUiResourcesResult {
+                success: true,
+                found: Some(scan.found),
+                missing: Some(scan.missing),
+                failed: if scan.failed.is_empty() {
+                    None
+                } else {
+                    Some(scan.failed)
+                },
+                error: None,
+            },
+            Err(e) => ScanUiResourcesResult {
+                success: false,
+                found: None,
+                missing: None,
+                failed: None,
+                error: Some(e),
+            },
+        }
+    })
+    .await
+}
+
+fn metadata_differences(root_a: &Path, root_b: &Path) -> Vec<MetadataDifference> {
+    let a = read_mod_data(root_a);
+    let b = read_mod_data(root_b);
+    let pair = |get: fn(&ModData) -> String| (a.as_ref().map(get), b.as_ref().map(get));
+    [
+        ("version", pair(|m| m.version.clone())),
+        ("modId", pair(|m| m.mod_id.clone())),
+    ]
+    .into_iter()
+    .filter(|(_, (a, b))| a != b)
+    .map(|(field, (a, b))| MetadataDifference {
+        field: field.to_string(),
+        a,
+        b,
+    })
+    .collect()
+}
+
+// A single hash of everything the mod ships, for "changed since last
+// publish?" checks and spotting duplicate mods. Unchanged files are not
+// re-read on later calls
+#[tauri::command]
+async fn mod_content_hash(app: AppHandle, mod_root: String) -> timing::Timed<ModContentHashResult> {
+    timing::measure(app.clone(), "mod_content_hash", async move {
+        let handle = app.clone();
+        let result = run_blocking(None, move || {
+            handle
+                .state::<content_hash::HashCache>()
+                .mod_hash(Path::new(&mod_root))
+                .map_err(|e| format!("Failed to hash {}: {}", mod_root, e))
+        })
+        .await;
+        match result.and_then(|r| r) {
+            Ok(content) => ModContentHashResult {
+                success: true,
+                content: Some(content),
+                error: None,
+            },
+            Err(e) => ModContentHashResult {
+                success: false,
+                content: None,
+                error: Some(e),
+            },
+        }
+    })
+    .await
+}
+
+// Differences between two mods, such as a fork and its upstream: files only
+// in one of them, files whose content differs, and version/modId changes.
+// Each mod's .r5vignore is honoured for its own files
+#[tauri::command]
+async fn compare_mods(
+    app: AppHandle,
+    root_a: String,
+    root_b: String,
+) -> timing::Timed<CompareModsResult> {
+    timing::measure(app, "compare_mods", async move {
+        let a = PathBuf::from(root_a);
+        let b = PathBuf::from(root_b);
+        let result = run_blocking(None, move || {
+            compare::compare(&a, &b)
+                .map(|files| (files, metadata_differences(&a, &b)))
+                .map_err(|e| e.to_string())
+        })
+        .await;
+        match result.and_then(|r| r) {
+            Ok((files, metadata)) => CompareModsResult {
+                success: true,
+                files: Some(files),
+                metadata: Some(metadata),
+                error: None,
+            },
+            Err(e) => CompareModsResult {
+                success: false,
+                files: None,
+                metadata: None,
+                error: Some(e),
+            },
+        }
+    })
+    .await
+}
+
+// Replaced installs are kept here, out of the game's mods folder
+const INSTALL_BACKUP_DIR_NAME: &str = "install-backups";
+
+fn install_backup_root(app: &AppHandle) -> Result<PathBuf, String> {
+    app_dirs(app).map(|(_, data_dir)| data_dir.join(INSTALL_BACKUP_DIR_NAME))
+}
+
+// Installs a mod into the game's mods folder as <mods_dir>/<modId>, replacing
+// an existing install of the same mod after backing it up. Mode "symlink"
+// links to the mod instead of copying it, so edits are live in game; an error
+// starting with "PrivilegeRequired:" means Windows wouldn't allow the link
+#[tauri::command]
+async fn install_mod(
+    app: AppHandle,
+    mod_root: String,
+    mods_dir: String,
+    mode: Option<install::InstallMode>,
+) -> timing::Timed<InstallModResult> {
+    timing::measure(app.clone(), "install_mod", async move {
+        let failed = |error: String| InstallModResult {
+            success: false,
+            path: None,
+            backup_path: None,
+            link_kind: None,
+            error: Some(error),
+        };
+        let backup_root = match install_backup_root(&app) {
+            Ok(root) => root,
+            Err(e) => return failed(e),
+        };
+        let source = PathBuf::from(&mod_root);
+        let Some(mod_data) = read_mod_data(&source) else {
+            return failed(format!("{} is not a mod: no readable mod.vdf", mod_root));
+        };
+        let mods_dir = PathBuf::from(mods_dir);
+        let result = run_blocking(None, move || {
+            let mode = mode.unwrap_or_default();
+            install::install(&source, &mods_dir, &mod_data.mod_id, mode, &backup_root)
+        })
+        .await;
+        match result.and_then(|r| r) {
+            Ok(installed) => InstallModResult {
+                success: true,
+                path: Some(installed.path.to_string_lossy().to_string()),
+                backup_path: installed.backup.map(|p| p.to_string_lossy().to_string()),
+                link_kind: installed.link_kind.map(str::to_string),
+                error: None,
+            },
+            Err(e) => failed(e),
+        }
+    })
+    .await
+}
+
+// Removes <mods_dir>/<modId>. A linked install only loses the link, never the
+// folder it points to; a copied install is moved to the install backups
+#[tauri::command]
+async fn uninstall_mod(
+    app: AppHandle,
+    mods_dir: String,
+    mod_id: String,
+) -> timing::Timed<UninstallModResult> {
+    timing::measure(app.clone(), "uninstall_mod", async move {
+        let result = match install_backup_root(&app) {
+            Ok(backup_root) => {
+                let mods_dir = PathBuf::from(mods_dir);
+                run_blocking(None, move || {
+                    install::uninstall(&mods_dir, &mod_id, &backup_root)
+                })
+                .await
+                .and_then(|r| r)
+            }
+            Err(e) => Err(e),
+        };
+        match result {
+            Ok(uninstalled) => UninstallModResult {
+                success: true,
+                was_link: Some(uninstalled.was_link),
+                backup_path: uninstalled.backup.map(|p| p.to_string_lossy().to_string()),
+                warning: uninstalled.warning,
+                error: None,
+            },
+            Err(e) => UninstallModResult {
+                success: false,
+                was_link: None,
+                backup_path: None,
+                warning: None,
+                error: Some(e),
+            },
+        }
+    })
+    .await
+}
+
+// Resets a folder to files 0644 and folders 0755, so a mod shared between
+// systems is readable by whoever unpacks it. Nothing to do on Windows
+#[tauri::command]
+async fn normalize_permissions(
+    app: AppHandle,
+    root: String,
+    dry_run: Option<bool>,
+) -> timing::Timed<NormalizePermissionsResult> {
+    timing::measure(app, "normalize_permissions", async move {
+        let dry_run = dry_run.unwrap_or(false);
+        let path = PathBuf::from(&root);
+        let result = run_blocking(None, move || {
+            permissions::normalize(&path, dry_run)
+                .map_err(|e| format!("Failed to read {}: {}", root, e))
+        })
+        .await;
+        match result.and_then(|r| r) {
+            Ok(outcome) => NormalizePermissionsResult {
+                success: true,
+                changes: Some(outcome.changes),
+                failed: if outcome.failed.is_empty() { None } else { Some(outcome.failed) },
+                error: None,
+            },
+            Err(e) => NormalizePermissionsResult {
+                success: false,
+                changes: None,
+                failed: None,
+                error: Some(e),
+            },
+        }
+    })
+    .await
+}
+
+const UTF8_BOM: &[u8] = &[0xEF, 0xBB, 0xBF];
+
+// Text files under the mod that start with a UTF-8 BOM, sorted
+fn files_with_bom(mod_root: &Path) -> Result<Vec<(PathBuf, String)>, String> {
+    let files = paths::walk_files(mod_root).map_err(|e| e.to_string())?;
+    let mut found: Vec<(PathBuf, String)> = files
+        .into_iter()
+        .filter(|path| file_type::detect_file_type(path).is_text())
+        .filter(|path| {
+            let mut head = [0u8; 3];
+            fs::File::open(path)
+                .and_then(|mut file| file.read_exact(&mut head))
+                .is_ok_and(|_| head == UTF8_BOM)
+        })
+        .filter_map(|path| {
+            let relative = paths::relative_slash_path(mod_root, &path)?;
+            Some((path, relative))
+        })
+        .collect();
+    found.sort_by(|a, b| a.1.cmp(&b.1));
+    Ok(found)
+}
+
+#[tauri::command]
+async fn scan_boms(app: AppHandle, mod_root: String) -> timing::Timed<BomFilesResult> {
+    timing::measure(app, "scan_boms", async move {
+        let root = PathBuf::from(mod_root);
+        match run_blocking(None, move || files_with_bom(&root)).await.and_then(|r| r) {
+            Ok(found) => BomFilesResult {
+                success: true,
+                files: Some(found.into_iter().map(|(_, relative)| relative).collect()),
+                failed: None,
+                error: None,
+            },
+            Err(e) => BomFilesResult {
+                success: false,
+                files: None,
+                failed: None,
+                error: Some(e),
+            },
+        }
+    })
+    .await
+}
+
+fn strip_boms_blocking(mod_root: &Path, dry_run: bool) -> Result<BomFilesResult, String> {
+    let mut stripped = Vec::new();
+    let mut failed = Vec::new();
+    for (path, relative) in files_with_bom(mod_root)? {
+        if dry_run {
+            stripped.push(relative);
+            continue;
+        }
+        let result = fs::read(&path).and_then(|data| write_atomic(&path, &data[UTF8_BOM.len()..]));
+        match result {
+            Ok(()) => stripped.push(relative),
+            Err(e) => failed.push(format!("{}: {}", relative, e)),
+        }
+    }
+    Ok(BomFilesResult {
+        success: failed.is_empty(),
+        files: Some(stripped),
+        failed: if failed.is_empty() { None } else { Some(failed) },
+        error: None,
+    })
+}
+
+fn normalize_all_scripts_blocking(
+    mod_root: &Path,
+    options: &normalize::NormalizeOptions,
+    dry_run: bool,
+) -> Result<NormalizeScriptsResult, String> {
+    let mut scripts: Vec<(PathBuf, String)> = paths::walk_files(mod_root)
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .filter(|path| file_type::detect_file_type(path) == file_type::FileKind::Script)
+        .filter_map(|path| {
+            let relative = paths::relative_slash_path(mod_root, &path)?;
+            Some((path, relative))
+        })
+        .collect();
+    scripts.sort_by(|a, b| a.1.cmp(&b.1));
+
+    let mut files = Vec::new();
+    let mut failed = Vec::new();
+    for (path, relative) in scripts {
+        let data = match fs::read(&path) {
+            Ok(data) => data,
+            Err(e) => {
+                failed.push(format!("{}: {}", relative, e));
+                continue;
+            }
+        };
+        // A script that isn't UTF-8 or has NULs is left alone rather than mangled
+        let text = match String::from_utf8(data) {
+            Ok(text) if !file_type::looks_binary(text.as_bytes()) => text,
+            _ => {
+                failed.push(format!("{}: not a UTF-8 text file, skipped", relative));
+                continue;
+            }
+        };
+        let (normalized, changes) = normalize::normalize(&text, options);
+        if changes.is_empty() {
+            continue;
+        }
+        if !dry_run {
+            if let Err(e) = write_atomic(&path, normalized.as_bytes()) {
+                failed.push(format!("{}: {}", relative, e));
+                continue;
+            }
+        }
+        files.push(NormalizedFile {
+            path: relative,
+            changes,
+        });
+    }
+
+    Ok(NormalizeScriptsResult {
+        success: failed.is_empty(),
+        files: Some(files),
+        failed: if failed.is_empty() { None } else { Some(failed) },
+        error: None,
+    })
+}
+
+// Normalizes line endings, trailing whitespace and the final newline across
+// every script in the mod; with dry_run the changes are only reported
+#[tauri::command]
+async fn normalize_all_scripts(
+    app: AppHandle,
+    mod_root: String,
+    options: normalize::NormalizeOptions,
+    dry_run: Option<bool>,
+) -> timing::Timed<NormalizeScriptsResult> {
+    timing::measure(app, "normalize_all_scripts", async move {
+        let root = PathBuf::from(mod_root);
+        let dry_run = dry_run.unwrap_or(false);
+        let result =
+            run_blocking(None, move || normalize_all_scripts_blocking(&root, &options, dry_run))
+                .await;
+        match result.and_then(|r| r) {
+            Ok(result) => result,
+            Err(e) => NormalizeScriptsResult {
+                success: false,
+                files: None,
+                failed: None,
+                error: Some(e),
+            },
+        }
+    })
+    .await
+}
+
+fn check_whitespace_blocking(mod_root: &Path) -> Result<CheckWhitespaceResult, String> {
+    let options = normalize::NormalizeOptions {
+        line_ending: None,
+        trim_trailing_whitespace: true,
+        final_newline: true,
+    };
+    let mut files = Vec::new();
+    let mut failed = Vec::new();
+    for file in export::collect_mod_files(mod_root).map_err(|e| e.to_string())? {
+        if !file_type::detect_file_type(&file.path).is_text() {
+            continue;
+        }
+        let text = match fs::read(&file.path) {
+            Ok(data) => String::from_utf8_lossy(&data).into_owned(),
+            Err(e) => {
+                failed.push(format!("{}: {}", file.relative, e));
+                continue;
+            }
+        };
+        // What normalizing would change is exactly what is wrong
+        let (_, changes) = normalize::normalize(&text, &options);
+        if !changes.is_empty() {
+            files.push(WhitespaceIssues {
+                path: file.relative,
+                trailing_whitespace: changes.trailing_whitespace,
+                missing_final_newline: changes.final_newline,
+            });
+        }
+    }
+    Ok(CheckWhitespaceResult {
+        success: true,
+        files: Some(files),
+        failed: if failed.is_empty() { None } else { Some(failed) },
+        error: None,
+    })
+}
+
+fn has_json_extension(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|e| e.to_string_lossy().eq_ignore_ascii_case("json"))
+}
+
+// Parses every .json file in the mod. With `detect_content`, other text files
+// whose content opens like a JSON document are checked too.
+fn validate_json_files_blocking(
+    mod_root: &Path,
+    detect_content: bool,
+) -> Result<ValidateJsonResult, String> {
+    let mut checked = 0;
+    let mut files = Vec::new();
+    let mut failed = Vec::new();
+    for file in export::collect_mod_files(mod_root).map_err(|e| e.to_string())? {
+        let by_extension = has_json_extension(&file.path);
+        let candidate = by_extension
+            || (detect_content
+                && file_type::detect_file_type(&file.path) == file_type::FileKind::Text);
+        if !candidate {
+            continue;
+        }
+        let text = match fs::read(&file.path) {
+            Ok(data) => String::from_utf8_lossy(&data).into_owned(),
+            Err(e) => {
+                failed.push(format!("{}: {}", file.relative, e));
+                continue;
+            }
+        };
+        // Editors add BOMs, and the game reads past them
+        let text = text.strip_prefix('\u{feff}').unwrap_or(&text);
+        if !by_extension && !text.trim_start().starts_with(['{', '[']) {
+            continue;
+        }
+        checked += 1;
+        if let Err(e) = serde_json::from_str::<serde_json::Value>(text) {
+            let message = e.to_string();
+            // The position is reported separately
+            let message = message.split(" at line ").next().unwrap_or_default();
+            files.push(JsonIssue {
+                path: file.relative,
+                line: e.line(),
+                column: e.column(),
+                message: message.to_string(),
+            });
+        }
+    }
+    Ok(ValidateJsonResult {
+        success: true,
+        checked: Some(checked),
+        files: Some(files),
+        failed: if failed.is_empty() { None } else { Some(failed) },
+        error: None,
+    })
+}
+
+// Lint for every JSON file in the mod, not just the manifest, so a broken
+// data table shows up before the game trips over it
+#[tauri::command]
+async fn validate_json_files(
+    app: AppHandle,
+    mod_root: String,
+    detect_content: Option<bool>,
+) -> timing::Timed<ValidateJsonResult> {
+    timing::measure(app, "validate_json_files", async move {
+        let root = PathBuf::from(mod_root);
+        let detect_content = detect_content.unwrap_or(false);
+        let result =
+            run_blocking(None, move || validate_json_files_blocking(&root, detect_content)).await;
+        match result.and_then(|r| r) {
+            Ok(result) => result,
+            Err(e) => ValidateJsonResult {
+                success: false,
+                checked: None,
+                files: None,
+                failed: None,
+                error: Some(e),
+            },
+        }
+    })
+    .await
+}
+
+// Lint-style scan of the mod's text files for trailing whitespace and a
+// missing final newline. Nothing is changed; normalize_all_scripts fixes
+// both in scripts
+#[tauri::command]
+async fn check_whitespace(
+    app: AppHandle,
+    mod_root: String,
+) -> timing::Timed<CheckWhitespaceResult> {
+    timing::measure(app, "check_whitespace", async move {
+        let root = PathBuf::from(mod_root);
+        let result = run_blocking(None, move || check_whitespace_blocking(&root)).await;
+        match result.and_then(|r| r) {
+            Ok(result) => result,
+            Err(e) => CheckWhitespaceResult {
+                success: false,
+                files: None,
+                failed: None,
+                error: Some(e),
+            },
+        }
+    })
+    .await
+}
+
+// Removes leading UTF-8 BOMs from the mod's text files; with dry_run the
+// files that would change are only listed
+#[tauri::command]
+async fn strip_boms(
+    app: AppHandle,
+    mod_root: String,
+    dry_run: Option<bool>,
+) -> timing::Timed<BomFilesResult> {
+    timing::measure(app, "strip_boms", async move {
+        let root = PathBuf::from(mod_root);
+        let dry_run = dry_run.unwrap_or(false);
+        let result = run_blocking(None, move || strip_boms_blocking(&root, dry_run)).await;
+        match result.and_then(|r| r) {
+            Ok(result) => result,
+            Err(e) => BomFilesResult {
+                success: false,
+                files: None,
+                failed: None,
+                error: Some(e),
+            },
+        }
+    })
+    .await
+}
+
+// Recent command timings for the performance panel
+#[tauri::command]
+async fn get_timing_stats(app: AppHandle) -> TimingStatsResult {
+    let timing = app.state::<timing::Timing>();
+    TimingStatsResult {
+        success: true,
+        enabled: timing.is_enabled(),
+        commands: Some(timing.stats()),
+        error: None,
+    }
+}
+
+#[tauri::command]
+async fn set_timing_enabled(app: AppHandle, enabled: bool) -> TimingStatsResult {
+    let result = load_settings(&app).and_then(|mut settings| {
+        settings.timing_enabled = enabled;
+        save_settings(&app, &settings)
+    });
+    let timing = app.state::<timing::Timing>();
+    if let Err(e) = result {
+        return TimingStatsResult {
+            success: false,
+            enabled: timing.is_enabled(),
+            commands: None,
+            error: Some(e),
+        };
+    }
+
+    timing.set_enabled(enabled);
+    TimingStatsResult {
+        success: true,
+        enabled,
+        commands: Some(timing.stats()),
+        error: None,
+    }
+}
+
+fn search_blocking(
+    app: &AppHandle,
+    root: &Path,
+    pattern: &regex::Regex,
+    context: usize,
+    scope: &search::SearchScope,
+    operation_id: Option<String>,
+    token: &operations::CancelToken,
+) -> Result<SearchSummary, String> {
+    let mut files = paths::walk_files(root).map_err(|e| e.to_string())?;
+    files.sort();
+
+    let mut summary = SearchSummary {
+        operation_id: operation_id.clone(),
+        total_matches: 0,
+        files_searched: 0,
+        cancelled: false,
+    };
+    let mut batch = Vec::new();
+    let flush = |batch: &mut Vec<search::SearchMatch>| {
+        let _ = app.emit(
+            SEARCH_RESULT_EVENT,
+            SearchBatch {
+                operation_id: operation_id.clone(),
+                matches: std::mem::take(batch),
+            },
+        );
+    };
+
+    for path in files {
+        if token.is_cancelled() {
+            summary.cancelled = true;
+            break;
+        }
+        let relative = paths::relative_slash_path(root, &path).unwrap_or_default();
+        if !scope.allows(&path, &relative) {
+            continue;
+        }
+        let display_path = path.to_string_lossy().to_string();
+        let matches = search::search_file(&path, &display_path, pattern, context);
+        summary.files_searched += 1;
+        summary.total_matches += matches.len();
+        batch.extend(matches);
+        if batch.len() >= SEARCH_BATCH_SIZE {
+            flush(&mut batch);
+        }
+    }
+    if !batch.is_empty() {
+        flush(&mut batch);
+    }
+    Ok(summary)
+}
+
+// Streams matches as search-result events and finishes with search-done.
+// Passing an operation_id makes the search cancellable via cancel_operation.
+// file_types ("script", "json", ...) and include/exclude globs narrow the
+// files searched; see search::SearchScope
+// Arguments arrive by name from the frontend, so they stay flat
+#[allow(clippy::too_many_arguments)]
+#[tauri::command]
+async fn search_in_files(
+    app: AppHandle,
+    root: String,
+    query: String,
+    is_regex: Option<bool>,
+    case_sensitive: Option<bool>,
+    context_lines: Option<usize>,
+    operation_id: Option<String>,
+    file_types: Option<Vec<String>>,
+    include: Option<Vec<String>>,
+    exclude: Option<Vec<String>>,
+) -> timing::Timed<SearchResult> {
+    timing::measure(app.clone(), "search_in_files", async move {
+        let pattern = match search::build_pattern(
+            &query,
+            is_regex.unwrap_or(false),
+            case_sensitive.unwrap_or(false),
+        ) {
+            Ok(pattern) => pattern,
+            Err(e) => {
+                return SearchResult {
+                    success: false,
+                    summary: None,
+                    error: Some(e),
+                }
             }
-        });
-        
-        for entry in entries {
-            let name = entry.file_name().to_string_lossy().to_string();
-            let entry_path = entry.path();
-            let path_str = entry_path.to_string_lossy().to_string();
-            let is_dir = entry.file_type().map(|t| t.is_dir()).unwrap_or(false);
-            
-            let children = if is_dir && depth < max_depth {
-                Some(build_file_tree(&entry_path, depth + 1, max_depth))
-            } else {
-                None
+        };
+
+        let operations = app.state::<operations::Operations>();
+        let token = match &operation_id {
+            Some(id) => operations.start(id),
+            None => operations::CancelToken::default(),
+        };
+        let context = context_lines.unwrap_or(2);
+        let scope = search::SearchScope::new(
+            &file_types.unwrap_or_default(),
+            &include.unwrap_or_default(),
+            &exclude.unwrap_or_default(),
+        );
+        let root = PathBuf::from(root);
+        let handle = app.clone();
+        let id = operation_id.clone();
+        let result = run_blocking(None, move || {
+            search_blocking(&handle, &root, &pattern, context, &scope, id, &token)
+        })
+        .await
+        .and_then(|r| r);
+        if let Some(id) = &operation_id {
+            operations.finish(id);
+        }
+
+        match result {
+            Ok(summary) => {
+                let _ = app.emit(SEARCH_DONE_EVENT, summary.clone());
+                SearchResult {
+                    success: true,
+                    summary: Some(summary),
+                    error: None,
+                }
+            }
+            Err(e) => SearchResult {
+                success: false,
+                summary: None,
+                error: Some(e),
+            },
+        }
+    })
+    .await
+}
+
+#[tauri::command]
+async fn cancel_operation(
+    app: AppHandle,
+    operation_id: String,
+) -> timing::Timed<CancelOperationResult> {
+    timing::measure(app.clone(), "cancel_operation", async move {
+        let cancelled = app.state::<operations::Operations>().cancel(&operation_id);
+        CancelOperationResult {
+            success: true,
+            cancelled,
+        }
+    })
+    .await
+}
+
+fn emit_manifest(app: &AppHandle, mod_root: &str, path: &Path) {
+    let parse_error = |message: String, line: Option<usize>, column: Option<usize>| {
+        ManifestParseError {
+            mod_root: mod_root.to_string(),
+            message,
+            line,
+            column,
+        }
+    };
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) => {
+            let message = format!("Failed to read {}: {}", path.display(), e);
+            let error = parse_error(message, None, None);
+            let _ = app.emit(MANIFEST_PARSE_ERROR_EVENT, error);
+            return;
+        }
+    };
+    match serde_json::from_str::<serde_json::Value>(&content) {
+        Ok(manifest) => {
+            let update = ManifestUpdate {
+                mod_root: mod_root.to_string(),
+                manifest,
             };
-            
-            items.push(FileItem {
-                name,
-                path: path_str,
-                item_type: if is_dir { "folder".to_string() } else { "file".to_string() },
-                children,
-            });
+            let _ = app.emit(MANIFEST_UPDATED_EVENT, update);
+        }
+        Err(e) => {
+            let error = parse_error(e.to_string(), Some(e.line()), Some(e.column()));
+            let _ = app.emit(MANIFEST_PARSE_ERROR_EVENT, error);
         }
     }
-    
-    items
 }
 
+// Emits manifest-updated with the parsed manifest now and after every change
+// on disk, or manifest-parse-error when it doesn't parse. Runs until
+// cancel_operation is called with `operation_id` or the window closes;
+// watching the same manifest again, or again under the same id, replaces the
+// earlier watch
 #[tauri::command]
-async fn open_mod_folder(folder_path: String) -> OpenModFolderResult {
-    let path = Path::new(&folder_path);
-    if !path.exists() {
-        return OpenModFolderResult {
-            success: false,
-            tree: None,
-            root_path: None,
-            error: Some("Folder does not exist".to_string()),
+async fn watch_manifest(
+    app: AppHandle,
+    window: tauri::Window,
+    mod_root: String,
+    operation_id: String,
+) -> timing::Timed<WatchManifestResult> {
+    timing::measure(app.clone(), "watch_manifest", async move {
+        if !Path::new(&mod_root).is_dir() {
+            return WatchManifestResult {
+                success: false,
+                error: Some(format!("{} is not a folder", mod_root)),
+            };
+        }
+        let path = Path::new(&mod_root).join(manifest::MANIFEST_FILE_NAME);
+        let key = paths::canonicalize_lenient(&path);
+        let operations = app.state::<operations::Operations>();
+        operations.cancel(&operation_id);
+        let token = operations.start(&operation_id);
+        app.state::<watch::Watchers>().replace(&key, window.label(), &token);
+        let handle = app.clone();
+        std::thread::spawn(move || {
+            watch::poll_file(&path, &token, || emit_manifest(&handle, &mod_root, &path));
+            // A replacing watch may already hold the id or the path
+            handle.state::<watch::Watchers>().remove(&key, &token);
+            handle
+                .state::<operations::Operations>()
+                .finish_token(&operation_id, &token);
+        });
+        WatchManifestResult {
+            success: true,
+            error: None,
+        }
+    })
+    .await
+}
+
+fn repair_manifest_blocking(mod_root: &Path, regenerate: bool) -> RepairManifestResult {
+    let failed = |error: String| RepairManifestResult {
+        success: false,
+        fixes: None,
+        backup_path: None,
+        regenerated: None,
+        error: Some(error),
+    };
+
+    let manifest_path = mod_root.join(manifest::MANIFEST_FILE_NAME);
+    let text = match fs::read_to_string(&manifest_path) {
+        Ok(text) => text,
+        Err(e) => return failed(format!("Failed to read manifest.json: {}", e)),
+    };
+    if serde_json::from_str::<serde_json::Value>(&text).is_ok() {
+        return RepairManifestResult {
+            success: true,
+            fixes: Some(Vec::new()),
+            backup_path: None,
+            regenerated: Some(false),
+            error: None,
         };
     }
-    
-    let tree = build_file_tree(path, 0, 3);
-    
-    OpenModFolderResult {
+
+    let (repaired, fixes, regenerated) = match manifest::parse_lenient(&text) {
+        Ok((value, fixes)) => (value, fixes, false),
+        Err(e) if !regenerate => return failed(e),
+        Err(_) => {
+            // Keep whatever identity can still be read from the wreckage or mod.vdf
+            let recovered = manifest::recover_string_fields(
+                &text,
+                &["name", "description", "version", "author", "modId"],
+            );
+            let vdf = manifest::load_mod_vdf(mod_root);
+            let field = |key: &str, vdf_key: &str| {
+                recovered
+                    .get(key)
+                    .and_then(|v| v.as_str())
+                    .or_else(|| vdf.as_ref().and_then(|v| v.get_str(vdf_key)))
+                    .unwrap_or_default()
+                    .to_string()
+            };
+            let folder_name = mod_root
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default();
+            // mod.vdf holds the id as its block's key rather than as a value
+            let mod_id = recovered
+                .get("modId")
+                .and_then(|v| v.as_str())
+                .filter(|id| !id.is_empty())
+                .map_or(folder_name, str::to_string);
+            let skeleton = manifest::skeleton(
+                &field("name", "Name"),
+                &field("description", "Description"),
+                &field("version", "Version"),
+                &field("author", "Author"),
+                &mod_id,
+            );
+            let fixes = vec![format!(
+                "Regenerated the manifest, keeping {} recovered field(s); \
+                 script and asset lists must be rebuilt",
+                recovered.len()
+            )];
+            (skeleton, fixes, true)
+        }
+    };
+
+    let timestamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let backup_path = mod_root.join(format!("{}.{}.bak", manifest::MANIFEST_FILE_NAME, timestamp));
+    if let Err(e) = fs::copy(&manifest_path, &backup_path) {
+        return failed(format!("Failed to back up manifest.json: {}", e));
+    }
+
+    let content = match serde_json::to_string_pretty(&repaired) {
+        Ok(content) => content,
+        Err(e) => return failed(e.to_string()),
+    };
+    if let Err(e) = write_atomic(&manifest_path, content.as_bytes()) {
+        return failed(format!("Failed to write manifest.json: {}", e));
+    }
+
+    RepairManifestResult {
         success: true,
-        tree: Some(tree),
-        root_path: Some(folder_path),
+        fixes: Some(fixes),
+        backup_path: Some(backup_path.to_string_lossy().to_string()),
+        regenerated: Some(regenerated),
         error: None,
     }
 }
 
+// Rewrites a manifest.json that fails to parse as clean JSON, after backing
+// up the original. With regenerate, a manifest beyond repair is rebuilt from
+// the create_mod skeleton using whatever fields can still be recovered.
 #[tauri::command]
-async fn create_mod(mod_data: ModData) -> CreateModResult {
-    let mod_dir = format!("{}/{}", mod_data.path, mod_data.mod_id);
-    let mod_path = Path::new(&mod_dir);
-    
-    if mod_path.exists() {
-        return CreateModResult {
-            success: false,
-            path: None,
-            error: Some("Mod directory already exists".to_string()),
-        };
+async fn repair_manifest(
+    app: AppHandle,
+    mod_root: String,
+    regenerate: Option<bool>,
+) -> timing::Timed<RepairManifestResult> {
+    timing::measure(app, "repair_manifest", async move {
+        let root = PathBuf::from(mod_root);
+        let regenerate = regenerate.unwrap_or(false);
+        run_blocking(None, move || repair_manifest_blocking(&root, regenerate))
+            .await
+            .unwrap_or_else(|e| RepairManifestResult {
+                success: false,
+                fixes: None,
+                backup_path: None,
+                regenerated: None,
+                error: Some(e),
+            })
+    })
+    .await
+}
+
+fn duplicate_mod_blocking(
+    source_root: &Path,
+    destination: &Path,
+    mod_id: &str,
+    name: &str,
+) -> Result<PathBuf, String> {
+    mod_identity::validate_mod_id(mod_id)?;
+    if !source_root.join("mod.vdf").is_file() {
+        return Err(format!("{} is not a mod folder", source_root.display()));
     }
-    
-    // Create directory structure
-    let dirs = [
-        mod_dir.clone(),
-        format!("{}/scripts", mod_dir),
-        format!("{}/scripts/vscripts", mod_dir),
-        format!("{}/paks", mod_dir),
-        format!("{}/audio", mod_dir),
-        format!("{}/resource", mod_dir),
-    ];
-    
-    for dir in &dirs {
-        if let Err(e) = fs::create_dir_all(dir) {
-            return CreateModResult {
+    let target = destination.join(mod_id);
+    if target.exists() {
+        return Err("Mod directory already exists".to_string());
+    }
+
+    // The source's current identity, so README mentions can be replaced too
+    let old_manifest = manifest::load(source_root).ok();
+    let old_field = |key: &str| {
+        old_manifest
+            .as_ref()
+            .and_then(|m| m.get(key))
+            .and_then(|v| v.as_str())
+            .unwrap_or_default()
+            .to_string()
+    };
+    let old_id = Some(old_field("modId"))
+        .filter(|id| !id.is_empty())
+        .or_else(|| source_root.file_name().map(|n| n.to_string_lossy().to_string()))
+        .unwrap_or_default();
+    let old_name = old_field("name");
+
+    // A half-built copy would still carry the source's id, so the game would
+    // see two mods with the same id; nothing is left behind on failure
+    let built = mod_identity::copy_mod_dir(source_root, &target)
+        .map_err(|e| format!("Failed to copy mod: {}", e))
+        .and_then(|_| {
+            mod_identity::rewrite_identity(&target, &old_id, &old_name, mod_id, name, write_atomic)
+        });
+    if let Err(e) = built {
+        let _ = fs::remove_dir_all(&target);
+        return Err(e);
+    }
+    Ok(target)
+}
+
+fn mod_id_consistency(mod_root: &Path) -> Result<ModIdConsistency, String> {
+    let folder = mod_root
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .ok_or_else(|| format!("{} is not a mod folder", mod_root.display()))?;
+    let vdf_key = manifest::load_mod_vdf_entry(mod_root).map(|(key, _)| key);
+    let manifest_id = if mod_root.join(manifest::MANIFEST_FILE_NAME).is_file() {
+        manifest::load(mod_root)?
+            .get("modId")
+            .and_then(|v| v.as_str())
+            .map(str::to_string)
+    } else {
+        None
+    };
+    let consistent = [&vdf_key, &manifest_id]
+        .into_iter()
+        .flatten()
+        .all(|id| *id == folder);
+    Ok(ModIdConsistency {
+        folder,
+        vdf_key,
+        manifest_id,
+        consistent,
+    })
+}
+
+// Compares the folder name, the mod.vdf key and the manifest's modId. With
+// auto_fix the last two are set to `canonical_id` (the folder name by
+// default); the folder itself is never renamed here
+#[tauri::command]
+async fn check_modid_consistency(
+    app: AppHandle,
+    mod_root: String,
+    auto_fix: Option<bool>,
+    canonical_id: Option<String>,
+) -> timing::Timed<ModIdConsistencyResult> {
+    timing::measure(app, "check_modid_consistency", async move {
+        let result = run_blocking(None, move || {
+            let root = Path::new(&mod_root);
+            let before = mod_id_consistency(root)?;
+            if !auto_fix.unwrap_or(false) {
+                return Ok((before, None));
+            }
+            let id = canonical_id.unwrap_or_else(|| before.folder.clone());
+            let fixed = mod_identity::align_mod_id(root, &id, write_atomic)?;
+            Ok((mod_id_consistency(root)?, Some(fixed)))
+        })
+        .await;
+        match result.and_then(|r| r) {
+            Ok((ids, fixed)) => ModIdConsistencyResult {
+                success: true,
+                ids: Some(ids),
+                fixed,
+                error: None,
+            },
+            Err(e) => ModIdConsistencyResult {
+                success: false,
+                ids: None,
+                fixed: None,
+                error: Some(e),
+            },
+        }
+    })
+    .await
+}
+
+// Copies an existing mod to `destination/<mod_id>` and gives the copy the
+// new id and name in mod.vdf, manifest.json and README.md
+#[tauri::command]
+async fn duplicate_mod(
+    app: AppHandle,
+    source_root: String,
+    mod_id: String,
+    name: String,
+    destination: String,
+) -> timing::Timed<CreateModResult> {
+    timing::measure(app, "duplicate_mod", async move {
+        let result = run_blocking(None, move || {
+            duplicate_mod_blocking(Path::new(&source_root), Path::new(&destination), &mod_id, &name)
+        })
+        .await;
+        match result.and_then(|r| r) {
+            Ok(path) => CreateModResult {
+                success: true,
+                path: Some(path.to_string_lossy().to_string()),
+                already_exists: None,
+                plan: None,
+                error: None,
+            },
+            Err(e) => CreateModResult {
                 success: false,
                 path: None,
-                error: Some(format!("Failed to create directory: {}", e)),
-            };
+                already_exists: None,
+                plan: None,
+                error: Some(e),
+            },
         }
-    }
-    
-    // Create mod.vdf
-    let vdf_content = format!(
-        r#""{}"
-{{
-    "Name"              "{}"
-    "Description"       "{}"
-    "Version"           "{}"
-    "RequiredOnClient"  "1"
-}}"#,
-        mod_data.mod_id, mod_data.name, mod_data.description, mod_data.version
-    );
-    
-    if let Err(e) = fs::write(format!("{}/mod.vdf", mod_dir), &vdf_content) {
-        return CreateModResult {
-            success: false,
-            path: None,
-            error: Some(format!("Failed to write mod.vdf: {}", e)),
+    })
+    .await
+}
+
+// Scans one mods root, or every configured root when none is given
+#[tauri::command]
+async fn scan_mods(app: AppHandle, root: Option<String>) -> timing::Timed<ScanModsResult> {
+    timing::measure(app.clone(), "scan_mods", async move {
+        let roots = match root {
+            Some(root) => vec![root],
+            None => match load_settings(&app) {
+                Ok(settings) => settings.mod_directories,
+                Err(e) => {
+                    return ScanModsResult {
+                        success: false,
+                        roots: None,
+                        error: Some(e),
+                    };
+                }
+            },
         };
+
+        let scans = run_blocking(None, move || {
+            roots.iter().map(|root| scan_mod_root(root)).collect()
+        });
+        match scans.await {
+            Ok(scans) => ScanModsResult {
+                success: true,
+                roots: Some(scans),
+                error: None,
+            },
+            Err(e) => ScanModsResult {
+                success: false,
+                roots: None,
+                error: Some(e),
+            },
+        }
+    })
+    .await
+}
+
+// Probes Steam libraries, the registry and default install paths for the game
+#[tauri::command]
+async fn detect_game_install(app: AppHandle) -> timing::Timed<DetectGameInstallResult> {
+    timing::measure(app, "detect_game_install", async move {
+        match run_blocking(None, game_install::detect_installs).await {
+            Ok(candidates) => DetectGameInstallResult {
+                success: true,
+                candidates: Some(candidates),
+                error: None,
+            },
+            Err(e) => DetectGameInstallResult {
+                success: false,
+                candidates: None,
+                error: Some(e),
+            },
+        }
+    })
+    .await
+}
+
+// Lists the libraries of every Steam install found, and whether each has the
+// app (the game by default) installed
+#[tauri::command]
+async fn list_steam_libraries(
+    app: AppHandle,
+    app_id: Option<String>,
+) -> timing::Timed<SteamLibrariesResult> {
+    timing::measure(app, "list_steam_libraries", async move {
+        let app_id = app_id.unwrap_or_else(|| game_install::APEX_APP_ID.to_string());
+        match run_blocking(None, move || game_install::list_libraries(&app_id)).await {
+            Ok((libraries, warnings)) => SteamLibrariesResult {
+                success: true,
+                libraries: Some(libraries),
+                warnings: if warnings.is_empty() { None } else { Some(warnings) },
+                error: None,
+            },
+            Err(e) => SteamLibrariesResult {
+                success: false,
+                libraries: None,
+                warnings: None,
+                error: Some(e),
+            },
+        }
+    })
+    .await
+}
+
+fn check_known_file(known: &KnownFile) -> std::io::Result<FileChange> {
+    let change = |status: &str, modified| FileChange {
+        path: known.path.clone(),
+        status: status.to_string(),
+        modified,
+    };
+    let metadata = match fs::metadata(&known.path) {
+        Ok(metadata) => metadata,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(change("deleted", None)),
+        Err(e) => return Err(e),
+    };
+    let modified = modified_ms(&metadata);
+    // An unchanged time is trusted; a changed one is confirmed by the hash
+    // when there is one, since saving without edits still touches the file
+    let changed = if known.modified.is_some() && known.modified == modified {
+        false
+    } else if let Some(hash) = &known.hash {
+        !export::hash_file(Path::new(&known.path))?.eq_ignore_ascii_case(hash)
+    } else {
+        true
+    };
+    Ok(change(if changed { "modified" } else { "unchanged" }, modified))
+}
+
+// Which of the files open in the editor changed on disk since they were
+// loaded, so a "reload all" can pick them up in one go
+#[tauri::command]
+async fn get_externally_modified(
+    app: AppHandle,
+    files: Vec<KnownFile>,
+) -> timing::Timed<ExternallyModifiedResult> {
+    timing::measure(app, "get_externally_modified", async move {
+        let result = run_blocking(None, move || {
+            let mut changes = Vec::new();
+            let mut warnings = Vec::new();
+            for known in &files {
+                match check_known_file(known) {
+                    Ok(change) => changes.push(change),
+                    Err(e) => warnings.push(format!("{}: {}", known.path, e)),
+                }
+            }
+            (changes, warnings)
+        })
+        .await;
+        match result {
+            Ok((changes, warnings)) => ExternallyModifiedResult {
+                success: true,
+                files: Some(changes),
+                warnings: if warnings.is_empty() { None } else { Some(warnings) },
+                error: None,
+            },
+            Err(e) => ExternallyModifiedResult {
+                success: false,
+                files: None,
+                warnings: None,
+                error: Some(e),
+            },
+        }
+    })
+    .await
+}
+
+type DirectoryDiff = (Vec<DirectoryEntry>, Vec<String>, Vec<DirectoryEntry>);
+
+// Compares one folder level against `previous`. An entry whose time and size
+// both match is unchanged; otherwise, with `compare_hashes` and a known hash,
+// a file's content decides, so a save without edits isn't a change.
+fn diff_directory_blocking(
+    dir: &Path,
+    previous: Vec<ListedEntry>,
+    compare_hashes: bool,
+) -> std::io::Result<DirectoryDiff> {
+    let mut previous: BTreeMap<String, ListedEntry> =
+        previous.into_iter().map(|entry| (entry.name.clone(), entry)).collect();
+    let mut current = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        // Through symlinks, as the tree lists them
+        let metadata = fs::metadata(entry.path()).ok();
+        current.push(DirectoryEntry {
+            name: entry.file_name().to_string_lossy().to_string(),
+            is_directory: metadata.as_ref().is_some_and(|m| m.is_dir()),
+            size: metadata.as_ref().filter(|m| m.is_file()).map(|m| m.len()),
+            modified: metadata.as_ref().and_then(modified_ms),
+        });
     }
-    
-    // Create manifest.json
-    let manifest = serde_json::json!({
-        "name": mod_data.name,
-        "description": mod_data.description,
-        "version": mod_data.version,
-        "author": mod_data.author,
-        "modId": mod_data.mod_id,
-        "scripts": [],
-        "rpaks": [],
-        "audio": [],
-        "localization": {}
-    });
-    
-    if let Err(e) = fs::write(
-        format!("{}/manifest.json", mod_dir),
-        serde_json::to_string_pretty(&manifest).unwrap(),
-    ) {
-        return CreateModResult {
-            success: false,
-            path: None,
-            error: Some(format!("Failed to write manifest.json: {}", e)),
+    current.sort_by(|a, b| a.name.cmp(&b.name));
+
+    let (mut added, mut changed) = (Vec::new(), Vec::new());
+    for entry in current {
+        let Some(known) = previous.remove(&entry.name) else {
+            added.push(entry);
+            continue;
+        };
+        if known.modified == entry.modified && known.size == entry.size {
+            continue;
+        }
+        let same_content = match &known.hash {
+            Some(hash) if compare_hashes && !entry.is_directory => {
+                export::hash_file(&dir.join(&entry.name))?.eq_ignore_ascii_case(hash)
+            }
+            _ => false,
         };
+        if !same_content {
+            changed.push(entry);
+        }
     }
-    
-    // Create README.md
-    let readme = format!(
-        r#"# {}
+    let removed = previous.into_keys().collect();
+    Ok((added, removed, changed))
+}
 
-{}
+// What changed in a single folder since the UI listed it, so one folder view
+// can be patched after a watcher event without rebuilding the tree
+#[tauri::command]
+async fn diff_directory(
+    app: AppHandle,
+    path: String,
+    previous: Vec<ListedEntry>,
+    compare_hashes: Option<bool>,
+) -> timing::Timed<DirectoryDiffResult> {
+    timing::measure(app, "diff_directory", async move {
+        let result = run_blocking(None, move || {
+            diff_directory_blocking(Path::new(&path), previous, compare_hashes.unwrap_or(false))
+                .map_err(|e| format!("Failed to list {}: {}", path, e))
+        })
+        .await
+        .and_then(|r| r);
+        match result {
+            Ok((added, removed, changed)) => DirectoryDiffResult {
+                success: true,
+                added: Some(added),
+                removed: Some(removed),
+                changed: Some(changed),
+                error: None,
+            },
+            Err(e) => DirectoryDiffResult {
+                success: false,
+                added: None,
+                removed: None,
+                changed: None,
+                error: Some(e),
+            },
+        }
+    })
+    .await
+}
 
-## Author
-{}
+fn diff_against_disk_blocking(file_path: String, content: String) -> DiffResult {
+    let disk = match fs::read(&file_path) {
+        Ok(data) => data,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            return DiffResult {
+                success: true,
+                hunks: Some(diff::diff_hunks(&content, "", 3)),
+                deleted: Some(true),
+                binary: Some(false),
+                error: None,
+            };
+        }
+        Err(e) => {
+            return DiffResult {
+                success: false,
+                hunks: None,
+                deleted: None,
+                binary: None,
+                error: Some(e.to_string()),
+            };
+        }
+    };
 
-## Version
-{}
+    let disk_text = match String::from_utf8(disk) {
+        Ok(text) if !file_type::looks_binary(text.as_bytes()) => text,
+        _ => {
+            return DiffResult {
+                success: false,
+                hunks: None,
+                deleted: Some(false),
+                binary: Some(true),
+                error: Some("Cannot diff binary file".to_string()),
+            };
+        }
+    };
 
-## Installation
-Place this mod in your mods directory.
-"#,
-        mod_data.name, mod_data.description, mod_data.author, mod_data.version
-    );
-    
-    if let Err(e) = fs::write(format!("{}/README.md", mod_dir), &readme) {
-        return CreateModResult {
-            success: false,
-            path: None,
-            error: Some(format!("Failed to write README.md: {}", e)),
-        };
-    }
-    
-    CreateModResult {
+    DiffResult {
         success: true,
-        path: Some(mod_dir),
+        hunks: Some(diff::diff_hunks(&content, &disk_text, 3)),
+        deleted: Some(false),
+        binary: Some(false),
         error: None,
     }
 }
 
+// Diffs the editor buffer (old) against the file on disk (new)
+#[tauri::command]
+async fn diff_against_disk(
+    app: AppHandle,
+    file_path: String,
+    content: String,
+) -> timing::Timed<DiffResult> {
+    timing::measure(app, "diff_against_disk", async move {
+        run_blocking(None, move || diff_against_disk_blocking(file_path, content))
+            .await
+            .unwrap_or_else(|e| DiffResult {
+                success: false,
+                hunks: None,
+                deleted: None,
+                binary: None,
+                error: Some(e),
+            })
+    })
+    .await
+}
+
+// A project the app was asked to open, held until the frontend takes it.
+// Once it has, it is listening, so later requests are emitted directly.
+struct LaunchState {
+    pending: Option<String>,
+    frontend_ready: bool,
+}
+
+struct LaunchProject(Mutex<LaunchState>);
+
+// Compressed projects start with the magic bytes; plain ones are JSON
+fn looks_like_project(path: &Path) -> bool {
+    let mut head = [0u8; 64];
+    let read = match fs::File::open(path).and_then(|mut file| file.read(&mut head)) {
+        Ok(read) => read,
+        Err(_) => return false,
+    };
+    let head = &head[..read];
+    is_compressed_project(head) || head.iter().find(|b| !b.is_ascii_whitespace()) == Some(&b'{')
+}
+
+// First launch argument that is a readable project file, resolved against
+// the directory the app was started from
+fn launch_project_arg<I>(args: I, cwd: &Path) -> Option<String>
+where
+    I: IntoIterator,
+    I::Item: AsRef<std::ffi::OsStr>,
+{
+    args.into_iter()
+        .map(|arg| PathBuf::from(arg.as_ref()))
+        .filter(|arg| !arg.to_string_lossy().starts_with('-'))
+        .map(|arg| cwd.join(arg))
+        .find(|path| path.is_file() && looks_like_project(path))
+        .map(|path| dunce::canonicalize(&path).unwrap_or(path).to_string_lossy().to_string())
+}
+
+fn open_project_on_launch(app: &AppHandle, project: String) {
+    let state = app.state::<LaunchProject>();
+    let mut launch = state.0.lock().unwrap_or_else(|e| e.into_inner());
+    if launch.frontend_ready {
+        let _ = app.emit(OPEN_PROJECT_EVENT, project);
+    } else {
+        launch.pending = Some(project);
+    }
+}
+
+// The project the app was launched to open, if any. The frontend calls this
+// once it is listening for open-project-on-launch, which later requests
+// arrive as; a second call returns nothing
+#[tauri::command]
+async fn take_launch_project(app: AppHandle) -> timing::Timed<TakeLaunchProjectResult> {
+    timing::measure(app.clone(), "take_launch_project", async move {
+        let state = app.state::<LaunchProject>();
+        let mut launch = state.0.lock().unwrap_or_else(|e| e.into_inner());
+        launch.frontend_ready = true;
+        TakeLaunchProjectResult {
+            success: true,
+            project: launch.pending.take(),
+        }
+    })
+    .await
+}
+
+// macOS hands file associations over as an event instead of arguments
+#[cfg_attr(not(target_os = "macos"), allow(unused_variables))]
+fn handle_run_event(app: &AppHandle, event: tauri::RunEvent) {
+    #[cfg(target_os = "macos")]
+    if let tauri::RunEvent::Opened { urls } = event {
+        let files = urls.into_iter().filter_map(|url| url.to_file_path().ok());
+        if let Some(project) = launch_project_arg(files, Path::new("/")) {
+            open_project_on_launch(app, project);
+        }
+    }
+}
+
 fn main() {
+    let cwd = std::env::current_dir().unwrap_or_default();
+    let launch_project = launch_project_arg(std::env::args_os().skip(1), &cwd);
+
     tauri::Builder::default()
+        // Registered first so a second launch is forwarded before anything else runs
+        .plugin(tauri_plugin_single_instance::init(|app, argv, cwd| {
+            if let Some(window) = app.get_webview_window("main") {
+                let _ = window.unminimize();
+                let _ = window.set_focus();
+            }
+            if let Some(project) = launch_project_arg(argv.iter().skip(1), Path::new(&cwd)) {
+                open_project_on_launch(app, project);
+            }
+        }))
         .plugin(tauri_plugin_shell::init())
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_fs::init())
+        .manage(operations::Operations::default())
+        .manage(watch::Watchers::default())
+        .manage(content_hash::HashCache::default())
+        .manage(LaunchProject(Mutex::new(LaunchState {
+            pending: launch_project,
+            frontend_ready: false,
+        })))
+        .setup(|app| {
+            let timing_enabled = load_settings(app.handle())
+                .map(|settings| settings.timing_enabled)
+                .unwrap_or(false);
+            app.manage(timing::Timing::new(timing_enabled));
+            Ok(())
+        })
+        .on_window_event(|window, event| {
+            if let tauri::WindowEvent::Destroyed = event {
+                window
+                    .app_handle()
+                    .state::<watch::Watchers>()
+                    .cancel_window(window.label());
+            }
+        })
         .invoke_handler(tauri::generate_handler![
             read_file,
             write_file,
             read_project_file,
+            read_project_file_head,
             write_project_file,
+            read_project_metadata,
+            write_project_metadata,
             list_directory,
             create_directory,
             delete_directory,
             open_mod_folder,
+            expand_directory,
             create_mod,
+            format_script,
+            move_path,
+            set_file_tags,
+            get_files_by_tag,
+            prune_file_tags,
+            get_mod_directories,
+            add_mod_directory,
+            remove_mod_directory,
+            scan_mods,
+            detect_game_install,
+            diff_against_disk,
+            open_external,
+            export_manifest_markdown,
+            reconcile_manifest,
+            export_mod_zip,
+            scan_boms,
+            strip_boms,
+            get_timing_stats,
+            set_timing_enabled,
+            is_file_locked,
+            snapshot_file,
+            list_snapshots,
+            restore_snapshot,
+            resolve_path,
+            search_in_files,
+            cancel_operation,
+            repair_manifest,
+            duplicate_mod,
+            disk_space,
+            list_steam_libraries,
+            validate_paks,
+            normalize_all_scripts,
+            decode_project_bytes,
+            encode_project_bytes,
+            list_all_files,
+            check_brace_balance,
+            decompress_gzip,
+            save_session,
+            load_session,
+            rename_extension,
+            get_tree_node,
+            get_data_dir,
+            open_data_dir,
+            directory_size,
+            count_tree,
+            list_directories,
+            compare_mods,
+            get_externally_modified,
+            write_gitignore,
+            install_mod,
+            uninstall_mod,
+            relative_path,
+            collect_manifest_files,
+            normalize_permissions,
+            read_file_tail,
+            write_project_versioned,
+            list_project_versions,
+            open_project_version,
+            validate_utf8,
+            export_template,
+            import_template,
+            scan_encodings,
+            convert_to_utf8,
+            mod_content_hash,
+            delete_file,
+            describe_project,
+            watch_manifest,
+            export_paths_zip,
+            find_orphans,
+            read_binary_ranges,
+            check_name_collision,
+            recent_files_in_mod,
+            validate_for_version,
+            read_file_tokenized,
+            write_file_at,
+            check_whitespace,
+            truncate_file,
+            scan_ui_resources,
+            build_flat_index,
+            check_modid_consistency,
+            count_lines,
+            project_version_changelog,
+            stat_paths,
+            common_root,
+            is_within,
+            compress_file_gzip,
+            diff_directory,
+            create_unique_file,
+            migrate_project,
+            validate_json_files,
+            compute_load_order,
+            backup_mod,
+            restore_mod_backup,
+            list_mod_backups,
+            prune_mod_backups,
+            take_launch_project,
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(handle_run_event);
 }
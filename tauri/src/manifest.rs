@@ -0,0 +1,397 @@
+// Helpers for a mod's manifest.json.
+//
+// The manifest is kept as a loose serde_json::Value rather than a typed
+// struct so that fields added by other tools survive a read/modify/write.
+
+use crate::{paks, paths, vdf};
+use serde_json::Value;
+use std::fs;
+use std::path::Path;
+
+pub const MANIFEST_FILE_NAME: &str = "manifest.json";
+
+/// Reads and parses `manifest.json` from a mod folder.
+pub fn load(mod_root: &Path) -> Result<Value, String> {
+    let path = mod_root.join(MANIFEST_FILE_NAME);
+    let content = fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    serde_json::from_str(&content).map_err(|e| format!("Failed to parse {}: {}", path.display(), e))
+}
+
+/// Parses `mod.vdf` and returns the block for the mod (the first top-level
+/// entry), or None when the file is missing or malformed.
+pub fn load_mod_vdf(mod_root: &Path) -> Option<vdf::Value> {
+    load_mod_vdf_entry(mod_root).map(|(_, block)| block)
+}
+
+/// Like `load_mod_vdf`, but also returns the entry's key, which is the mod id.
+pub fn load_mod_vdf_entry(mod_root: &Path) -> Option<(String, vdf::Value)> {
+    let content = fs::read_to_string(mod_root.join("mod.vdf")).ok()?;
+    let document = vdf::parse(&content).ok()?;
+    document.entries().first().cloned()
+}
+
+// A single list entry: a plain path, or an object with a path-like field
+fn entry_name(entry: &Value) -> Option<String> {
+    match entry {
+        Value::String(s) => Some(s.clone()),
+        Value::Object(map) => ["path", "file", "name"]
+            .into_iter()
+            .find_map(|key| map.get(key).and_then(Value::as_str))
+            .map(str::to_string),
+        _ => None,
+    }
+}
+
+/// Flattens a manifest list into the files it names. Entries may be plain
+/// paths or objects carrying a `path`/`file`/`name` field. Localization maps
+/// languages to entries, so each of its files comes with its language.
+pub fn entry_files(value: Option<&Value>) -> Vec<(Option<String>, String)> {
+    match value {
+        Some(Value::Array(items)) => items
+            .iter()
+            .filter_map(entry_name)
+            .map(|file| (None, file))
+            .collect(),
+        Some(Value::Object(map)) => map
+            .iter()
+            .filter_map(|(key, v)| Some((Some(key.clone()), entry_name(v)?)))
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+// Manifest sections that list files
+const FILE_SECTIONS: &[&str] = &["scripts", "rpaks", "audio", "localization"];
+
+#[derive(Debug, serde::Serialize)]
+pub struct DeclaredFile {
+    // "scripts", "rpaks", "audio" or "localization"
+    pub section: String,
+    // As written in the manifest
+    pub entry: String,
+    // Resolved absolute path; not set for missing files
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+}
+
+/// Resolves every file the manifest declares against `mod_root`. Returns the
+/// files that exist and those that don't; entries pointing outside the mod
+/// count as missing. Rpaks resolve as `paks::validate` matches them.
+pub fn declared_files(manifest: &Value, mod_root: &Path) -> (Vec<DeclaredFile>, Vec<DeclaredFile>) {
+    let mut found = Vec::new();
+    let mut missing = Vec::new();
+    for section in FILE_SECTIONS {
+        for (_, entry) in entry_files(manifest.get(*section)) {
+            // Resolved like ensure_within's result, so callers can compare
+            // every path against the resolved root
+            let path = if *section == "rpaks" {
+                paks::resolve_rpak(mod_root, &entry).map(|p| paths::canonicalize_lenient(&p))
+            } else {
+                let path = mod_root.join(normalize_entry(&entry));
+                paths::ensure_within(mod_root, &path)
+                    .ok()
+                    .filter(|path| path.is_file())
+            };
+            let file = DeclaredFile {
+                section: section.to_string(),
+                entry,
+                path: path.map(|p| p.to_string_lossy().to_string()),
+            };
+            if file.path.is_some() {
+                found.push(file);
+            } else {
+                missing.push(file);
+            }
+        }
+    }
+    (found, missing)
+}
+
+/// Renders a release-notes style summary of the mod. Manifest fields win
+/// over mod.vdf ones, which only fill in what the manifest lacks.
+pub fn to_markdown(manifest: &Value, mod_vdf: Option<&vdf::Value>) -> String {
+    let field = |manifest_key: &str, vdf_key: &str| -> Option<String> {
+        manifest
+            .get(manifest_key)
+            .and_then(Value::as_str)
+            .or_else(|| mod_vdf.and_then(|v| v.get_str(vdf_key)))
+            .filter(|s| !s.trim().is_empty())
+            .map(str::to_string)
+    };
+
+    let name = field("name", "Name").unwrap_or_else(|| "Untitled mod".to_string());
+    let mut out = format!("# {}\n\n", name);
+
+    let mut details = Vec::new();
+    if let Some(version) = field("version", "Version") {
+        details.push(format!("- **Version:** {}", version));
+    }
+    if let Some(author) = field("author", "Author") {
+        details.push(format!("- **Author:** {}", author));
+    }
+    if let Some(mod_id) = manifest.get("modId").and_then(Value::as_str) {
+        details.push(format!("- **Mod ID:** `{}`", mod_id));
+    }
+    if !details.is_empty() {
+        out.push_str(&details.join("\n"));
+        out.push_str("\n\n");
+    }
+
+    if let Some(description) = field("description", "Description") {
+        out.push_str("## Description\n\n");
+        out.push_str(description.trim());
+        out.push_str("\n\n");
+    }
+
+    let sections = [
+        ("Scripts", "scripts"),
+        ("Paks", "rpaks"),
+        ("Audio", "audio"),
+        ("Localization", "localization"),
+    ];
+    for (title, key) in sections {
+        let mut entries: Vec<String> = entry_files(manifest.get(key))
+            .into_iter()
+            .map(|(language, file)| match language {
+                Some(language) => format!("{} ({})", language, file),
+                None => file,
+            })
+            .collect();
+        if entries.is_empty() {
+            continue;
+        }
+        entries.sort_by_key(|e| e.to_lowercase());
+        out.push_str(&format!("## {} ({})\n\n", title, entries.len()));
+        for entry in entries {
+            out.push_str(&format!("- `{}`\n", entry));
+        }
+        out.push('\n');
+    }
+
+    out.truncate(out.trim_end().len());
+    out.push('\n');
+    out
+}
+
+const SCRIPT_EXTENSIONS: &[&str] = &["nut", "gnut"];
+
+// Manifest paths are compared with forward slashes and no leading "./"
+fn normalize_entry(entry: &str) -> String {
+    let entry = entry.replace('\\', "/");
+    entry.trim_start_matches("./").to_string()
+}
+
+/// Mod-relative paths of every script file under `mod_root`, sorted. Hidden
+/// folders (like `.git`) are skipped.
+pub fn script_files_on_disk(mod_root: &Path) -> Vec<String> {
+    let mut found = Vec::new();
+    let mut pending = vec![mod_root.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if entry.file_name().to_string_lossy().starts_with('.') {
+                continue;
+            }
+            if path.is_dir() {
+                pending.push(path);
+                continue;
+            }
+            let is_script = path
+                .extension()
+                .and_then(|e| e.to_str())
+                .is_some_and(|e| SCRIPT_EXTENSIONS.iter().any(|s| e.eq_ignore_ascii_case(s)));
+            if let (true, Ok(relative)) = (is_script, path.strip_prefix(mod_root)) {
+                let parts: Vec<_> = relative
+                    .components()
+                    .map(|c| c.as_os_str().to_string_lossy())
+                    .collect();
+                found.push(parts.join("/"));
+            }
+        }
+    }
+    found.sort();
+    found
+}
+
+#[derive(Debug, Default, serde::Serialize)]
+pub struct ScriptDrift {
+    // On disk but not listed in the manifest
+    pub unlisted: Vec<String>,
+    // Listed in the manifest but gone from disk
+    pub missing: Vec<String>,
+}
+
+/// Compares the manifest's `scripts` list with the scripts found on disk.
+pub fn script_drift(manifest: &Value, on_disk: &[String]) -> ScriptDrift {
+    let listed: Vec<String> = entry_files(manifest.get("scripts"))
+        .iter()
+        .map(|(_, e)| normalize_entry(e))
+        .collect();
+    ScriptDrift {
+        unlisted: on_disk
+            .iter()
+            .filter(|file| !listed.contains(file))
+            .cloned()
+            .collect(),
+        missing: listed
+            .iter()
+            .filter(|entry| !on_disk.contains(entry))
+            .cloned()
+            .collect(),
+    }
+}
+
+/// Drops the missing entries from `scripts` and appends the unlisted ones,
+/// leaving every other entry (and its shape) untouched.
+pub fn apply_script_drift(manifest: &mut Value, drift: &ScriptDrift) -> Result<(), String> {
+    let Some(root) = manifest.as_object_mut() else {
+        return Err("manifest.json is not an object".to_string());
+    };
+    let scripts = root
+        .entry("scripts")
+        .or_insert_with(|| Value::Array(Vec::new()));
+    let Some(scripts) = scripts.as_array_mut() else {
+        return Err("manifest.json \"scripts\" is not an array".to_string());
+    };
+
+    scripts.retain(|entry| {
+        !entry_name(entry).is_some_and(|name| drift.missing.contains(&normalize_entry(&name)))
+    });
+    scripts.extend(drift.unlisted.iter().cloned().map(Value::String));
+    Ok(())
+}
+
+/// The manifest `create_mod` writes for a new mod.
+pub fn skeleton(name: &str, description: &str, version: &str, author: &str, mod_id: &str) -> Value {
+    serde_json::json!({
+        "name": name,
+        "description": description,
+        "version": version,
+        "author": author,
+        "modId": mod_id,
+        "scripts": [],
+        "rpaks": [],
+        "audio": [],
+        "localization": {}
+    })
+}
+
+// Drops a comma left dangling at the end of the output so far
+fn strip_trailing_comma(out: &mut String) -> bool {
+    let trimmed = out.trim_end().len();
+    if out[..trimmed].ends_with(',') {
+        out.truncate(trimmed - 1);
+        true
+    } else {
+        false
+    }
+}
+
+/// Parses manifest text that strict JSON rejects, tolerating comments,
+/// trailing commas and a truncated end. Returns the value and a description
+/// of each kind of fix applied.
+pub fn parse_lenient(text: &str) -> Result<(Value, Vec<String>), String> {
+    let mut out = String::with_capacity(text.len());
+    let mut stack: Vec<char> = Vec::new();
+    let (mut comments, mut commas) = (0, 0);
+    let mut chars = text.trim_start_matches('\u{feff}').chars().peekable();
+    let mut in_string = false;
+
+    while let Some(c) = chars.next() {
+        if in_string {
+            out.push(c);
+            match c {
+                '\\' => out.extend(chars.next()),
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match c {
+            '"' => {
+                in_string = true;
+                out.push(c);
+            }
+            '/' if chars.peek() == Some(&'/') => {
+                comments += 1;
+                while chars.peek().is_some_and(|&c| c != '\n') {
+                    chars.next();
+                }
+            }
+            '/' if chars.peek() == Some(&'*') => {
+                comments += 1;
+                chars.next();
+                let mut previous = '\0';
+                for c in chars.by_ref() {
+                    if previous == '*' && c == '/' {
+                        break;
+                    }
+                    previous = c;
+                }
+            }
+            '{' | '[' => {
+                stack.push(if c == '{' { '}' } else { ']' });
+                out.push(c);
+            }
+            '}' | ']' => {
+                if strip_trailing_comma(&mut out) {
+                    commas += 1;
+                }
+                stack.pop();
+                out.push(c);
+            }
+            _ => out.push(c),
+        }
+    }
+
+    let mut fixes = Vec::new();
+    if comments > 0 {
+        fixes.push(format!("Removed {} comment(s)", comments));
+    }
+    if commas > 0 {
+        fixes.push(format!("Removed {} trailing comma(s)", commas));
+    }
+    if in_string {
+        out.push('"');
+        fixes.push("Closed an unterminated string".to_string());
+    }
+    if !stack.is_empty() {
+        // A truncated write can stop after a comma, a key or a colon
+        strip_trailing_comma(&mut out);
+        if out.trim_end().ends_with(':') {
+            out.push_str("null");
+        }
+        fixes.push(format!("Closed {} unclosed bracket(s)", stack.len()));
+        while let Some(close) = stack.pop() {
+            out.push(close);
+        }
+    }
+
+    serde_json::from_str(&out)
+        .map(|value| (value, fixes))
+        .map_err(|e| format!("Could not recover manifest.json: {}", e))
+}
+
+/// Picks string fields out of text too broken to parse, by looking for
+/// `"key": "value"` pairs at any depth.
+pub fn recover_string_fields(text: &str, keys: &[&str]) -> serde_json::Map<String, Value> {
+    let mut fields = serde_json::Map::new();
+    for key in keys {
+        let pattern = format!(r#""{}"\s*:\s*"((?:[^"\\]|\\.)*)""#, regex::escape(key));
+        let Ok(re) = regex::Regex::new(&pattern) else {
+            continue;
+        };
+        let value = re
+            .captures(text)
+            .and_then(|c| c.get(1))
+            .and_then(|m| serde_json::from_str::<String>(&format!("\"{}\"", m.as_str())).ok());
+        if let Some(value) = value {
+            fields.insert(key.to_string(), Value::String(value));
+        }
+    }
+    fields
+}
@@ -0,0 +1,168 @@
+// Changing a mod's identity (id and display name) across its metadata files.
+
+use crate::manifest;
+use regex::Regex;
+use std::fs;
+use std::path::Path;
+
+// Folders not carried over when copying a mod
+const SKIPPED_ON_COPY: &[&str] = &[".git", crate::history::HISTORY_DIR_NAME];
+
+/// Mod ids name the mod's folder and its mod.vdf block, so they are kept to
+/// characters that are safe in both.
+pub fn validate_mod_id(mod_id: &str) -> Result<(), String> {
+    if mod_id.is_empty() {
+        return Err("Mod id is empty".to_string());
+    }
+    if mod_id.starts_with('.') {
+        return Err("Mod id can't start with '.'".to_string());
+    }
+    match mod_id
+        .chars()
+        .find(|c| !(c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.')))
+    {
+        Some(c) => Err(format!("Mod id can't contain '{}'", c)),
+        None => Ok(()),
+    }
+}
+
+/// Recursively copies a mod folder, leaving out version control and local
+/// history.
+pub fn copy_mod_dir(from: &Path, to: &Path) -> std::io::Result<()> {
+    fs::create_dir(to)?;
+    for entry in fs::read_dir(from)? {
+        let entry = entry?;
+        let name = entry.file_name();
+        if SKIPPED_ON_COPY.iter().any(|s| name == *s) {
+            continue;
+        }
+        let target = to.join(&name);
+        if entry.file_type()?.is_dir() {
+            copy_mod_dir(&entry.path(), &target)?;
+        } else {
+            fs::copy(entry.path(), &target)?;
+        }
+    }
+    Ok(())
+}
+
+fn vdf_quote(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+// Rewrites mod.vdf's root key in place, keeping the rest of the file
+// (comments, spacing) as written
+fn rewrite_mod_vdf_key(text: &str, mod_id: &str) -> String {
+    let root_key = Regex::new(r#"^(\s*(?://[^\n]*\n\s*)*)(?:"[^"]*"|[^\s{]+)"#).unwrap();
+    root_key
+        .replace(text, |caps: &regex::Captures| {
+            format!("{}{}", &caps[1], vdf_quote(mod_id))
+        })
+        .into_owned()
+}
+
+// Rewrites mod.vdf's root key and Name value in place, keeping the rest of
+// the file (comments, spacing) as written
+fn rewrite_mod_vdf(text: &str, mod_id: &str, name: &str) -> String {
+    let text = rewrite_mod_vdf_key(text, mod_id);
+    let name_value = Regex::new(r#"(?im)^(\s*"?Name"?\s+)"(?:[^"\\]|\\.)*""#).unwrap();
+    name_value
+        .replace(&text, |caps: &regex::Captures| {
+            format!("{}{}", &caps[1], vdf_quote(name))
+        })
+        .into_owned()
+}
+
+/// Rewrites mod.vdf, manifest.json and README.md under `mod_root` from the
+/// old identity to the new one. Missing files are skipped; returns the
+/// files that changed.
+pub fn rewrite_identity(
+    mod_root: &Path,
+    old_id: &str,
+    old_name: &str,
+    new_id: &str,
+    new_name: &str,
+    write: impl Fn(&Path, &[u8]) -> std::io::Result<()>,
+) -> Result<Vec<String>, String> {
+    let mut changed = Vec::new();
+    let mut update = |file: &str, content: String| -> Result<(), String> {
+        write(&mod_root.join(file), content.as_bytes())
+            .map_err(|e| format!("Failed to write {}: {}", file, e))?;
+        changed.push(file.to_string());
+        Ok(())
+    };
+
+    if let Ok(text) = fs::read_to_string(mod_root.join("mod.vdf")) {
+        update("mod.vdf", rewrite_mod_vdf(&text, new_id, new_name))?;
+    }
+
+    if mod_root.join(manifest::MANIFEST_FILE_NAME).is_file() {
+        let mut value = manifest::load(mod_root)?;
+        if let Some(fields) = value.as_object_mut() {
+            fields.insert("modId".to_string(), new_id.into());
+            fields.insert("name".to_string(), new_name.into());
+        }
+        let content = serde_json::to_string_pretty(&value).map_err(|e| e.to_string())?;
+        update(manifest::MANIFEST_FILE_NAME, content)?;
+    }
+
+    if let Ok(text) = fs::read_to_string(mod_root.join("README.md")) {
+        let mut content = text.clone();
+        for (old, new) in [(old_name, new_name), (old_id, new_id)] {
+            if !old.is_empty() && old != new {
+                content = content.replace(old, new);
+            }
+        }
+        if content != text {
+            update("README.md", content)?;
+        }
+    }
+
+    Ok(changed)
+}
+
+/// Sets the mod.vdf root key and the manifest's modId to `mod_id`, leaving
+/// names alone. Both new files are prepared before either is written, and
+/// if the second write fails the first file is put back. Returns the files
+/// that changed.
+pub fn align_mod_id(
+    mod_root: &Path,
+    mod_id: &str,
+    write: impl Fn(&Path, &[u8]) -> std::io::Result<()>,
+) -> Result<Vec<String>, String> {
+    validate_mod_id(mod_id)?;
+    // (file, original, rewritten)
+    let mut updates = Vec::new();
+
+    if let Ok(text) = fs::read_to_string(mod_root.join("mod.vdf")) {
+        let rewritten = rewrite_mod_vdf_key(&text, mod_id);
+        if rewritten != text {
+            updates.push(("mod.vdf", text, rewritten));
+        }
+    }
+    if mod_root.join(manifest::MANIFEST_FILE_NAME).is_file() {
+        let mut value = manifest::load(mod_root)?;
+        if value.get("modId").and_then(|v| v.as_str()) != Some(mod_id) {
+            if let Some(fields) = value.as_object_mut() {
+                fields.insert("modId".to_string(), mod_id.into());
+            }
+            let original = fs::read_to_string(mod_root.join(manifest::MANIFEST_FILE_NAME))
+                .map_err(|e| e.to_string())?;
+            let content = serde_json::to_string_pretty(&value).map_err(|e| e.to_string())?;
+            updates.push((manifest::MANIFEST_FILE_NAME, original, content));
+        }
+    }
+
+    for (index, (file, _, content)) in updates.iter().enumerate() {
+        if let Err(e) = write(&mod_root.join(file), content.as_bytes()) {
+            for (written, original, _) in &updates[..index] {
+                let _ = write(&mod_root.join(written), original.as_bytes());
+            }
+            return Err(format!("Failed to write {}: {}", file, e));
+        }
+    }
+    Ok(updates
+        .into_iter()
+        .map(|(file, _, _)| file.to_string())
+        .collect())
+}
@@ -0,0 +1,123 @@
+// Whitespace normalization for scripts: line endings, trailing whitespace
+// and the final newline.
+
+use crate::squirrel::{self, TokenKind};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LineEnding {
+    Lf,
+    Crlf,
+}
+
+impl LineEnding {
+    fn as_str(self) -> &'static str {
+        match self {
+            LineEnding::Lf => "\n",
+            LineEnding::Crlf => "\r\n",
+        }
+    }
+}
+
+// Everything is off by default, so only the requested fixes are applied
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct NormalizeOptions {
+    pub line_ending: Option<LineEnding>,
+    pub trim_trailing_whitespace: bool,
+    pub final_newline: bool,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct NormalizeChanges {
+    // Lines whose ending was converted
+    pub line_endings: usize,
+    // Lines that had trailing spaces or tabs removed
+    pub trailing_whitespace: usize,
+    // Whether a newline was added at the end
+    pub final_newline: bool,
+}
+
+impl NormalizeChanges {
+    pub fn is_empty(&self) -> bool {
+        self.line_endings == 0 && self.trailing_whitespace == 0 && !self.final_newline
+    }
+}
+
+// Byte ranges of strings and comments that run over more than one line.
+// Whitespace at the end of a line inside one of these is content.
+fn multiline_spans(text: &str) -> Vec<(usize, usize)> {
+    squirrel::tokenize(text)
+        .into_iter()
+        .filter(|t| matches!(t.kind, TokenKind::String | TokenKind::Comment))
+        .filter(|t| text[t.start..t.end].contains('\n'))
+        .map(|t| (t.start, t.end))
+        .collect()
+}
+
+/// Applies `options` to `text`, returning the new text and what changed.
+/// Trailing whitespace inside verbatim strings and block comments is kept.
+pub fn normalize(text: &str, options: &NormalizeOptions) -> (String, NormalizeChanges) {
+    let mut out = String::with_capacity(text.len());
+    let mut changes = NormalizeChanges::default();
+    let mut last_ending = None;
+    let spans = if options.trim_trailing_whitespace {
+        multiline_spans(text)
+    } else {
+        Vec::new()
+    };
+    // Spans come in order, so the ones already passed are skipped for good
+    let mut spans = spans.into_iter().peekable();
+    let mut offset = 0;
+
+    for line in text.split_inclusive('\n') {
+        let (body, ending) = if let Some(body) = line.strip_suffix("\r\n") {
+            (body, "\r\n")
+        } else if let Some(body) = line.strip_suffix('\n') {
+            (body, "\n")
+        } else {
+            (line, "")
+        };
+
+        let body_end = offset + body.len();
+        offset += line.len();
+        while spans.next_if(|&(_, end)| end <= body_end).is_some() {}
+        let in_span = spans.peek().is_some_and(|&(start, _)| start < body_end);
+
+        let trimmed = if options.trim_trailing_whitespace && !in_span {
+            body.trim_end_matches([' ', '\t'])
+        } else {
+            body
+        };
+        if trimmed.len() != body.len() {
+            changes.trailing_whitespace += 1;
+        }
+        out.push_str(trimmed);
+
+        let ending = match options.line_ending {
+            Some(target) if !ending.is_empty() && ending != target.as_str() => {
+                changes.line_endings += 1;
+                target.as_str()
+            }
+            _ => ending,
+        };
+        out.push_str(ending);
+        if !ending.is_empty() {
+            last_ending = Some(ending);
+        }
+    }
+
+    if options.final_newline && !out.is_empty() && !out.ends_with('\n') {
+        // Without a target style, follow the file's own
+        let ending = options
+            .line_ending
+            .map(LineEnding::as_str)
+            .or(last_ending)
+            .unwrap_or("\n");
+        out.push_str(ending);
+        changes.final_newline = true;
+    }
+
+    (out, changes)
+}
@@ -0,0 +1,77 @@
+// Cancellation for long-running operations.
+//
+// The frontend picks an id when it starts an operation and can later call
+// `cancel_operation` with it; the operation polls its token between units of
+// work and stops early once it is set.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+#[derive(Clone, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    /// Whether both are clones of the same token.
+    pub fn same(&self, other: &CancelToken) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+#[derive(Default)]
+pub struct Operations {
+    running: Mutex<HashMap<String, CancelToken>>,
+}
+
+impl Operations {
+    /// Registers an operation and returns its token. Reusing the id of a
+    /// running operation replaces its entry.
+    pub fn start(&self, id: &str) -> CancelToken {
+        let token = CancelToken::default();
+        self.running
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(id.to_string(), token.clone());
+        token
+    }
+
+    pub fn finish(&self, id: &str) {
+        self.running
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(id);
+    }
+
+    /// Like `finish`, but leaves the entry alone when `id` has since been
+    /// reused by an operation with another token.
+    pub fn finish_token(&self, id: &str, token: &CancelToken) {
+        let mut running = self.running.lock().unwrap_or_else(|e| e.into_inner());
+        if running.get(id).is_some_and(|current| current.same(token)) {
+            running.remove(id);
+        }
+    }
+
+    /// Flags an operation as cancelled. Returns false if it isn't running.
+    pub fn cancel(&self, id: &str) -> bool {
+        match self
+            .running
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(id)
+        {
+            Some(token) => {
+                token.cancel();
+                true
+            }
+            None => false,
+        }
+    }
+}
@@ -0,0 +1,88 @@
+// Files nothing leads to. Starting from what the game loads directly (the
+// manifest's declared files, `mod.vdf`, the assets UI declaration files
+// reference and any entry points the author names), include references
+// are followed through text files; whatever is never reached is an orphan.
+// Matching is case-insensitive like the game's file lookups, and a reference
+// to a folder reaches everything under it.
+
+use crate::{ignore, manifest, paths, references, ui_resources};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashSet;
+use std::io;
+use std::path::Path;
+
+// Always loaded by the game or the studio, whatever references them
+const ALWAYS_USED: &[&str] = &[manifest::MANIFEST_FILE_NAME, "mod.vdf"];
+
+#[derive(Debug, Serialize)]
+pub struct Orphan {
+    // Forward-slash path relative to the mod root
+    pub path: String,
+    pub size: u64,
+}
+
+/// Orphaned files under `mod_root`, sorted by path. Files `.r5vignore` lists
+/// are neither reported nor followed. `entry_points` are extra roots, in
+/// `.r5vignore` pattern syntax.
+pub fn find(
+    mod_root: &Path,
+    manifest: Option<&Value>,
+    entry_points: &[String],
+) -> io::Result<Vec<Orphan>> {
+    let graph = references::include_graph(mod_root)?;
+
+    let entry_rules = ignore::IgnoreRules::parse(&entry_points.join("\n"));
+    let mut pending: Vec<usize> = graph
+        .files
+        .iter()
+        .enumerate()
+        .filter(|(_, file)| {
+            ALWAYS_USED
+                .iter()
+                .any(|name| file.relative.eq_ignore_ascii_case(name))
+                || entry_rules.is_file_ignored(&file.relative)
+        })
+        .map(|(i, _)| i)
+        .collect();
+    if let Some(manifest) = manifest {
+        // Declared paths come back resolved, so compare against the
+        // resolved root
+        let root = paths::canonicalize_lenient(mod_root);
+        let (declared, _) = manifest::declared_files(manifest, mod_root);
+        pending.extend(
+            declared
+                .iter()
+                .filter_map(|file| file.path.as_deref())
+                .filter_map(|path| paths::relative_slash_path(&root, Path::new(path)))
+                .filter_map(|relative| graph.find(&relative)),
+        );
+    }
+    // Images and fonts named by `.res` and `.menu` files, which no include
+    // reaches
+    let ui = ui_resources::scan(mod_root)?;
+    pending.extend(
+        ui.found
+            .iter()
+            .filter_map(|resource| resource.resolved.as_deref())
+            .filter_map(|relative| graph.find(relative)),
+    );
+
+    let mut reached = HashSet::new();
+    while let Some(i) = pending.pop() {
+        if reached.insert(i) {
+            pending.extend(&graph.edges[i]);
+        }
+    }
+
+    Ok(graph
+        .files
+        .into_iter()
+        .enumerate()
+        .filter(|(i, _)| !reached.contains(i))
+        .map(|(_, file)| Orphan {
+            path: file.relative,
+            size: file.size,
+        })
+        .collect())
+}
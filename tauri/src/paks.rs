@@ -0,0 +1,143 @@
+// Checks for a mod's paks/ folder. Misnamed or unlisted paks don't error in
+// game, they just never load, so these are reported before release.
+
+use crate::{manifest, paths};
+use regex::Regex;
+use serde::Serialize;
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+
+pub const PAKS_DIR_NAME: &str = "paks";
+
+#[derive(Debug, Serialize)]
+pub struct PakWarning {
+    // Path relative to the mod root, or the manifest entry for "missing"
+    pub file: String,
+    // "naming", "orphanStarpak", "unlisted" or "missing"
+    pub kind: String,
+    pub message: String,
+}
+
+fn warning(file: &str, kind: &str, message: String) -> PakWarning {
+    PakWarning {
+        file: file.to_string(),
+        kind: kind.to_string(),
+        message,
+    }
+}
+
+fn file_name(path: &str) -> &str {
+    path.rsplit(['/', '\\']).next().unwrap_or(path)
+}
+
+fn is_rpak(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|e| e.to_string_lossy().eq_ignore_ascii_case("rpak"))
+}
+
+/// The rpak under paks/ a manifest `rpaks` entry names. Entries may carry a
+/// paks/ prefix or not, so this matches on file name, case-insensitively.
+pub fn resolve_rpak(mod_root: &Path, entry: &str) -> Option<PathBuf> {
+    let name = file_name(entry);
+    paths::walk_files(&mod_root.join(PAKS_DIR_NAME))
+        .ok()?
+        .into_iter()
+        .filter(|path| is_rpak(path))
+        .find(|path| {
+            path.file_name()
+                .is_some_and(|n| n.to_string_lossy().eq_ignore_ascii_case(name))
+        })
+}
+
+/// Validates the paks on disk against the expected `name(00).rpak` naming,
+/// pairs every `.starpak` with an rpak of the same name, and compares the
+/// rpaks with the manifest's `rpaks` list when a manifest is given.
+pub fn validate(mod_root: &Path, manifest: Option<&Value>) -> Result<Vec<PakWarning>, String> {
+    let rpak_name = Regex::new(r"^([A-Za-z0-9_]+)\((\d{2})\)\.rpak$").unwrap();
+    let starpak_name = Regex::new(r"^([A-Za-z0-9_]+)(?:\(\d{2}\))?(?:\.opt)?\.starpak$").unwrap();
+    let paks_dir = mod_root.join(PAKS_DIR_NAME);
+    let mut warnings = Vec::new();
+
+    let files = if paks_dir.is_dir() {
+        paths::walk_files(&paks_dir)
+            .map_err(|e| format!("Failed to read {}: {}", paks_dir.display(), e))?
+    } else {
+        Vec::new()
+    };
+
+    let mut rpaks = Vec::new();
+    let mut rpak_bases = Vec::new();
+    let mut starpaks = Vec::new();
+    for path in &files {
+        let Some(relative) = paths::relative_slash_path(mod_root, path) else {
+            continue;
+        };
+        let name = file_name(&relative).to_string();
+        let extension = path
+            .extension()
+            .map(|e| e.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        match extension.as_str() {
+            "rpak" => {
+                match rpak_name.captures(&name) {
+                    Some(caps) => rpak_bases.push(caps[1].to_string()),
+                    None => warnings.push(warning(
+                        &relative,
+                        "naming",
+                        format!("{} should be named like name(00).rpak", name),
+                    )),
+                }
+                rpaks.push((relative, name));
+            }
+            "starpak" => match starpak_name.captures(&name) {
+                Some(caps) => starpaks.push((relative, caps[1].to_string())),
+                None => warnings.push(warning(
+                    &relative,
+                    "naming",
+                    format!("{} should be named like name.starpak", name),
+                )),
+            },
+            _ => {}
+        }
+    }
+
+    for (relative, base) in &starpaks {
+        if !rpak_bases.contains(base) {
+            warnings.push(warning(
+                relative,
+                "orphanStarpak",
+                format!("No {}(NN).rpak streams from {}", base, file_name(relative)),
+            ));
+        }
+    }
+
+    if let Some(manifest) = manifest {
+        // Entries may carry a paks/ prefix or not, so match on file name
+        let listed: Vec<String> = manifest::entry_files(manifest.get("rpaks"))
+            .into_iter()
+            .map(|(_, file)| file)
+            .collect();
+        let listed_names: Vec<String> =
+            listed.iter().map(|e| file_name(e).to_lowercase()).collect();
+        for (relative, name) in &rpaks {
+            if !listed_names.contains(&name.to_lowercase()) {
+                warnings.push(warning(
+                    relative,
+                    "unlisted",
+                    format!("{} is not listed in the manifest's rpaks", name),
+                ));
+            }
+        }
+        for entry in &listed {
+            if resolve_rpak(mod_root, entry).is_none() {
+                warnings.push(warning(
+                    entry,
+                    "missing",
+                    format!("{} is listed in the manifest but not in paks/", entry),
+                ));
+            }
+        }
+    }
+
+    Ok(warnings)
+}
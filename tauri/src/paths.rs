@@ -0,0 +1,241 @@
+// Path normalization and root containment checks.
+//
+// Paths coming from the frontend are often built by string concatenation, may
+// contain `.`/`..` segments and may not exist yet, so these helpers work
+// lexically first and only consult the filesystem for the parts that exist.
+
+use std::collections::HashSet;
+use std::ffi::OsString;
+use std::path::{Component, Path, PathBuf};
+
+/// Resolves `.` and `..` segments without touching the filesystem. `..` at
+/// the root of an absolute path is dropped, as the OS would do.
+pub fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => match normalized.components().next_back() {
+                Some(Component::Normal(_)) => {
+                    normalized.pop();
+                }
+                Some(Component::RootDir) | Some(Component::Prefix(_)) => {}
+                _ => normalized.push(".."),
+            },
+            other => normalized.push(other.as_os_str()),
+        }
+    }
+    normalized
+}
+
+/// Canonicalizes the longest existing ancestor of `path` (resolving symlinks)
+/// and appends the remaining, not-yet-existing components to it.
+pub fn canonicalize_lenient(path: &Path) -> PathBuf {
+    let normalized = normalize(path);
+    let mut existing = normalized.as_path();
+    let mut missing: Vec<OsString> = Vec::new();
+
+    loop {
+        if let Ok(canonical) = dunce::canonicalize(existing) {
+            let mut resolved = canonical;
+            for part in missing.iter().rev() {
+                resolved.push(part);
+            }
+            return resolved;
+        }
+        match (existing.parent(), existing.file_name()) {
+            (Some(parent), Some(name)) => {
+                missing.push(name.to_os_string());
+                existing = parent;
+            }
+            _ => return normalized,
+        }
+    }
+}
+
+/// Returns the resolved target if it lies inside `root` (or is `root`
+/// itself), following symlinks for the parts of both paths that exist.
+pub fn ensure_within(root: &Path, target: &Path) -> Result<PathBuf, String> {
+    let root = canonicalize_lenient(root);
+    let target = canonicalize_lenient(target);
+    if target.starts_with(&root) {
+        Ok(target)
+    } else {
+        Err(format!(
+            "{} is outside of {}",
+            target.display(),
+            root.display()
+        ))
+    }
+}
+
+/// Resolves a path given relative to `root`, rejecting anything that ends up
+/// outside of it (`..` segments, absolute paths, symlinks leading out).
+pub fn resolve_in_root(root: &Path, relative: &str) -> Result<PathBuf, String> {
+    ensure_within(root, &root.join(relative))
+}
+
+/// The folders a walk has entered, by resolved path, so a symlink pointing
+/// back up the tree is walked once instead of forever.
+#[derive(Default)]
+pub struct VisitedDirs(HashSet<PathBuf>);
+
+impl VisitedDirs {
+    /// Whether `dir` is new to the walk, recording it if so. A folder that
+    /// can't be resolved counts as visited.
+    pub fn first_visit(&mut self, dir: &Path) -> bool {
+        dunce::canonicalize(dir).is_ok_and(|resolved| self.0.insert(resolved))
+    }
+}
+
+/// Every file under `root`, recursively, in no particular order. Hidden
+/// files and folders (names starting with `.`) are skipped. Symlinked
+/// folders are followed, each real folder only once, and entries that can't
+/// be read (such as dangling links) are left out.
+pub fn walk_files(root: &Path) -> std::io::Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    let mut visited = VisitedDirs::default();
+    visited.first_visit(root);
+    let mut pending = vec![root.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        for entry in std::fs::read_dir(&dir)?.flatten() {
+            if entry.file_name().to_string_lossy().starts_with('.') {
+                continue;
+            }
+            let path = entry.path();
+            match std::fs::metadata(&path) {
+                Ok(metadata) if metadata.is_dir() => {
+                    if visited.first_visit(&path) {
+                        pending.push(path);
+                    }
+                }
+                Ok(_) => files.push(path),
+                Err(_) => {}
+            }
+        }
+    }
+    Ok(files)
+}
+
+/// `path` relative to `root`, joined with forward slashes whatever the
+/// platform, as stored in manifests and sidecar files.
+pub fn relative_slash_path(root: &Path, path: &Path) -> Option<String> {
+    let relative = path.strip_prefix(root).ok()?;
+    Some(
+        relative
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/"),
+    )
+}
+
+// Drive letters and, on Windows, names compare without regard to case
+fn same_component(a: Component, b: Component) -> bool {
+    if cfg!(windows) {
+        a.as_os_str().eq_ignore_ascii_case(b.as_os_str())
+    } else {
+        a == b
+    }
+}
+
+/// The deepest directory containing every one of `paths`, compared
+/// component by component so `/mods/a` and `/mods/ab` share `/mods` and not
+/// `/mods/a`. A path that is an existing directory counts as itself, anything
+/// else as its parent. Fails on relative paths and on paths that don't share
+/// a drive or root.
+pub fn common_root(paths: &[PathBuf]) -> Result<PathBuf, String> {
+    let mut directories = Vec::with_capacity(paths.len());
+    for path in paths {
+        if !path.is_absolute() {
+            return Err(format!("{} is not an absolute path", path.display()));
+        }
+        let resolved = canonicalize_lenient(path);
+        let directory = match resolved.parent() {
+            Some(parent) if !resolved.is_dir() => parent.to_path_buf(),
+            _ => resolved,
+        };
+        directories.push(directory);
+    }
+    let (first, rest) = directories
+        .split_first()
+        .ok_or_else(|| "No paths given".to_string())?;
+
+    let mut shared = first.components().count();
+    for directory in rest {
+        shared = first
+            .components()
+            .zip(directory.components())
+            .take(shared)
+            .take_while(|(a, b)| same_component(*a, *b))
+            .count();
+    }
+    // Sharing less than the drive and root means different filesystems
+    let root: PathBuf = first.components().take(shared).collect();
+    if !root.has_root() {
+        return Err("The paths are on different drives".to_string());
+    }
+    Ok(root)
+}
+
+/// Whether `from` -> `to` only changes the casing of the last component, and
+/// the filesystem treats both as the same entry (Windows, macOS). On a
+/// case-sensitive filesystem this is an ordinary rename.
+pub fn is_case_only_rename(from: &Path, to: &Path) -> bool {
+    let (Some(from_name), Some(to_name)) = (from.file_name(), to.file_name()) else {
+        return false;
+    };
+    if from.parent() != to.parent() || from_name == to_name {
+        return false;
+    }
+    let (from_name, to_name) = (from_name.to_string_lossy(), to_name.to_string_lossy());
+    if from_name.to_lowercase() != to_name.to_lowercase() || !to.exists() {
+        return false;
+    }
+    // `to` exists; it is the same entry unless the folder lists it by that exact name
+    let Some(parent) = to.parent().filter(|p| !p.as_os_str().is_empty()) else {
+        return false;
+    };
+    std::fs::read_dir(parent).is_ok_and(|entries| {
+        !entries
+            .flatten()
+            .any(|entry| entry.file_name().to_string_lossy() == to_name)
+    })
+}
+
+/// The entry of `dir` whose name equals `name` apart from case, which a
+/// case-insensitive filesystem would treat as the same file. An entry with
+/// exactly that name doesn't count, and a missing `dir` has none.
+pub fn case_collision(dir: &Path, name: &str) -> std::io::Result<Option<String>> {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e),
+    };
+    let lowered = name.to_lowercase();
+    let mut collision = None;
+    for entry in entries {
+        let existing = entry?.file_name().to_string_lossy().to_string();
+        if existing == name {
+            return Ok(None);
+        }
+        if existing.to_lowercase() == lowered {
+            collision = Some(existing);
+        }
+    }
+    Ok(collision)
+}
+
+/// Renames through a temporary name, since renaming straight to a name that
+/// differs only in case can be a no-op on case-insensitive filesystems.
+pub fn rename_case_only(from: &Path, to: &Path) -> std::io::Result<()> {
+    let mut temp_name = OsString::from(".");
+    temp_name.push(from.file_name().unwrap_or_default());
+    temp_name.push(format!(".{}.rename", std::process::id()));
+    let temp = from.with_file_name(temp_name);
+
+    std::fs::rename(from, &temp)?;
+    std::fs::rename(&temp, to).inspect_err(|_| {
+        let _ = std::fs::rename(&temp, from);
+    })
+}
@@ -0,0 +1,102 @@
+// Resetting Unix permissions on a shared mod folder. Archives made on
+// another system can carry modes like 0600 that lock other users out, so
+// the game can't read the mod after it is unpacked. Windows has nothing
+// equivalent to reset.
+
+use serde::Serialize;
+use std::path::Path;
+
+#[cfg(unix)]
+const FILE_MODE: u32 = 0o644;
+#[cfg(unix)]
+const DIR_MODE: u32 = 0o755;
+
+#[derive(Debug, Serialize)]
+pub struct PermissionChange {
+    pub path: String,
+    // Octal, e.g. "600" -> "644"
+    pub from: String,
+    pub to: String,
+}
+
+pub struct Outcome {
+    pub changes: Vec<PermissionChange>,
+    // Entries that couldn't be read or changed, as "path: error"
+    pub failed: Vec<String>,
+}
+
+/// Sets every file under `root` to 0644 and every folder, `root` included,
+/// to 0755. Symlinks are left alone and not followed. With `dry_run` the
+/// changes are only listed.
+#[cfg(unix)]
+pub fn normalize(root: &Path, dry_run: bool) -> std::io::Result<Outcome> {
+    use std::fs;
+    use std::os::unix::fs::PermissionsExt;
+
+    let mut outcome = Outcome {
+        changes: Vec::new(),
+        failed: Vec::new(),
+    };
+    let metadata = fs::symlink_metadata(root)?;
+    let mut pending = vec![(root.to_path_buf(), metadata)];
+    while let Some((path, metadata)) = pending.pop() {
+        let file_type = metadata.file_type();
+        if file_type.is_symlink() {
+            continue;
+        }
+        let current = metadata.permissions().mode() & 0o7777;
+        let target = if file_type.is_dir() {
+            DIR_MODE
+        } else {
+            FILE_MODE
+        };
+        if current != target {
+            let result = if dry_run {
+                Ok(())
+            } else {
+                fs::set_permissions(&path, fs::Permissions::from_mode(target))
+            };
+            match result {
+                Ok(()) => outcome.changes.push(PermissionChange {
+                    path: path.to_string_lossy().to_string(),
+                    from: format!("{:o}", current),
+                    to: format!("{:o}", target),
+                }),
+                Err(e) => {
+                    outcome.failed.push(format!("{}: {}", path.display(), e));
+                    continue;
+                }
+            }
+        }
+
+        if file_type.is_dir() {
+            // With dry_run an unreadable folder may only become readable
+            // once fixed, so it is not reported as a failure
+            let entries = match fs::read_dir(&path) {
+                Ok(entries) => entries,
+                Err(_) if dry_run && current & 0o500 != 0o500 => continue,
+                Err(e) => {
+                    outcome.failed.push(format!("{}: {}", path.display(), e));
+                    continue;
+                }
+            };
+            for entry in entries {
+                match entry.and_then(|entry| Ok((entry.path(), entry.metadata()?))) {
+                    Ok(child) => pending.push(child),
+                    Err(e) => outcome.failed.push(format!("{}: {}", path.display(), e)),
+                }
+            }
+        }
+    }
+    outcome.changes.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(outcome)
+}
+
+#[cfg(not(unix))]
+pub fn normalize(root: &Path, _dry_run: bool) -> std::io::Result<Outcome> {
+    std::fs::metadata(root)?;
+    Ok(Outcome {
+        changes: Vec::new(),
+        failed: Vec::new(),
+    })
+}
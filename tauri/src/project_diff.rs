@@ -0,0 +1,71 @@
+// Structural views of a project's JSON: what each top-level key holds, and
+// how the top level changed between two versions.
+
+use serde::Serialize;
+use serde_json::Value;
+
+/// A value's kind ("object", "array", "string", "number", "boolean" or
+/// "null") and size: key count, element count or string length in
+/// characters. Other kinds have no size.
+pub fn shape(value: &Value) -> (&'static str, Option<usize>) {
+    match value {
+        Value::Object(o) => ("object", Some(o.len())),
+        Value::Array(a) => ("array", Some(a.len())),
+        Value::String(s) => ("string", Some(s.chars().count())),
+        Value::Number(_) => ("number", None),
+        Value::Bool(_) => ("boolean", None),
+        Value::Null => ("null", None),
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct KeyChange {
+    pub key: String,
+    // "added", "removed" or "modified"
+    pub change: &'static str,
+    // Kind and size on each side, as from `shape`; not set on the side the
+    // key is missing from
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub kind_before: Option<&'static str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub size_before: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub kind_after: Option<&'static str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub size_after: Option<usize>,
+}
+
+/// Top-level keys added, removed or changed from `old` to `new`, sorted by
+/// key. None unless both are JSON objects.
+pub fn top_level_changes(old: &Value, new: &Value) -> Option<Vec<KeyChange>> {
+    let (Value::Object(old), Value::Object(new)) = (old, new) else {
+        return None;
+    };
+    let mut keys: Vec<&String> = old.keys().chain(new.keys()).collect();
+    keys.sort();
+    keys.dedup();
+
+    let changes = keys
+        .into_iter()
+        .filter_map(|key| {
+            let (before, after) = (old.get(key), new.get(key));
+            let change = match (before, after) {
+                (Some(a), Some(b)) if a == b => return None,
+                (Some(_), Some(_)) => "modified",
+                (None, _) => "added",
+                (_, None) => "removed",
+            };
+            let (kind_before, size_before) = before.map(shape).unzip();
+            let (kind_after, size_after) = after.map(shape).unzip();
+            Some(KeyChange {
+                key: key.clone(),
+                change,
+                kind_before,
+                size_before: size_before.flatten(),
+                kind_after,
+                size_after: size_after.flatten(),
+            })
+        })
+        .collect();
+    Some(changes)
+}
@@ -0,0 +1,304 @@
+// Finding and rewriting script include paths.
+//
+// Scripts pull each other in with directives like `#include "path"` or
+// `IncludeFile( "path" )`, and `.rson` script lists name one path per line.
+// Paths may be relative to the mod root or to `scripts/vscripts`, and may use
+// either slash style.
+
+use crate::export::{self, ExportFile};
+use crate::squirrel::{self, TokenKind};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+const INCLUDE_DIRECTIVES: &[&str] = &["#include", "IncludeFile", "IncludeScript", "require"];
+
+// Folder script paths are resolved against by the game
+const SCRIPTS_ROOT: &str = "scripts/vscripts";
+
+// Larger files are assets rather than scripts or lists, so they aren't read
+// for references
+const MAX_REFERENCING_FILE_BYTES: u64 = 8 * 1024 * 1024;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ReferenceChange {
+    // Mod-relative path of the file containing the reference
+    pub file: String,
+    // 1-based
+    pub line: usize,
+    pub old: String,
+    pub new: String,
+}
+
+// `relative` as written relative to the scripts root, when it lies inside it
+fn scripts_relative(relative: &str) -> Option<&str> {
+    relative.strip_prefix(SCRIPTS_ROOT)?.strip_prefix('/')
+}
+
+// The (old, new) pairs a rename rewrites, one per form `old` can be
+// referenced by: relative to the mod root, and relative to the scripts root
+// when it lies inside it. A scripts-relative reference moved out of the
+// scripts root becomes mod-relative, which resolves just the same.
+fn rename_pairs(old: &str, new: &str) -> Vec<(String, String)> {
+    let mut pairs = vec![(old.to_string(), new.to_string())];
+    if let Some(short_old) = scripts_relative(old) {
+        let short_new = scripts_relative(new).unwrap_or(new);
+        pairs.push((short_old.to_string(), short_new.to_string()));
+    }
+    pairs
+}
+
+// Whether each line of `content` holds an include: a directive written as
+// code, not inside a comment or string, followed by a path or an opening
+// parenthesis. Indexed like `content.split('\n')`.
+fn include_lines(content: &str) -> Vec<bool> {
+    let mut lines = vec![false; content.split('\n').count()];
+    let tokens = squirrel::tokenize(content);
+    for pair in tokens.windows(2) {
+        let (directive, next) = (pair[0], pair[1]);
+        let is_directive = matches!(directive.kind, TokenKind::Keyword | TokenKind::Identifier)
+            && INCLUDE_DIRECTIVES.contains(&&content[directive.start..directive.end]);
+        let takes_argument =
+            next.kind == TokenKind::String || &content[next.start..next.end] == "(";
+        if is_directive && takes_argument {
+            let line = content[..directive.start].matches('\n').count();
+            lines[line] = true;
+        }
+    }
+    lines
+}
+
+// Rewrites one referenced path if it is `old` or lies under it, keeping the
+// separator style it was written with (`/`, `\` or an escaped `\\`)
+fn rewrite_path(path: &str, old: &str, new: &str) -> Option<String> {
+    let separator = if path.contains("\\\\") {
+        "\\\\"
+    } else if path.contains('\\') {
+        "\\"
+    } else {
+        "/"
+    };
+    let normalized = path.replace(separator, "/");
+    let head = normalized.get(..old.len())?;
+    let rest = &normalized[old.len()..];
+    if !head.eq_ignore_ascii_case(old) || !(rest.is_empty() || rest.starts_with('/')) {
+        return None;
+    }
+    Some(format!("{}{}", new, rest).replace('/', separator))
+}
+
+// A bare path entry in a script list
+fn rson_path(line: &str) -> Option<&str> {
+    let path = line.trim();
+    if path.is_empty() || path.contains(|c: char| c.is_whitespace() || "{}[]:\"".contains(c)) {
+        return None;
+    }
+    Some(path)
+}
+
+fn is_rson(file: &str) -> bool {
+    Path::new(file)
+        .extension()
+        .is_some_and(|e| e.eq_ignore_ascii_case("rson"))
+}
+
+fn rewrite_line(
+    line: &str,
+    is_rson: bool,
+    is_include: bool,
+    renames: &[(String, String)],
+) -> Option<String> {
+    if is_rson {
+        let path = rson_path(line)?;
+        let rewritten = renames
+            .iter()
+            .find_map(|(old, new)| rewrite_path(path, old, new))?;
+        return Some(line.replacen(path, &rewritten, 1));
+    }
+
+    if !is_include {
+        return None;
+    }
+    let mut result = String::with_capacity(line.len());
+    let mut changed = false;
+    let mut rest = line;
+    while let Some(open) = rest.find('"') {
+        let Some(close) = rest[open + 1..].find('"') else {
+            break;
+        };
+        let literal = &rest[open + 1..open + 1 + close];
+        result.push_str(&rest[..=open]);
+        match renames
+            .iter()
+            .find_map(|(old, new)| rewrite_path(literal, old, new))
+        {
+            Some(rewritten) => {
+                result.push_str(&rewritten);
+                changed = true;
+            }
+            None => result.push_str(literal),
+        }
+        result.push('"');
+        rest = &rest[open + close + 2..];
+    }
+    result.push_str(rest);
+    changed.then_some(result)
+}
+
+/// Rewrites references to renamed files or folders in one file's content.
+/// `renames` holds (old, new) pairs, mod-relative with forward slashes; a
+/// folder rename also covers everything under it, and the first matching
+/// pair wins. Returns the new content and the changes made, or None when
+/// nothing referenced the renamed paths.
+pub fn rewrite_references(
+    file: &str,
+    content: &str,
+    renames: &[(String, String)],
+) -> Option<(String, Vec<ReferenceChange>)> {
+    let renames: Vec<(String, String)> = renames
+        .iter()
+        .flat_map(|(old, new)| rename_pairs(old, new))
+        .collect();
+    let is_rson = is_rson(file);
+    let includes = if is_rson {
+        Vec::new()
+    } else {
+        include_lines(content)
+    };
+
+    let mut changes = Vec::new();
+    let mut lines = Vec::new();
+    for (index, line) in content.split('\n').enumerate() {
+        let is_include = includes.get(index).copied().unwrap_or(false);
+        match rewrite_line(line, is_rson, is_include, &renames) {
+            Some(rewritten) => {
+                changes.push(ReferenceChange {
+                    file: file.to_string(),
+                    line: index + 1,
+                    old: line.trim().to_string(),
+                    new: rewritten.trim().to_string(),
+                });
+                lines.push(rewritten);
+            }
+            None => lines.push(line.to_string()),
+        }
+    }
+
+    if changes.is_empty() {
+        None
+    } else {
+        Some((lines.join("\n"), changes))
+    }
+}
+
+// Every path `content` references, with forward slashes and as written
+// otherwise, so it may be relative to the mod root or to the scripts root.
+// `file` is the referencing file's name, which decides how it is read.
+fn referenced_paths(file: &str, content: &str) -> Vec<String> {
+    let normalize = |path: &str| {
+        path.replace("\\\\", "/")
+            .replace('\\', "/")
+            .trim_start_matches("./")
+            .trim_start_matches('/')
+            .to_string()
+    };
+    if is_rson(file) {
+        return content
+            .lines()
+            .filter_map(rson_path)
+            .map(normalize)
+            .collect();
+    }
+    let mut paths = Vec::new();
+    for (line, include) in content.split('\n').zip(include_lines(content)) {
+        if !include {
+            continue;
+        }
+        // Every second piece between quotes is a literal
+        paths.extend(line.split('"').skip(1).step_by(2).map(normalize));
+    }
+    paths.retain(|path| !path.is_empty());
+    paths
+}
+
+// The mod-relative paths a referenced path may stand for
+fn resolve_forms(referenced: &str) -> [String; 2] {
+    [
+        referenced.to_string(),
+        format!("{}/{}", SCRIPTS_ROOT, referenced),
+    ]
+}
+
+/// A mod's files and the references between them.
+pub struct IncludeGraph {
+    // The mod's files as export collects them, sorted by relative path
+    pub files: Vec<ExportFile>,
+    // Lowercased relative path -> index into `files`
+    pub index: HashMap<String, usize>,
+    // Indices each file pulls in, in the order it names them
+    pub edges: Vec<Vec<usize>>,
+}
+
+impl IncludeGraph {
+    /// The file `relative` names, matched case-insensitively.
+    pub fn find(&self, relative: &str) -> Option<usize> {
+        self.index.get(&relative.to_lowercase()).copied()
+    }
+
+    // Every file a referenced path stands for: for each form, the file it
+    // names, or else everything under the folder it names
+    fn resolve(&self, referenced: &str) -> Vec<usize> {
+        let mut targets = Vec::new();
+        for form in resolve_forms(referenced) {
+            if let Some(target) = self.find(&form) {
+                targets.push(target);
+                continue;
+            }
+            let folder = format!("{}/", form.trim_end_matches('/').to_lowercase());
+            targets.extend(
+                self.files
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, file)| file.relative.to_lowercase().starts_with(&folder))
+                    .map(|(i, _)| i),
+            );
+        }
+        targets
+    }
+}
+
+/// Builds the reference graph of the files under `mod_root`. Files
+/// `.r5vignore` lists are left out. Every form of a referenced path is
+/// resolved, case-insensitively as the game's lookups are, and a reference to
+/// a folder reaches everything under it. Binary and undecodable files
+/// reference nothing.
+pub fn include_graph(mod_root: &Path) -> io::Result<IncludeGraph> {
+    let files = export::collect_mod_files(mod_root)?;
+    let index = files
+        .iter()
+        .enumerate()
+        .map(|(i, file)| (file.relative.to_lowercase(), i))
+        .collect();
+    let mut graph = IncludeGraph {
+        files,
+        index,
+        edges: Vec::new(),
+    };
+    graph.edges = graph
+        .files
+        .iter()
+        .map(|file| {
+            if file.size > MAX_REFERENCING_FILE_BYTES {
+                return Vec::new();
+            }
+            let content = fs::read_to_string(&file.path).unwrap_or_default();
+            referenced_paths(&file.relative, &content)
+                .iter()
+                .flat_map(|referenced| graph.resolve(referenced))
+                .collect()
+        })
+        .collect();
+    Ok(graph)
+}
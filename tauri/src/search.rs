@@ -0,0 +1,146 @@
+// Text search across a mod's files.
+
+use crate::file_type::{self, FileKind};
+use crate::ignore::IgnoreRules;
+use regex::{Regex, RegexBuilder};
+use serde::Serialize;
+use std::fs;
+use std::path::Path;
+
+// Lines longer than this are clipped in results so minified files don't
+// flood the preview
+const MAX_PREVIEW_CHARS: usize = 400;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchMatch {
+    pub path: String,
+    // 1-based line, and 0-based character column of the match start
+    pub line: usize,
+    pub column: usize,
+    pub length: usize,
+    pub text: String,
+    pub context_before: Vec<String>,
+    pub context_after: Vec<String>,
+}
+
+/// Which files a search reads. Everything but the file kind is decided from
+/// the path, so files outside the scope are never opened.
+#[derive(Default)]
+pub struct SearchScope {
+    kinds: Vec<FileKind>,
+    extensions: Vec<String>,
+    include: Option<IgnoreRules>,
+    exclude: IgnoreRules,
+}
+
+impl SearchScope {
+    /// `file_types` holds kind names ("script", "text") or extensions
+    /// ("json", ".nut"); a file matching any of them is searched. Globs use
+    /// `.r5vignore` syntax; with `include` only matching files are searched.
+    pub fn new(file_types: &[String], include: &[String], exclude: &[String]) -> Self {
+        let mut scope = SearchScope::default();
+        for file_type in file_types {
+            let file_type = file_type.trim().to_lowercase();
+            match FileKind::from_name(&file_type) {
+                Some(kind) => scope.kinds.push(kind),
+                None => scope
+                    .extensions
+                    .push(file_type.trim_start_matches('.').to_string()),
+            }
+        }
+        if !include.is_empty() {
+            scope.include = Some(IgnoreRules::parse(&include.join("\n")));
+        }
+        scope.exclude = IgnoreRules::parse(&exclude.join("\n"));
+        scope
+    }
+
+    /// `relative` is the file's slash-separated path from the search root.
+    /// Only text files are ever searched, whatever the scope.
+    pub fn allows(&self, path: &Path, relative: &str) -> bool {
+        if self.exclude.is_file_ignored(relative) {
+            return false;
+        }
+        // Include globs are matched with the same rules, so "ignored" here
+        // means "listed"
+        if self
+            .include
+            .as_ref()
+            .is_some_and(|include| !include.is_file_ignored(relative))
+        {
+            return false;
+        }
+        let extension = path
+            .extension()
+            .map(|e| e.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        let by_extension = self.extensions.contains(&extension);
+        // An extension list alone settles it without sniffing the file
+        if self.kinds.is_empty() && !self.extensions.is_empty() && !by_extension {
+            return false;
+        }
+        let kind = file_type::detect_file_type(path);
+        let unfiltered = self.kinds.is_empty() && self.extensions.is_empty();
+        kind.is_text() && (unfiltered || by_extension || self.kinds.contains(&kind))
+    }
+}
+
+/// Compiles the query; plain queries match literally.
+pub fn build_pattern(query: &str, is_regex: bool, case_sensitive: bool) -> Result<Regex, String> {
+    if query.is_empty() {
+        return Err("Search query is empty".to_string());
+    }
+    let source = if is_regex {
+        query.to_string()
+    } else {
+        regex::escape(query)
+    };
+    RegexBuilder::new(&source)
+        .case_insensitive(!case_sensitive)
+        .build()
+        .map_err(|e| format!("Invalid search pattern: {}", e))
+}
+
+fn preview(line: &str) -> String {
+    let line = line.trim_end_matches('\r');
+    match line.char_indices().nth(MAX_PREVIEW_CHARS) {
+        Some((cut, _)) => format!("{}…", &line[..cut]),
+        None => line.to_string(),
+    }
+}
+
+/// Every match in one file, with `context` lines around each. Files that
+/// aren't valid UTF-8 give no matches.
+pub fn search_file(
+    path: &Path,
+    display_path: &str,
+    pattern: &Regex,
+    context: usize,
+) -> Vec<SearchMatch> {
+    let Ok(content) = fs::read_to_string(path) else {
+        return Vec::new();
+    };
+    let lines: Vec<&str> = content.lines().collect();
+
+    let mut matches = Vec::new();
+    for (index, line) in lines.iter().enumerate() {
+        for found in pattern.find_iter(line) {
+            matches.push(SearchMatch {
+                path: display_path.to_string(),
+                line: index + 1,
+                column: line[..found.start()].chars().count(),
+                length: found.as_str().chars().count(),
+                text: preview(line),
+                context_before: lines[index.saturating_sub(context)..index]
+                    .iter()
+                    .map(|l| preview(l))
+                    .collect(),
+                context_after: lines[index + 1..(index + 1 + context).min(lines.len())]
+                    .iter()
+                    .map(|l| preview(l))
+                    .collect(),
+            });
+        }
+    }
+    matches
+}
@@ -0,0 +1,323 @@
+// Lightweight squirrel lexer and source utilities.
+//
+// This is not a parser: it only knows enough about squirrel to tell code
+// apart from strings and comments, which is all the formatter needs.
+
+use serde::Serialize;
+
+const KEYWORDS: &[&str] = &[
+    "array", "asset", "bool", "break", "case", "catch", "class", "clone", "const", "constructor",
+    "continue", "default", "delaythread", "delegate", "delete", "else", "entity", "enum",
+    "expect", "extends", "false", "float", "for", "foreach", "function", "functionref", "global",
+    "globalize_all_functions", "if", "in", "instanceof", "int", "local", "null", "ornull",
+    "resume", "return", "static", "string", "struct", "switch", "table", "this", "thread",
+    "throw", "true", "try", "typeof", "unreachable", "untyped", "var", "vector", "void", "wait",
+    "waitthread", "waitthreadsolo", "while", "yield",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TokenKind {
+    Keyword,
+    Identifier,
+    Number,
+    String,
+    Comment,
+    Punctuation,
+}
+
+/// A token as a byte range into the source.
+#[derive(Debug, Clone, Copy)]
+pub struct Token {
+    pub start: usize,
+    pub end: usize,
+    pub kind: TokenKind,
+}
+
+/// Splits squirrel source into tokens, skipping whitespace. Unterminated
+/// strings and comments run to the end of the line (or file, for block
+/// comments and verbatim strings) rather than failing.
+pub fn tokenize(source: &str) -> Vec<Token> {
+    let bytes = source.as_bytes();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < bytes.len() {
+        let c = bytes[i];
+        let start = i;
+
+        let kind = if c.is_ascii_whitespace() {
+            i += 1;
+            continue;
+        } else if c == b'/' && bytes.get(i + 1) == Some(&b'/') {
+            i = line_end(bytes, i);
+            TokenKind::Comment
+        } else if c == b'/' && bytes.get(i + 1) == Some(&b'*') {
+            i = match source[i + 2..].find("*/") {
+                Some(pos) => i + 2 + pos + 2,
+                None => bytes.len(),
+            };
+            TokenKind::Comment
+        } else if c == b'@' && bytes.get(i + 1) == Some(&b'"') {
+            // Verbatim string: may span lines, `""` is an escaped quote
+            i += 2;
+            loop {
+                match bytes.get(i) {
+                    None => break,
+                    Some(b'"') if bytes.get(i + 1) == Some(&b'"') => i += 2,
+                    Some(b'"') => {
+                        i += 1;
+                        break;
+                    }
+                    Some(_) => i += 1,
+                }
+            }
+            TokenKind::String
+        } else if c == b'"' || c == b'\'' {
+            i += 1;
+            loop {
+                match bytes.get(i) {
+                    None | Some(b'\n') => break,
+                    Some(b'\\') => i += 2,
+                    Some(&q) if q == c => {
+                        i += 1;
+                        break;
+                    }
+                    Some(_) => i += 1,
+                }
+            }
+            i = i.min(bytes.len());
+            TokenKind::String
+        } else if c.is_ascii_digit() {
+            i += 1;
+            while i < bytes.len() {
+                let d = bytes[i];
+                let exponent_sign = (d == b'+' || d == b'-')
+                    && matches!(bytes[i - 1], b'e' | b'E')
+                    && !source[start..i].starts_with("0x");
+                if d.is_ascii_alphanumeric() || d == b'.' || exponent_sign {
+                    i += 1;
+                } else {
+                    break;
+                }
+            }
+            TokenKind::Number
+        } else if c.is_ascii_alphabetic()
+            || c == b'_'
+            || (c == b'#' && is_ident_start(bytes.get(i + 1)))
+        {
+            // `#if`/`#endif` style directives lex as keywords
+            i += 1;
+            while i < bytes.len() && (bytes[i].is_ascii_alphanumeric() || bytes[i] == b'_') {
+                i += 1;
+            }
+            if c == b'#' || KEYWORDS.contains(&&source[start..i]) {
+                TokenKind::Keyword
+            } else {
+                TokenKind::Identifier
+            }
+        } else {
+            // Advance a whole UTF-8 character so token ranges stay on char boundaries
+            i += source[i..].chars().next().map(char::len_utf8).unwrap_or(1);
+            TokenKind::Punctuation
+        };
+
+        tokens.push(Token { start, end: i, kind });
+    }
+
+    tokens
+}
+
+/// A token for highlighting: (offset, length, kind), counted in UTF-16 code
+/// units so the frontend can index its JavaScript string directly.
+pub type HighlightToken = (usize, usize, TokenKind);
+
+/// The tokens of `source` worth highlighting, which is all but punctuation.
+pub fn highlight_tokens(source: &str) -> Vec<HighlightToken> {
+    let mut highlights = Vec::new();
+    // Byte and UTF-16 positions advance together; tokens come in order
+    let (mut byte, mut unit) = (0, 0);
+    let mut advance = |to: usize| {
+        unit += source[byte..to].encode_utf16().count();
+        byte = to;
+        unit
+    };
+    for token in tokenize(source) {
+        if token.kind == TokenKind::Punctuation {
+            continue;
+        }
+        let start = advance(token.start);
+        let end = advance(token.end);
+        highlights.push((start, end - start, token.kind));
+    }
+    highlights
+}
+
+fn is_ident_start(c: Option<&u8>) -> bool {
+    matches!(c, Some(c) if c.is_ascii_alphabetic() || *c == b'_')
+}
+
+fn line_end(bytes: &[u8], from: usize) -> usize {
+    bytes[from..]
+        .iter()
+        .position(|&b| b == b'\n')
+        .map(|pos| from + pos)
+        .unwrap_or(bytes.len())
+}
+
+fn is_opener(source: &str, token: &Token) -> bool {
+    token.kind == TokenKind::Punctuation
+        && matches!(&source[token.start..token.end], "{" | "(" | "[")
+}
+
+fn is_closer(source: &str, token: &Token) -> bool {
+    token.kind == TokenKind::Punctuation
+        && matches!(&source[token.start..token.end], "}" | ")" | "]")
+}
+
+/// Re-indents squirrel source by bracket depth, strips trailing whitespace
+/// and ensures exactly one trailing newline.
+///
+/// Lines that start inside a block comment or verbatim string keep their
+/// leading whitespace, and lines that end inside one keep their trailing
+/// whitespace, so literal content is never altered. Only whitespace at the
+/// edges of lines changes, which keeps the result idempotent.
+pub fn format_source(source: &str, indent_unit: &str) -> String {
+    let tokens = tokenize(source);
+    let newline = if source.contains("\r\n") { "\r\n" } else { "\n" };
+
+    let mut lines: Vec<String> = Vec::new();
+    let mut depth: usize = 0;
+    let mut next_token = 0;
+    let mut line_start = 0;
+
+    for raw_line in source.split('\n') {
+        let line_end = line_start + raw_line.len();
+        let line = raw_line.strip_suffix('\r').unwrap_or(raw_line);
+
+        // Skip tokens that finished before this line
+        while next_token < tokens.len() && tokens[next_token].end <= line_start {
+            next_token += 1;
+        }
+        let starts_inside = tokens
+            .get(next_token)
+            .is_some_and(|t| t.start < line_start);
+
+        let line_tokens: Vec<&Token> = tokens[next_token..]
+            .iter()
+            .take_while(|t| t.start < line_end)
+            .filter(|t| t.start >= line_start)
+            .collect();
+        let ends_inside = tokens[next_token..]
+            .iter()
+            .take_while(|t| t.start <= line_end)
+            .any(|t| t.end > line_end && line_end < source.len());
+
+        let body = if ends_inside { line } else { line.trim_end() };
+        let formatted = if starts_inside {
+            body.to_string()
+        } else {
+            let body = body.trim_start();
+            if body.is_empty() {
+                String::new()
+            } else {
+                let leading_closers = line_tokens
+                    .iter()
+                    .take_while(|t| is_closer(source, t))
+                    .count();
+                indent_unit.repeat(depth.saturating_sub(leading_closers)) + body
+            }
+        };
+        lines.push(formatted);
+
+        for token in &line_tokens {
+            if is_opener(source, token) {
+                depth += 1;
+            } else if is_closer(source, token) {
+                depth = depth.saturating_sub(1);
+            }
+        }
+
+        line_start = line_end + 1;
+    }
+
+    while lines.last().is_some_and(|l| l.is_empty()) {
+        lines.pop();
+    }
+    if lines.is_empty() {
+        return String::new();
+    }
+
+    let mut formatted = lines.join(newline);
+    formatted.push_str(newline);
+    formatted
+}
+
+#[derive(Debug, Serialize)]
+pub struct BraceIssue {
+    // "unclosed", "unexpected" (a closer with nothing open) or "mismatched"
+    pub kind: String,
+    pub bracket: String,
+    // The closer that would have matched, for "mismatched" and "unclosed"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expected: Option<String>,
+    // 1-based, column counted in characters
+    pub line: usize,
+    pub column: usize,
+}
+
+fn closer_for(opener: &str) -> &'static str {
+    match opener {
+        "{" => "}",
+        "(" => ")",
+        _ => "]",
+    }
+}
+
+fn line_column(source: &str, offset: usize) -> (usize, usize) {
+    let before = &source[..offset];
+    let line_start = before.rfind('\n').map_or(0, |pos| pos + 1);
+    (
+        before.matches('\n').count() + 1,
+        before[line_start..].chars().count() + 1,
+    )
+}
+
+/// Finds the first bracket that breaks `{}`/`()`/`[]` nesting, ignoring
+/// strings and comments. At the end of the source the innermost bracket
+/// still open is reported. None when everything balances.
+pub fn check_brace_balance(source: &str) -> Option<BraceIssue> {
+    let issue = |kind: &str, token: &Token, expected: Option<&str>| {
+        let (line, column) = line_column(source, token.start);
+        BraceIssue {
+            kind: kind.to_string(),
+            bracket: source[token.start..token.end].to_string(),
+            expected: expected.map(str::to_string),
+            line,
+            column,
+        }
+    };
+
+    let mut open: Vec<Token> = Vec::new();
+    for token in tokenize(source) {
+        if is_opener(source, &token) {
+            open.push(token);
+        } else if is_closer(source, &token) {
+            let closer = &source[token.start..token.end];
+            match open.pop() {
+                None => return Some(issue("unexpected", &token, None)),
+                Some(opener) => {
+                    let expected = closer_for(&source[opener.start..opener.end]);
+                    if closer != expected {
+                        return Some(issue("mismatched", &token, Some(expected)));
+                    }
+                }
+            }
+        }
+    }
+
+    open.last().map(|opener| {
+        let expected = closer_for(&source[opener.start..opener.end]);
+        issue("unclosed", opener, Some(expected))
+    })
+}
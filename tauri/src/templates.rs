@@ -0,0 +1,144 @@
+// Mod templates: folders of starting files kept in the app's data directory,
+// each described by a `template.json`. A template is shared as a single
+// bundle holding the descriptor and every file, base64-encoded in JSON.
+
+use crate::{mod_identity, paths};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub const TEMPLATES_DIR_NAME: &str = "templates";
+pub const DESCRIPTOR_FILE_NAME: &str = "template.json";
+
+// Bumped when the bundle layout changes incompatibly
+const BUNDLE_FORMAT: u32 = 1;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TemplateDescriptor {
+    // Also the template's folder name
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub version: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct BundleFile {
+    // Forward-slash path relative to the template folder
+    path: String,
+    data: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Bundle {
+    format: u32,
+    descriptor: TemplateDescriptor,
+    files: Vec<BundleFile>,
+}
+
+fn validate(descriptor: &TemplateDescriptor) -> Result<(), String> {
+    // Names become folder names, under the same rules as mod ids
+    mod_identity::validate_mod_id(&descriptor.name)
+        .map_err(|e| format!("Invalid template name {:?}: {}", descriptor.name, e))
+}
+
+/// Reads and checks the descriptor of the template in `dir`.
+pub fn load_descriptor(dir: &Path) -> Result<TemplateDescriptor, String> {
+    let path = dir.join(DESCRIPTOR_FILE_NAME);
+    let content = fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let descriptor: TemplateDescriptor = serde_json::from_str(&content)
+        .map_err(|e| format!("Invalid {}: {}", DESCRIPTOR_FILE_NAME, e))?;
+    validate(&descriptor)?;
+    Ok(descriptor)
+}
+
+/// Bundles the template in `dir` as JSON. Hidden files are left out.
+pub fn bundle(dir: &Path) -> Result<String, String> {
+    let descriptor = load_descriptor(dir)?;
+    let mut files = Vec::new();
+    for path in paths::walk_files(dir).map_err(|e| e.to_string())? {
+        let Some(relative) = paths::relative_slash_path(dir, &path) else {
+            continue;
+        };
+        if relative == DESCRIPTOR_FILE_NAME {
+            continue;
+        }
+        let data = fs::read(&path).map_err(|e| format!("Failed to read {}: {}", relative, e))?;
+        files.push(BundleFile {
+            path: relative,
+            data: BASE64.encode(data),
+        });
+    }
+    files.sort_by(|a, b| a.path.cmp(&b.path));
+    serde_json::to_string(&Bundle {
+        format: BUNDLE_FORMAT,
+        descriptor,
+        files,
+    })
+    .map_err(|e| e.to_string())
+}
+
+fn write_files(
+    dir: &Path,
+    files: &[BundleFile],
+    descriptor: &TemplateDescriptor,
+) -> Result<(), String> {
+    fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    for file in files {
+        // Bundles come from elsewhere, so no entry may point outside
+        let path = paths::resolve_in_root(dir, &file.path)?;
+        let data = BASE64
+            .decode(&file.data)
+            .map_err(|e| format!("Corrupt data for {}: {}", file.path, e))?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        fs::write(&path, data).map_err(|e| format!("Failed to write {}: {}", file.path, e))?;
+    }
+    let descriptor_json = serde_json::to_string_pretty(descriptor).map_err(|e| e.to_string())?;
+    fs::write(dir.join(DESCRIPTOR_FILE_NAME), descriptor_json).map_err(|e| e.to_string())
+}
+
+/// Installs a bundle as `templates_root/<name>`, with `rename` replacing the
+/// bundle's own name. An installed template of the same name is never
+/// replaced: the error then starts with "Exists:" so the caller can ask for
+/// another name.
+pub fn install(
+    bundle: &str,
+    templates_root: &Path,
+    rename: Option<&str>,
+) -> Result<(TemplateDescriptor, PathBuf), String> {
+    let bundle: Bundle =
+        serde_json::from_str(bundle).map_err(|e| format!("Not a template bundle: {}", e))?;
+    if bundle.format > BUNDLE_FORMAT {
+        return Err("The template was exported by a newer version of the studio".to_string());
+    }
+    let mut descriptor = bundle.descriptor;
+    if let Some(name) = rename {
+        descriptor.name = name.to_string();
+    }
+    validate(&descriptor)?;
+
+    let target = templates_root.join(&descriptor.name);
+    if target.exists() {
+        return Err(format!(
+            "Exists: a template named {} is already installed",
+            descriptor.name
+        ));
+    }
+
+    // Written to a staging folder so a bad entry leaves nothing behind
+    let staging = templates_root.join(format!(".{}.importing", descriptor.name));
+    let _ = fs::remove_dir_all(&staging);
+    let result = write_files(&staging, &bundle.files, &descriptor)
+        .and_then(|_| fs::rename(&staging, &target).map_err(|e| e.to_string()));
+    if let Err(e) = result {
+        let _ = fs::remove_dir_all(&staging);
+        return Err(e);
+    }
+    Ok((descriptor, target))
+}
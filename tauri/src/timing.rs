@@ -0,0 +1,117 @@
+// Opt-in timing of backend commands for the performance panel.
+//
+// When enabled, command results carry an `elapsed_ms` field and the most
+// recent durations are kept per command name. When disabled, commands run
+// untouched and nothing is recorded.
+
+use serde::Serialize;
+use std::collections::{BTreeMap, VecDeque};
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::Instant;
+use tauri::{AppHandle, Manager};
+
+// Samples kept per command; older ones are dropped
+const MAX_SAMPLES: usize = 100;
+
+pub struct Timing {
+    enabled: AtomicBool,
+    samples: Mutex<BTreeMap<&'static str, VecDeque<u64>>>,
+}
+
+/// A command result with its duration flattened alongside its fields.
+#[derive(Debug, Serialize)]
+pub struct Timed<T> {
+    #[serde(flatten)]
+    pub result: T,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub elapsed_ms: Option<u64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CommandStats {
+    pub command: String,
+    pub count: usize,
+    pub last_ms: u64,
+    pub min_ms: u64,
+    pub max_ms: u64,
+    pub avg_ms: u64,
+}
+
+impl Timing {
+    pub fn new(enabled: bool) -> Self {
+        Timing {
+            enabled: AtomicBool::new(enabled),
+            samples: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::Relaxed)
+    }
+
+    /// Turning timing off also forgets what was recorded.
+    pub fn set_enabled(&self, enabled: bool) {
+        self.enabled.store(enabled, Ordering::Relaxed);
+        if !enabled {
+            self.samples
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .clear();
+        }
+    }
+
+    fn record(&self, command: &'static str, elapsed_ms: u64) {
+        let mut samples = self.samples.lock().unwrap_or_else(|e| e.into_inner());
+        let recent = samples.entry(command).or_default();
+        if recent.len() == MAX_SAMPLES {
+            recent.pop_front();
+        }
+        recent.push_back(elapsed_ms);
+    }
+
+    /// Aggregates the recent samples of every command, sorted by name.
+    pub fn stats(&self) -> Vec<CommandStats> {
+        let samples = self.samples.lock().unwrap_or_else(|e| e.into_inner());
+        samples
+            .iter()
+            .filter(|(_, recent)| !recent.is_empty())
+            .map(|(command, recent)| CommandStats {
+                command: command.to_string(),
+                count: recent.len(),
+                last_ms: recent.back().copied().unwrap_or(0),
+                min_ms: recent.iter().copied().min().unwrap_or(0),
+                max_ms: recent.iter().copied().max().unwrap_or(0),
+                avg_ms: recent.iter().sum::<u64>() / recent.len() as u64,
+            })
+            .collect()
+    }
+}
+
+/// Runs a command body, timing it when timing is enabled.
+pub async fn measure<T, F>(app: AppHandle, command: &'static str, work: F) -> Timed<T>
+where
+    F: Future<Output = T>,
+{
+    let enabled = app
+        .try_state::<Timing>()
+        .is_some_and(|timing| timing.is_enabled());
+    if !enabled {
+        return Timed {
+            result: work.await,
+            elapsed_ms: None,
+        };
+    }
+
+    let started = Instant::now();
+    let result = work.await;
+    let elapsed_ms = started.elapsed().as_millis() as u64;
+    if let Some(timing) = app.try_state::<Timing>() {
+        timing.record(command, elapsed_ms);
+    }
+    Timed {
+        result,
+        elapsed_ms: Some(elapsed_ms),
+    }
+}
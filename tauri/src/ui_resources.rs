@@ -0,0 +1,176 @@
+// Assets referenced from a mod's UI declaration files: the KeyValues `.res`
+// and `.menu` files that lay out panels and declare fonts.
+//
+// A value counts as an asset reference when its key names one (`image`,
+// `font` files and the like) or when it ends in an asset extension. Images
+// are looked up the way the engine does, under `materials/` and
+// `materials/vgui/` and with `.vmt`/`.vtf` added when the extension is left
+// off; `#base` includes are relative to the file declaring them.
+
+use crate::{paths, vdf};
+use serde::Serialize;
+use std::fs;
+use std::path::Path;
+
+const DECLARATION_EXTENSIONS: &[&str] = &["res", "menu"];
+
+// Keys whose value is always an asset path
+const IMAGE_KEYS: &[&str] = &["image", "fillimage", "icon", "texture", "material"];
+const FILE_KEYS: &[&str] = &["file", "fontfile", "#base"];
+
+const ASSET_EXTENSIONS: &[&str] = &["ttf", "otf", "vtf", "vmt", "png", "dds", "tga", "res"];
+
+const IMAGE_ROOTS: &[&str] = &["", "materials/", "materials/vgui/"];
+const IMAGE_EXTENSIONS: &[&str] = &["", ".vmt", ".vtf"];
+
+#[derive(Debug, Serialize)]
+pub struct UiResource {
+    // Mod-relative declaration file
+    pub file: String,
+    // Path of keys down to the value, e.g. "Scheme/CustomFontFiles/1"
+    pub key: String,
+    pub reference: String,
+    // Mod-relative file it resolved to; not set when missing
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resolved: Option<String>,
+}
+
+pub struct Scan {
+    pub found: Vec<UiResource>,
+    pub missing: Vec<UiResource>,
+    // Declaration files that didn't parse, as "path: error"
+    pub failed: Vec<String>,
+}
+
+fn is_declaration(path: &Path) -> bool {
+    path.extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .is_some_and(|e| DECLARATION_EXTENSIONS.contains(&e.as_str()))
+}
+
+fn has_asset_extension(value: &str) -> bool {
+    Path::new(value)
+        .extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .is_some_and(|e| ASSET_EXTENSIONS.contains(&e.as_str()))
+}
+
+// Resolves `.` and `..` segments; a path climbing above the root keeps its
+// `..` and so matches nothing in the mod
+fn normalize_relative(path: &str) -> String {
+    let mut segments: Vec<&str> = Vec::new();
+    for segment in path.split('/') {
+        match segment {
+            "" | "." => {}
+            ".." if segments.last().is_some_and(|s| *s != "..") => {
+                segments.pop();
+            }
+            _ => segments.push(segment),
+        }
+    }
+    segments.join("/")
+}
+
+// The mod-relative paths a reference may mean, most likely first
+fn candidates(key: &str, reference: &str, declaring_dir: &str) -> Vec<String> {
+    let reference = reference.replace('\\', "/");
+    let reference = reference.trim_start_matches('/');
+    if key == "#base" {
+        let joined = format!("{}/{}", declaring_dir, reference);
+        return vec![normalize_relative(&joined)];
+    }
+    if IMAGE_KEYS.contains(&key) {
+        return IMAGE_ROOTS
+            .iter()
+            .flat_map(|root| {
+                IMAGE_EXTENSIONS
+                    .iter()
+                    .map(move |ext| normalize_relative(&format!("{}{}{}", root, reference, ext)))
+            })
+            .collect();
+    }
+    vec![normalize_relative(reference)]
+}
+
+fn collect_references(
+    value: &vdf::Value,
+    key_path: &str,
+    references: &mut Vec<(String, String, String)>,
+) {
+    for (key, child) in value.entries() {
+        let child_path = if key_path.is_empty() {
+            key.clone()
+        } else {
+            format!("{}/{}", key_path, key)
+        };
+        match child {
+            vdf::Value::Block(_) => collect_references(child, &child_path, references),
+            vdf::Value::Str(text) => {
+                let lowered = key.to_lowercase();
+                let is_asset = IMAGE_KEYS.contains(&lowered.as_str())
+                    || FILE_KEYS.contains(&lowered.as_str())
+                    || has_asset_extension(text);
+                if is_asset && !text.trim().is_empty() {
+                    references.push((lowered, child_path, text.clone()));
+                }
+            }
+        }
+    }
+}
+
+/// Scans every UI declaration file under `mod_root` and resolves the assets
+/// they reference. Hidden files are skipped. Lookups are case-insensitive,
+/// as they are in game.
+pub fn scan(mod_root: &Path) -> std::io::Result<Scan> {
+    let files = paths::walk_files(mod_root)?;
+    let on_disk: std::collections::HashMap<String, String> = files
+        .iter()
+        .filter_map(|path| paths::relative_slash_path(mod_root, path))
+        .map(|relative| (relative.to_lowercase(), relative))
+        .collect();
+
+    let mut declarations: Vec<_> = files
+        .iter()
+        .filter(|path| is_declaration(path))
+        .filter_map(|path| Some((path, paths::relative_slash_path(mod_root, path)?)))
+        .collect();
+    declarations.sort_by(|a, b| a.1.cmp(&b.1));
+
+    let mut scan = Scan {
+        found: Vec::new(),
+        missing: Vec::new(),
+        failed: Vec::new(),
+    };
+    for (path, relative) in declarations {
+        let document = match fs::read(path)
+            .map_err(|e| e.to_string())
+            .and_then(|data| vdf::parse(&String::from_utf8_lossy(&data)))
+        {
+            Ok(document) => document,
+            Err(e) => {
+                scan.failed.push(format!("{}: {}", relative, e));
+                continue;
+            }
+        };
+        let declaring_dir = relative.rsplit_once('/').map_or("", |(dir, _)| dir);
+        let mut references = Vec::new();
+        collect_references(&document, "", &mut references);
+        for (key, key_path, reference) in references {
+            let resolved = candidates(&key, &reference, declaring_dir)
+                .into_iter()
+                .find_map(|candidate| on_disk.get(&candidate.to_lowercase()).cloned());
+            let resource = UiResource {
+                file: relative.clone(),
+                key: key_path,
+                reference,
+                resolved,
+            };
+            if resource.resolved.is_some() {
+                scan.found.push(resource);
+            } else {
+                scan.missing.push(resource);
+            }
+        }
+    }
+    Ok(scan)
+}
@@ -0,0 +1,171 @@
+// Minimal parser for Valve KeyValues (VDF) text, as used by mod.vdf and
+// Steam's libraryfolders.vdf.
+//
+// Keys are kept in file order and looked up case-insensitively, matching how
+// the engine treats them.
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Str(String),
+    Block(Vec<(String, Value)>),
+}
+
+impl Value {
+    /// First value stored under `key` in a block.
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        self.entries()
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(key))
+            .map(|(_, v)| v)
+    }
+
+    pub fn get_str(&self, key: &str) -> Option<&str> {
+        self.get(key).and_then(Value::as_str)
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::Str(s) => Some(s),
+            Value::Block(_) => None,
+        }
+    }
+
+    pub fn entries(&self) -> &[(String, Value)] {
+        match self {
+            Value::Block(entries) => entries,
+            Value::Str(_) => &[],
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum Token {
+    Str(String),
+    Open,
+    Close,
+}
+
+struct Lexer<'a> {
+    chars: std::iter::Peekable<std::str::Chars<'a>>,
+    line: usize,
+}
+
+impl Lexer<'_> {
+    fn next_token(&mut self) -> Result<Option<Token>, String> {
+        while let Some(&c) = self.chars.peek() {
+            match c {
+                '\n' => {
+                    self.line += 1;
+                    self.chars.next();
+                }
+                c if c.is_whitespace() => {
+                    self.chars.next();
+                }
+                '/' => {
+                    self.chars.next();
+                    if self.chars.peek() != Some(&'/') {
+                        return Err(format!("Unexpected '/' on line {}", self.line));
+                    }
+                    while self.chars.peek().is_some_and(|&c| c != '\n') {
+                        self.chars.next();
+                    }
+                }
+                // Platform conditionals like [$WIN32] are ignored
+                '[' => {
+                    for c in self.chars.by_ref() {
+                        if c == ']' {
+                            break;
+                        }
+                    }
+                }
+                '{' => {
+                    self.chars.next();
+                    return Ok(Some(Token::Open));
+                }
+                '}' => {
+                    self.chars.next();
+                    return Ok(Some(Token::Close));
+                }
+                '"' => {
+                    self.chars.next();
+                    return self.quoted().map(|s| Some(Token::Str(s)));
+                }
+                _ => return Ok(Some(Token::Str(self.unquoted()))),
+            }
+        }
+        Ok(None)
+    }
+
+    fn quoted(&mut self) -> Result<String, String> {
+        let start_line = self.line;
+        let mut value = String::new();
+        while let Some(c) = self.chars.next() {
+            match c {
+                '"' => return Ok(value),
+                '\\' => match self.chars.next() {
+                    Some('n') => value.push('\n'),
+                    Some('t') => value.push('\t'),
+                    Some(other) => value.push(other),
+                    None => break,
+                },
+                '\n' => {
+                    self.line += 1;
+                    value.push(c);
+                }
+                _ => value.push(c),
+            }
+        }
+        Err(format!(
+            "Unterminated string starting on line {}",
+            start_line
+        ))
+    }
+
+    fn unquoted(&mut self) -> String {
+        let mut value = String::new();
+        while let Some(&c) = self.chars.peek() {
+            if c.is_whitespace() || c == '{' || c == '}' || c == '"' {
+                break;
+            }
+            value.push(c);
+            self.chars.next();
+        }
+        value
+    }
+}
+
+/// Parses a whole document into a root block.
+pub fn parse(source: &str) -> Result<Value, String> {
+    let mut lexer = Lexer {
+        chars: source.chars().peekable(),
+        line: 1,
+    };
+    let entries = parse_block(&mut lexer, false)?;
+    Ok(Value::Block(entries))
+}
+
+fn parse_block(lexer: &mut Lexer, nested: bool) -> Result<Vec<(String, Value)>, String> {
+    let mut entries = Vec::new();
+    loop {
+        let key = match lexer.next_token()? {
+            Some(Token::Str(key)) => key,
+            Some(Token::Close) if nested => return Ok(entries),
+            None if !nested => return Ok(entries),
+            Some(Token::Close) => return Err(format!("Unexpected '}}' on line {}", lexer.line)),
+            Some(Token::Open) => return Err(format!("Expected a key on line {}", lexer.line)),
+            None => return Err("Unexpected end of file, missing '}'".to_string()),
+        };
+
+        let value = match lexer.next_token()? {
+            Some(Token::Str(value)) => Value::Str(value),
+            Some(Token::Open) => Value::Block(parse_block(lexer, true)?),
+            _ => {
+                return Err(format!(
+                    "Missing value for \"{}\" on line {}",
+                    key, lexer.line
+                ))
+            }
+        };
+        entries.push((key, value));
+    }
+}
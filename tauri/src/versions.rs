@@ -0,0 +1,133 @@
+// Full save history for projects: before a versioned save overwrites
+// `name.r5vp`, the previous file is archived as
+// `name.versions/<timestamp>.r5vp`, where the timestamp is the archive time
+// in milliseconds and doubles as the version's id.
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const VERSIONS_SUFFIX: &str = ".versions";
+const VERSION_EXTENSION: &str = "r5vp";
+const DAY_MS: u64 = 24 * 60 * 60 * 1000;
+
+#[derive(Debug, Serialize)]
+pub struct ProjectVersion {
+    pub timestamp: u64,
+    // Size of the archived file, as stored
+    pub size: u64,
+}
+
+// Which versions survive pruning. A version is kept if either rule keeps
+// it; with neither set, every version is kept.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct VersionRetention {
+    pub keep_last: Option<usize>,
+    pub keep_days: Option<u64>,
+}
+
+impl VersionRetention {
+    /// Whether an older entry survives pruning, given its position among the
+    /// older entries (newest first) and its age relative to `newest`, the
+    /// timestamp of the entry just added.
+    pub fn keeps(&self, index: usize, timestamp: u64, newest: u64) -> bool {
+        if self.keep_last.is_none() && self.keep_days.is_none() {
+            return true;
+        }
+        let by_count = self.keep_last.is_some_and(|n| index + 1 < n.max(1));
+        let by_age = self
+            .keep_days
+            .is_some_and(|days| timestamp >= newest.saturating_sub(days * DAY_MS));
+        by_count || by_age
+    }
+}
+
+pub fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+fn versions_dir(project: &Path) -> Result<PathBuf, String> {
+    let stem = project
+        .file_stem()
+        .ok_or_else(|| format!("{} is not a file", project.display()))?;
+    let mut name = stem.to_os_string();
+    name.push(VERSIONS_SUFFIX);
+    Ok(project.with_file_name(name))
+}
+
+fn version_path(dir: &Path, timestamp: u64) -> PathBuf {
+    dir.join(format!("{}.{}", timestamp, VERSION_EXTENSION))
+}
+
+/// Versions of `project`, newest first. A project never saved with
+/// versioning has none.
+pub fn list(project: &Path) -> Result<Vec<ProjectVersion>, String> {
+    let dir = versions_dir(project)?;
+    let entries = match fs::read_dir(&dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(format!("Failed to read {}: {}", dir.display(), e)),
+    };
+    let mut versions: Vec<ProjectVersion> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let path = entry.path();
+            if path.extension()? != VERSION_EXTENSION {
+                return None;
+            }
+            let timestamp = path.file_stem()?.to_str()?.parse().ok()?;
+            let size = entry.metadata().ok()?.len();
+            Some(ProjectVersion { timestamp, size })
+        })
+        .collect();
+    versions.sort_by_key(|v| std::cmp::Reverse(v.timestamp));
+    Ok(versions)
+}
+
+/// Stores `data`, the project's previous file, as a new version, then prunes
+/// by `retention`. The new version is always kept.
+pub fn archive(
+    project: &Path,
+    data: &[u8],
+    retention: &VersionRetention,
+) -> Result<ProjectVersion, String> {
+    let dir = versions_dir(project)?;
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+
+    let existing = list(project)?;
+    let mut timestamp = now_ms();
+    // Two saves within the same millisecond still get distinct ids
+    if let Some(latest) = existing.first() {
+        timestamp = timestamp.max(latest.timestamp + 1);
+    }
+    let path = version_path(&dir, timestamp);
+    fs::write(&path, data).map_err(|e| format!("Failed to archive version: {}", e))?;
+
+    // `existing` is newest first and doesn't include the new version
+    for (index, old) in existing.iter().enumerate() {
+        if !retention.keeps(index, old.timestamp, timestamp) {
+            let _ = fs::remove_file(version_path(&dir, old.timestamp));
+        }
+    }
+
+    Ok(ProjectVersion {
+        timestamp,
+        size: data.len() as u64,
+    })
+}
+
+/// The stored bytes of one version, in project file format.
+pub fn read(project: &Path, timestamp: u64) -> Result<Vec<u8>, String> {
+    let path = version_path(&versions_dir(project)?, timestamp);
+    fs::read(&path).map_err(|e| match e.kind() {
+        std::io::ErrorKind::NotFound => {
+            format!("No version {} of {}", timestamp, project.display())
+        }
+        _ => e.to_string(),
+    })
+}
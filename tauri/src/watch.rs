@@ -0,0 +1,96 @@
+// Watching a single file by polling its size and modification time. An
+// editor save can show up as several writes in quick succession (truncate,
+// write, rename), so a change is only reported once the file has stopped
+// changing for the debounce interval.
+
+use crate::operations::CancelToken;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+pub const POLL_INTERVAL: Duration = Duration::from_millis(250);
+pub const DEBOUNCE: Duration = Duration::from_millis(300);
+
+// None while the file doesn't exist, so deleting and recreating it both count
+// as changes
+fn stamp(path: &Path) -> Option<(u64, Option<SystemTime>)> {
+    let metadata = fs::metadata(path).ok()?;
+    Some((metadata.len(), metadata.modified().ok()))
+}
+
+struct Watch {
+    // Label of the window that asked for the watch
+    window: String,
+    token: CancelToken,
+}
+
+/// The running watches, at most one per file.
+#[derive(Default)]
+pub struct Watchers {
+    by_path: Mutex<HashMap<PathBuf, Watch>>,
+}
+
+impl Watchers {
+    /// Records a watch of `path` for `window`, cancelling any earlier watch
+    /// of the same file.
+    pub fn replace(&self, path: &Path, window: &str, token: &CancelToken) {
+        let watch = Watch {
+            window: window.to_string(),
+            token: token.clone(),
+        };
+        let mut by_path = self.by_path.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(previous) = by_path.insert(path.to_path_buf(), watch) {
+            previous.token.cancel();
+        }
+    }
+
+    /// Forgets the watch of `path` once it has stopped, unless another watch
+    /// has replaced it in the meantime.
+    pub fn remove(&self, path: &Path, token: &CancelToken) {
+        let mut by_path = self.by_path.lock().unwrap_or_else(|e| e.into_inner());
+        if by_path.get(path).is_some_and(|w| w.token.same(token)) {
+            by_path.remove(path);
+        }
+    }
+
+    /// Cancels every watch `window` asked for.
+    pub fn cancel_window(&self, window: &str) {
+        let by_path = self.by_path.lock().unwrap_or_else(|e| e.into_inner());
+        for watch in by_path.values().filter(|w| w.window == window) {
+            watch.token.cancel();
+        }
+    }
+}
+
+/// Calls `on_change` once now and then after every settled change to `path`,
+/// until `token` is cancelled. Blocks the calling thread.
+pub fn poll_file(path: &Path, token: &CancelToken, mut on_change: impl FnMut()) {
+    let mut reported = stamp(path);
+    on_change();
+    while !token.is_cancelled() {
+        thread::sleep(POLL_INTERVAL);
+        let mut current = stamp(path);
+        if current == reported {
+            continue;
+        }
+        // Wait for the writes to settle
+        loop {
+            thread::sleep(DEBOUNCE);
+            if token.is_cancelled() {
+                return;
+            }
+            let next = stamp(path);
+            if next == current {
+                break;
+            }
+            current = next;
+        }
+        if current != reported {
+            reported = current;
+            on_change();
+        }
+    }
+}